@@ -0,0 +1,152 @@
+//! Runs under ckb-vm and reports the cycles each newline search takes, for
+//! the word-at-a-time `memchr`/`memrchr` of the crate and the byte-at-a-time
+//! loops they replaced, over the same inputs.
+//!
+//! Each measurement is printed through the `ckb_debug` syscall as
+//! `<function> <implementation> <size> <cycles>`, for the host to collect.
+#![no_std]
+#![no_main]
+
+use core::arch::{asm, global_asm};
+use core::fmt::{self, Write};
+use core::hint::black_box;
+use core::ptr::addr_of_mut;
+
+// Formatted with the crate.
+#[rustfmt::skip]
+#[path = "../../../../src/io/cherry_picking/memchr.rs"]
+mod memchr;
+
+const SYS_EXIT: u64 = 93;
+const SYS_CURRENT_CYCLES: u64 = 2042;
+const SYS_DEBUG: u64 = 2177;
+
+// Witness sizes: a signature, a small lock witness, a typical type script
+// argument blob, and larger proofs.
+const SIZES: [usize; 6] = [65, 256, 1024, 4096, 32 * 1024, 500 * 1024];
+
+type Search = fn(u8, &[u8]) -> Option<usize>;
+
+static mut INPUT: [u8; 500 * 1024] = [0; 500 * 1024];
+
+global_asm!(
+    ".global _start",
+    "_start:",
+    "call main",
+    "li a7, 93",
+    "ecall",
+);
+
+fn syscall(n: u64, a0: u64) -> u64 {
+    let ret;
+    // SAFETY: ckb-vm only reads the registers passed here.
+    unsafe { asm!("ecall", inlateout("a0") a0 => ret, in("a7") n) };
+    ret
+}
+
+fn current_cycles() -> u64 {
+    syscall(SYS_CURRENT_CYCLES, 0)
+}
+
+/// The `memchr` before the word-at-a-time port.
+fn old_memchr(x: u8, text: &[u8]) -> Option<usize> {
+    let mut i = 0;
+    while i < text.len() {
+        if text[i] == x {
+            return Some(i);
+        }
+
+        i += 1;
+    }
+
+    None
+}
+
+/// The `memrchr` before the word-at-a-time port.
+fn old_memrchr(x: u8, text: &[u8]) -> Option<usize> {
+    let mut i = text.len();
+    while i > 0 {
+        i -= 1;
+        if text[i] == x {
+            return Some(i);
+        }
+    }
+    None
+}
+
+/// Formats a line into a fixed buffer and prints it with `ckb_debug`.
+struct Line {
+    buf: [u8; 128],
+    len: usize,
+}
+
+impl Write for Line {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        // Leave room for the NUL terminator.
+        let end = self.len + s.len();
+        if end >= self.buf.len() {
+            return Err(fmt::Error);
+        }
+        self.buf[self.len..end].copy_from_slice(s.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+}
+
+fn report(args: fmt::Arguments<'_>) {
+    let mut line = Line {
+        buf: [0; 128],
+        len: 0,
+    };
+    line.write_fmt(args).unwrap();
+    syscall(SYS_DEBUG, line.buf.as_ptr() as u64);
+}
+
+/// Returns the cycles spent in `search`, without those of reading the
+/// cycle counter.
+#[inline(never)]
+fn measure(search: Search, text: &[u8]) -> u64 {
+    let start = current_cycles();
+    black_box(search(b'\n', black_box(text)));
+    let spent = current_cycles() - start;
+    let start = current_cycles();
+    black_box(none(b'\n', black_box(text)));
+    spent - (current_cycles() - start)
+}
+
+fn none(_: u8, _: &[u8]) -> Option<usize> {
+    None
+}
+
+/// Reports the cycles of the `old` and `new` implementations of `function`.
+fn compare(function: &str, old: Search, new: Search, text: &[u8]) {
+    let size = text.len();
+    report(format_args!("{function} old {size} {}", measure(old, text)));
+    report(format_args!("{function} new {size} {}", measure(new, text)));
+}
+
+#[no_mangle]
+extern "C" fn main() -> i8 {
+    // SAFETY: `main` is the only code touching `INPUT`.
+    let input = unsafe { &mut *addr_of_mut!(INPUT) };
+    for size in SIZES {
+        let text = &mut input[..size];
+
+        // The delimiter is the last byte, so the whole text is scanned.
+        text.fill(0x5a);
+        text[size - 1] = b'\n';
+        compare("memchr", old_memchr, memchr::memchr, text);
+
+        // A newline only at the start makes `memrchr` scan the whole text.
+        text.fill(0x5a);
+        text[0] = b'\n';
+        compare("memrchr", old_memrchr, memchr::memrchr, text);
+    }
+    0
+}
+
+#[panic_handler]
+fn panic(_: &core::panic::PanicInfo<'_>) -> ! {
+    syscall(SYS_EXIT, -1i64 as u64);
+    loop {}
+}
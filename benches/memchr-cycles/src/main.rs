@@ -0,0 +1,153 @@
+//! Measures the newline searches behind `BufRead::skip_until`,
+//! `read_until` and `LineWriter` in CKB cycles.
+//!
+//! Run with `cargo run --release` from this directory. This builds the guest
+//! in `guest/` for `riscv64imac-unknown-none-elf` (add the target with
+//! `rustup target add`), runs it on ckb-vm with CKB's cycle model, and
+//! prints the cycles and the instructions that `memchr` and `memrchr` take,
+//! next to the byte-at-a-time loops the crate used before. Both run over the
+//! same inputs and the delimiter is found last, so the whole input is
+//! scanned.
+use ckb_vm::cost_model::{constant_cycles, estimate_cycles};
+use ckb_vm::registers::{A0, A7};
+use ckb_vm::{
+    Bytes, DefaultCoreMachine, DefaultMachineBuilder, Error, InstructionCycleFunc, Memory,
+    Register, SparseMemory, SupportMachine, Syscalls, ISA_A, ISA_B, ISA_IMC, ISA_MOP,
+};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+
+const TARGET: &str = "riscv64imac-unknown-none-elf";
+
+/// The `ckb_current_cycles` and `ckb_debug` syscalls, collecting what the
+/// guest prints.
+struct Guest {
+    lines: Arc<Mutex<Vec<String>>>,
+}
+
+impl<Mac: SupportMachine> Syscalls<Mac> for Guest {
+    fn initialize(&mut self, _machine: &mut Mac) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn ecall(&mut self, machine: &mut Mac) -> Result<bool, Error> {
+        match machine.registers()[A7].to_u64() {
+            2042 => {
+                let cycles = machine.cycles();
+                machine.set_register(A0, Mac::REG::from_u64(cycles));
+            }
+            2177 => {
+                let mut addr = machine.registers()[A0].to_u64();
+                let mut line = Vec::new();
+                loop {
+                    let byte = machine
+                        .memory_mut()
+                        .load8(&Mac::REG::from_u64(addr))?
+                        .to_u8();
+                    if byte == 0 {
+                        break;
+                    }
+                    line.push(byte);
+                    addr += 1;
+                }
+                let line = String::from_utf8(line).expect("the guest prints UTF-8");
+                self.lines.lock().unwrap().push(line);
+            }
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+}
+
+fn build_guest() -> Bytes {
+    let guest = Path::new(env!("CARGO_MANIFEST_DIR")).join("guest");
+    let status = Command::new(env!("CARGO"))
+        .args(["build", "--release", "--target", TARGET, "--manifest-path"])
+        .arg(guest.join("Cargo.toml"))
+        .status()
+        .expect("failed to run cargo");
+    assert!(status.success(), "failed to build the guest");
+    let elf = guest
+        .join("target")
+        .join(TARGET)
+        .join("release/memchr-cycles-guest");
+    std::fs::read(elf).expect("failed to read the guest").into()
+}
+
+/// Runs the guest, counting each instruction with `cost`, and returns the
+/// count for each `(function, implementation, size)`.
+fn run(program: &Bytes, cost: Box<InstructionCycleFunc>) -> BTreeMap<(String, String, usize), u64> {
+    let lines = Arc::new(Mutex::new(Vec::new()));
+    // The ISA and VM version of CKB's current hard fork.
+    let core = DefaultCoreMachine::<u64, SparseMemory<u64>>::new(
+        ISA_IMC | ISA_A | ISA_B | ISA_MOP,
+        ckb_vm::machine::VERSION2,
+        u64::MAX,
+    );
+    let mut machine = DefaultMachineBuilder::new(core)
+        .instruction_cycle_func(cost)
+        .syscall(Box::new(Guest {
+            lines: lines.clone(),
+        }))
+        .build();
+    machine
+        .load_program(program, std::iter::empty())
+        .expect("failed to load the guest");
+    let exit = machine.run().expect("the guest failed");
+    assert_eq!(exit, 0, "the guest failed");
+
+    let lines = lines.lock().unwrap();
+    lines
+        .iter()
+        .map(|line| {
+            let fields: Vec<&str> = line.split(' ').collect();
+            let [function, implementation, size, count] = fields[..] else {
+                panic!("unexpected line {line:?}");
+            };
+            let key = (
+                function.to_string(),
+                implementation.to_string(),
+                size.parse().unwrap(),
+            );
+            (key, count.parse().unwrap())
+        })
+        .collect()
+}
+
+fn print_table(title: &str, counts: &BTreeMap<(String, String, usize), u64>) {
+    let get = |function: &str, implementation: &str, size: usize| {
+        counts[&(function.to_string(), implementation.to_string(), size)]
+    };
+    let mut sizes: Vec<usize> = counts.keys().map(|(_, _, size)| *size).collect();
+    sizes.sort_unstable();
+    sizes.dedup();
+
+    println!("{title}");
+    println!(
+        "{:>8} {:>12} {:>12} {:>6} {:>12} {:>12} {:>6}",
+        "size", "memchr old", "memchr new", "ratio", "memrchr old", "memrchr new", "ratio"
+    );
+    for size in sizes {
+        let (chr_old, chr_new) = (get("memchr", "old", size), get("memchr", "new", size));
+        let (rchr_old, rchr_new) = (get("memrchr", "old", size), get("memrchr", "new", size));
+        println!(
+            "{:>8} {:>12} {:>12} {:>5.1}x {:>12} {:>12} {:>5.1}x",
+            size,
+            chr_old,
+            chr_new,
+            chr_old as f64 / chr_new as f64,
+            rchr_old,
+            rchr_new,
+            rchr_old as f64 / rchr_new as f64,
+        );
+    }
+    println!();
+}
+
+fn main() {
+    let program = build_guest();
+    print_table("CKB cycles", &run(&program, Box::new(estimate_cycles)));
+    print_table("Instructions", &run(&program, Box::new(constant_cycles)));
+}
@@ -1,22 +1,22 @@
 //! An encapsulation of `BufReader`'s buffer management logic.
 //!
-//! This module factors out the basic functionality of `BufReader` in order to protect two core
-//! invariants:
+//! This module factors out the basic functionality of `BufReader` (for any buffer memory, so also
+//! `ArrayBufReader`) in order to protect two core invariants:
 //! * `filled` bytes of `buf` are always initialized
 //! * `pos` is always <= `filled`
 //! Since this module encapsulates the buffer management logic, we can ensure that the range
 //! `pos..filled` is always a valid index into the initialized region of the buffer. This means
 //! that user code which wants to do reads from a `BufReader` via `buffer` + `consume` can do so
 //! without encountering any runtime bounds checks.
-use alloc::boxed::Box;
-use alloc::vec;
+#[cfg(feature = "alloc")]
+use alloc::{boxed::Box, vec};
 
 use crate::io::{self, BorrowedBuf, Read};
 use core::cmp;
 use core::mem::MaybeUninit;
-pub struct Buffer {
-    // The buffer.
-    buf: Box<[MaybeUninit<u8>]>,
+pub struct Buffer<S> {
+    // The buffer. Either a `Box<[MaybeUninit<u8>]>` or an inline array.
+    buf: S,
     // The current seek offset into `buf`, must always be <= `filled`.
     pos: usize,
     // Each call to `fill_buf` sets `filled` to indicate how many bytes at the start of `buf` are
@@ -30,10 +30,20 @@ pub struct Buffer {
     initialized: usize,
 }
 
-impl Buffer {
+#[cfg(feature = "alloc")]
+impl Buffer<Box<[MaybeUninit<u8>]>> {
     #[inline]
     pub fn with_capacity(capacity: usize) -> Self {
-        let buf = vec![MaybeUninit::uninit(); capacity].into_boxed_slice();
+        Self::new(vec![MaybeUninit::uninit(); capacity].into_boxed_slice())
+    }
+}
+
+impl<S> Buffer<S>
+where
+    S: AsRef<[MaybeUninit<u8>]> + AsMut<[MaybeUninit<u8>]>,
+{
+    #[inline]
+    pub const fn new(buf: S) -> Self {
         Self {
             buf,
             pos: 0,
@@ -41,20 +51,21 @@ impl Buffer {
             initialized: 0,
         }
     }
+
     #[inline]
     pub fn buffer(&self) -> &[u8] {
         // SAFETY: self.pos and self.cap are valid, and self.cap => self.pos, and
         // that region is initialized because those are all invariants of this type.
         unsafe {
             core::slice::from_raw_parts(
-                self.buf.as_ptr().add(self.pos) as *const u8,
+                self.buf.as_ref().as_ptr().add(self.pos) as *const u8,
                 self.filled - self.pos,
             )
         }
     }
     #[inline]
     pub fn capacity(&self) -> usize {
-        self.buf.len()
+        self.buf.as_ref().len()
     }
 
     #[inline]
@@ -115,7 +126,7 @@ impl Buffer {
         if self.pos >= self.filled {
             debug_assert!(self.pos == self.filled);
 
-            let mut buf = BorrowedBuf::from(&mut *self.buf);
+            let mut buf = BorrowedBuf::from(self.buf.as_mut());
             // SAFETY: `self.filled` bytes will always have been initialized.
             unsafe {
                 buf.set_init(self.initialized);
@@ -129,4 +140,46 @@ impl Buffer {
         }
         Ok(self.buffer())
     }
+
+    /// Reads until at least `n` bytes are buffered or the reader reaches EOF,
+    /// moving the buffered bytes to the front of the buffer if there is not
+    /// enough room after them. `n` must not exceed the capacity.
+    pub fn fill_to(&mut self, n: usize, mut reader: impl Read) -> io::Result<&[u8]> {
+        debug_assert!(n <= self.capacity());
+        while self.filled - self.pos < n {
+            if self.capacity() - self.pos < n {
+                self.backshift();
+            }
+            match self.read_more(&mut reader) {
+                Ok(0) => break,
+                Ok(_) => {}
+                Err(e) if e.is_interrupted() => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(self.buffer())
+    }
+
+    /// Reads more bytes into the buffer without discarding any of its
+    /// contents.
+    fn read_more(&mut self, mut reader: impl Read) -> io::Result<usize> {
+        let mut buf = BorrowedBuf::from(&mut self.buf.as_mut()[self.filled..]);
+        let old_init = self.initialized - self.filled;
+        // SAFETY: `self.initialized` bytes of the whole buffer are
+        // initialized, so `old_init` bytes after `self.filled` are.
+        unsafe {
+            buf.set_init(old_init);
+        }
+        reader.read_buf(buf.unfilled())?;
+        self.filled += buf.len();
+        self.initialized += buf.init_len() - old_init;
+        Ok(buf.len())
+    }
+
+    /// Moves the unconsumed bytes to the front of the buffer.
+    fn backshift(&mut self) {
+        self.buf.as_mut().copy_within(self.pos..self.filled, 0);
+        self.filled -= self.pos;
+        self.pos = 0;
+    }
 }
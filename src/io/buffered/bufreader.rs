@@ -1,10 +1,15 @@
-mod buffer;
+use super::Buffer;
+#[cfg(feature = "alloc")]
+use crate::io::DEFAULT_BUF_SIZE;
 use crate::io::{
-    self, uninlined_slow_read_byte, BorrowedCursor, BufRead, Read, Seek, SeekFrom, SizeHint,
-    SpecReadByte, DEFAULT_BUF_SIZE,
+    self, uninlined_slow_read_byte, BorrowedCursor, BufRead, IoSliceMut, Read, Seek, SeekFrom,
+    SizeHint, SpecReadByte,
 };
-use alloc::{fmt, string::String, vec::Vec};
-use buffer::Buffer;
+#[cfg(feature = "alloc")]
+use alloc::{boxed::Box, string::String, vec::Vec};
+use core::cmp;
+use core::fmt;
+use core::mem::MaybeUninit;
 
 /// The `BufReader<R>` struct adds buffering to any reader.
 ///
@@ -45,14 +50,51 @@ use buffer::Buffer;
 ///     Ok(())
 /// }
 /// ```
-pub struct BufReader<R: ?Sized> {
-    buf: Buffer,
+#[cfg(feature = "alloc")]
+pub type BufReader<R> = GenericBufReader<R, Box<[MaybeUninit<u8>]>>;
+
+/// A [`BufReader`] whose buffer is an inline `[u8; N]` instead of a heap
+/// allocation.
+///
+/// `ArrayBufReader` behaves exactly like a `BufReader` created with
+/// `BufReader::with_capacity(N, inner)`, since both are the same
+/// [`GenericBufReader`] with a different buffer. It is available without the
+/// `alloc` feature, and its memory use is fixed at compile time.
+///
+/// [`BufReader`]: crate::io::BufReader
+///
+/// # Examples
+///
+/// ```no_run
+/// use ckb_rust_std::io::{self, ArrayBufReader, BufRead};
+///
+/// fn main() -> io::Result<()> {
+///     let data: &[u8] = b"hello\nworld\n";
+///     let mut reader = ArrayBufReader::<_, 64>::new(data);
+///     let line = reader.fill_buf()?;
+///     assert_eq!(line, b"hello\nworld\n");
+///     Ok(())
+/// }
+/// ```
+pub type ArrayBufReader<R, const N: usize> = GenericBufReader<R, [MaybeUninit<u8>; N]>;
+
+/// A buffered reader over any buffer memory `S`.
+///
+/// This is the type behind [`BufReader`], which buffers into a heap
+/// allocation, and [`ArrayBufReader`], which buffers into an inline array.
+/// Use [`with_storage`](GenericBufReader::with_storage) to buffer into some
+/// other memory, e.g. a `&mut [MaybeUninit<u8>]` borrowed from the caller.
+///
+/// [`BufReader`]: crate::io::BufReader
+pub struct GenericBufReader<R: ?Sized, S> {
+    buf: Buffer<S>,
     inner: R,
 }
 
+#[cfg(feature = "alloc")]
 impl<R: Read> BufReader<R> {
-    /// Creates a new `BufReader<R>` with a default buffer capacity. The default is currently 8 KiB,
-    /// but may change in the future.
+    /// Creates a new `BufReader<R>` with a default buffer capacity. The default is currently 1 KiB,
+    /// and can be changed at build time with the `CKB_RUST_STD_BUF_SIZE` environment variable.
     ///
     /// # Examples
     ///
@@ -94,7 +136,25 @@ impl<R: Read> BufReader<R> {
     }
 }
 
-impl<R: ?Sized> BufReader<R> {
+impl<R: Read, const N: usize> ArrayBufReader<R, N> {
+    /// Creates a new `ArrayBufReader<R, N>` with a buffer of `N` bytes.
+    pub fn new(inner: R) -> ArrayBufReader<R, N> {
+        GenericBufReader::with_storage([MaybeUninit::uninit(); N], inner)
+    }
+}
+
+impl<R: Read, S: AsRef<[MaybeUninit<u8>]> + AsMut<[MaybeUninit<u8>]>> GenericBufReader<R, S> {
+    /// Creates a new `GenericBufReader` that buffers into `storage`. The
+    /// capacity is the length of `storage`.
+    pub fn with_storage(storage: S, inner: R) -> GenericBufReader<R, S> {
+        GenericBufReader {
+            buf: Buffer::new(storage),
+            inner,
+        }
+    }
+}
+
+impl<R: ?Sized, S: AsRef<[MaybeUninit<u8>]> + AsMut<[MaybeUninit<u8>]>> GenericBufReader<R, S> {
     /// Gets a reference to the underlying reader.
     ///
     /// It is inadvisable to directly read from the underlying reader.
@@ -223,13 +283,15 @@ impl<R: ?Sized> BufReader<R> {
 
 // This is only used by a test which asserts that the initialization-tracking is correct.
 #[cfg(test)]
-impl<R: ?Sized> BufReader<R> {
+impl<R: ?Sized, S: AsRef<[MaybeUninit<u8>]> + AsMut<[MaybeUninit<u8>]>> GenericBufReader<R, S> {
     pub fn initialized(&self) -> usize {
         self.buf.initialized()
     }
 }
 
-impl<R: ?Sized + Seek> BufReader<R> {
+impl<R: ?Sized + Seek, S: AsRef<[MaybeUninit<u8>]> + AsMut<[MaybeUninit<u8>]>>
+    GenericBufReader<R, S>
+{
     /// Seeks relative to the current position. If the new position lies within the buffer,
     /// the buffer will not be flushed, allowing for more efficient seeks.
     /// This method does not return the location of the underlying reader, so the caller
@@ -252,9 +314,10 @@ impl<R: ?Sized + Seek> BufReader<R> {
     }
 }
 
-impl<R> SpecReadByte for BufReader<R>
+impl<R, S> SpecReadByte for GenericBufReader<R, S>
 where
     Self: Read,
+    S: AsRef<[MaybeUninit<u8>]> + AsMut<[MaybeUninit<u8>]>,
 {
     #[inline]
     fn spec_read_byte(&mut self) -> Option<io::Result<u8>> {
@@ -267,7 +330,9 @@ where
         uninlined_slow_read_byte(self)
     }
 }
-impl<R: ?Sized + Read> Read for BufReader<R> {
+impl<R: ?Sized + Read, S: AsRef<[MaybeUninit<u8>]> + AsMut<[MaybeUninit<u8>]>> Read
+    for GenericBufReader<R, S>
+{
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
         // If we don't have any buffered data and we're doing a massive read
         // (larger than our internal buffer), bypass our internal buffer
@@ -325,8 +390,27 @@ impl<R: ?Sized + Read> Read for BufReader<R> {
 
         crate::io::default_read_buf_exact(self, cursor)
     }
+
+    fn read_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
+        let total_len = bufs.iter().map(|b| b.len()).sum::<usize>();
+        if self.buf.pos() == self.buf.filled() && total_len >= self.capacity() {
+            self.discard_buffer();
+            return self.inner.read_vectored(bufs);
+        }
+        let mut rem = self.fill_buf()?;
+        let nread = rem.read_vectored(bufs)?;
+
+        self.consume(nread);
+        Ok(nread)
+    }
+
+    fn is_read_vectored(&self) -> bool {
+        self.inner.is_read_vectored()
+    }
+
     // The inner reader might have an optimized `read_to_end`. Drain our buffer and then
     // delegate to the inner implementation.
+    #[cfg(feature = "alloc")]
     fn read_to_end(&mut self, buf: &mut Vec<u8>) -> io::Result<usize> {
         let inner_buf = self.buffer();
         buf.try_reserve(inner_buf.len())?;
@@ -338,6 +422,7 @@ impl<R: ?Sized + Read> Read for BufReader<R> {
 
     // The inner reader might have an optimized `read_to_end`. Drain our buffer and then
     // delegate to the inner implementation.
+    #[cfg(feature = "alloc")]
     fn read_to_string(&mut self, buf: &mut String) -> io::Result<usize> {
         // In the general `else` case below we must read bytes into a side buffer, check
         // that they are valid UTF-8, and then append them to `buf`. This requires a
@@ -362,24 +447,69 @@ impl<R: ?Sized + Read> Read for BufReader<R> {
             let mut bytes = Vec::new();
             self.read_to_end(&mut bytes)?;
             let string =
-                alloc::str::from_utf8(&bytes).map_err(|_| crate::io::Error::INVALID_UTF8)?;
+                core::str::from_utf8(&bytes).map_err(|_| crate::io::Error::INVALID_UTF8)?;
             *buf += string;
             Ok(string.len())
         }
     }
 }
-impl<R: ?Sized + Read> BufRead for BufReader<R> {
+impl<R: ?Sized + Read, S: AsRef<[MaybeUninit<u8>]> + AsMut<[MaybeUninit<u8>]>>
+    GenericBufReader<R, S>
+{
+    /// Returns the next `n` bytes without consuming them, reading from the
+    /// underlying reader until that many bytes are buffered.
+    ///
+    /// Fewer than `n` bytes are returned only if the reader reaches EOF
+    /// first. Buffered data is moved to the front of the buffer if there is
+    /// not enough room after it, so the bytes are always contiguous.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is larger than the [capacity](BufReader::capacity) of
+    /// the buffer.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use ckb_rust_std::io::{self, ckb::{self, Source}, BufReader};
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let mut reader = BufReader::new(ckb::witness(0, Source::GroupInput)?);
+    ///     // Look at a molecule header without consuming it.
+    ///     let header = reader.peek(4)?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn peek(&mut self, n: usize) -> io::Result<&[u8]> {
+        assert!(
+            n <= self.capacity(),
+            "peek size exceeds the buffer capacity"
+        );
+        let buf = self.buf.fill_to(n, &mut self.inner)?;
+        Ok(&buf[..cmp::min(n, buf.len())])
+    }
+}
+
+impl<R: ?Sized + Read, S: AsRef<[MaybeUninit<u8>]> + AsMut<[MaybeUninit<u8>]>> BufRead
+    for GenericBufReader<R, S>
+{
     fn fill_buf(&mut self) -> io::Result<&[u8]> {
         self.buf.fill_buf(&mut self.inner)
     }
 
+    fn fill_at_least(&mut self, n: usize) -> io::Result<&[u8]> {
+        let n = cmp::min(n, self.capacity());
+        self.buf.fill_to(n, &mut self.inner)
+    }
+
     fn consume(&mut self, amt: usize) {
         self.buf.consume(amt)
     }
 }
-impl<R> fmt::Debug for BufReader<R>
+impl<R, S> fmt::Debug for GenericBufReader<R, S>
 where
     R: ?Sized + fmt::Debug,
+    S: AsRef<[MaybeUninit<u8>]> + AsMut<[MaybeUninit<u8>]>,
 {
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
         fmt.debug_struct("BufReader")
@@ -391,7 +521,9 @@ where
             .finish()
     }
 }
-impl<R: ?Sized + Seek> Seek for BufReader<R> {
+impl<R: ?Sized + Seek, S: AsRef<[MaybeUninit<u8>]> + AsMut<[MaybeUninit<u8>]>> Seek
+    for GenericBufReader<R, S>
+{
     /// Seek to an offset, in bytes, in the underlying reader.
     ///
     /// The position used for seeking with <code>[SeekFrom::Current]\(_)</code> is the
@@ -493,7 +625,9 @@ impl<R: ?Sized + Seek> Seek for BufReader<R> {
         self.seek_relative(offset)
     }
 }
-impl<T: ?Sized + SizeHint> SizeHint for BufReader<T> {
+impl<T: ?Sized + SizeHint, S: AsRef<[MaybeUninit<u8>]> + AsMut<[MaybeUninit<u8>]>> SizeHint
+    for GenericBufReader<T, S>
+{
     #[inline]
     fn lower_bound(&self) -> usize {
         SizeHint::lower_bound(self.get_ref()) + self.buffer().len()
@@ -1,10 +1,10 @@
-use crate::io::{
-    self, error::core_error as error, error::ErrorKind, IntoInnerError, Seek, SeekFrom, Write,
-    DEFAULT_BUF_SIZE,
-};
-use alloc::fmt;
-use alloc::vec::Vec;
-use core::mem::{self, ManuallyDrop};
+use crate::io::{self, const_io_error, ErrorKind, IntoInnerError, IoSlice, Seek, SeekFrom, Write};
+#[cfg(feature = "alloc")]
+use crate::io::{error::core_error as error, DEFAULT_BUF_SIZE};
+#[cfg(feature = "alloc")]
+use alloc::{boxed::Box, vec, vec::Vec};
+use core::fmt;
+use core::mem::{ManuallyDrop, MaybeUninit};
 use core::ptr;
 /// Wraps a writer and buffers its output.
 ///
@@ -64,13 +64,57 @@ use core::ptr;
 ///
 /// [`TcpStream::write`]: crate::net::TcpStream::write
 /// [`TcpStream`]: crate::net::TcpStream
-/// [`flush`]: BufWriter::flush
-pub struct BufWriter<W: ?Sized + Write> {
-    // The buffer. Avoid using this like a normal `Vec` in common code paths.
-    // That is, don't use `buf.push`, `buf.extend_from_slice`, or any other
-    // methods that require bounds checking or the like. This makes an enormous
-    // difference to performance (we may want to stop using a `Vec` entirely).
-    buf: Vec<u8>,
+/// [`flush`]: Write::flush
+#[cfg(feature = "alloc")]
+pub type BufWriter<W> = GenericBufWriter<W, Box<[MaybeUninit<u8>]>>;
+
+/// A [`BufWriter`] whose buffer is an inline `[u8; N]` instead of a heap
+/// allocation.
+///
+/// `ArrayBufWriter` behaves exactly like a `BufWriter` created with
+/// `BufWriter::with_capacity(N, inner)`, since both are the same
+/// [`GenericBufWriter`] with a different buffer: writes smaller than the
+/// buffer are collected and handed to the inner writer in one go, larger
+/// writes bypass it, and the buffer is flushed on [`flush`], [`seek`],
+/// [`into_inner`] and drop. It is available without the `alloc` feature, and
+/// its memory use is fixed at compile time.
+///
+/// [`BufWriter`]: crate::io::BufWriter
+/// [`flush`]: Write::flush
+/// [`seek`]: Seek::seek
+/// [`into_inner`]: GenericBufWriter::into_inner
+///
+/// # Examples
+///
+/// ```no_run
+/// use ckb_rust_std::io::{self, ArrayBufWriter, Write};
+///
+/// fn main() -> io::Result<()> {
+///     let mut out = [0u8; 16];
+///     let mut writer = ArrayBufWriter::<_, 8>::new(&mut out[..]);
+///     writer.write_all(b"hello")?;
+///     writer.flush()?;
+///     Ok(())
+/// }
+/// ```
+pub type ArrayBufWriter<W, const N: usize> = GenericBufWriter<W, [MaybeUninit<u8>; N]>;
+
+/// A buffered writer over any buffer memory `S`.
+///
+/// This is the type behind [`BufWriter`], which buffers into a heap
+/// allocation, and [`ArrayBufWriter`], which buffers into an inline array.
+/// Use [`with_storage`](GenericBufWriter::with_storage) to buffer into some
+/// other memory, e.g. a `&mut [MaybeUninit<u8>]` borrowed from the caller.
+///
+/// [`BufWriter`]: crate::io::BufWriter
+pub struct GenericBufWriter<
+    W: ?Sized + Write,
+    S: AsRef<[MaybeUninit<u8>]> + AsMut<[MaybeUninit<u8>]>,
+> {
+    // The buffer, of which the first `len` bytes are initialized and waiting
+    // to be written. Its length is the capacity.
+    buf: S,
+    len: usize,
     // #30888: If the inner writer panics in a call to write, we don't want to
     // write the buffered data a second time in BufWriter's destructor. This
     // flag tells the Drop impl if it should skip the flush.
@@ -78,9 +122,10 @@ pub struct BufWriter<W: ?Sized + Write> {
     inner: W,
 }
 
+#[cfg(feature = "alloc")]
 impl<W: Write> BufWriter<W> {
-    /// Creates a new `BufWriter<W>` with a default buffer capacity. The default is currently 8 KiB,
-    /// but may change in the future.
+    /// Creates a new `BufWriter<W>` with a default buffer capacity. The default is currently 1 KiB,
+    /// and can be changed at build time with the `CKB_RUST_STD_BUF_SIZE` environment variable.
     ///
     /// # Examples
     ///
@@ -108,37 +153,8 @@ impl<W: Write> BufWriter<W> {
     /// let mut buffer = BufWriter::with_capacity(100, stream);
     /// ```
     pub fn with_capacity(capacity: usize, inner: W) -> BufWriter<W> {
-        BufWriter {
-            inner,
-            buf: Vec::with_capacity(capacity),
-            panicked: false,
-        }
-    }
-
-    /// Unwraps this `BufWriter<W>`, returning the underlying writer.
-    ///
-    /// The buffer is written out before returning the writer.
-    ///
-    /// # Errors
-    ///
-    /// An [`Err`] will be returned if an error occurs while flushing the buffer.
-    ///
-    /// # Examples
-    ///
-    /// ```no_run
-    /// use std::io::BufWriter;
-    /// use std::net::TcpStream;
-    ///
-    /// let mut buffer = BufWriter::new(TcpStream::connect("127.0.0.1:34254").unwrap());
-    ///
-    /// // unwrap the TcpStream and flush the buffer
-    /// let stream = buffer.into_inner().unwrap();
-    /// ```
-    pub fn into_inner(mut self) -> Result<W, IntoInnerError<BufWriter<W>>> {
-        match self.flush_buf() {
-            Err(e) => Err(IntoInnerError::new(self, e)),
-            Ok(()) => Ok(self.into_parts().0),
-        }
+        let buf = vec![MaybeUninit::uninit(); capacity].into_boxed_slice();
+        GenericBufWriter::with_storage(buf, inner)
     }
 
     /// Disassembles this `BufWriter<W>`, returning the underlying writer, and any buffered but
@@ -164,22 +180,85 @@ impl<W: Write> BufWriter<W> {
     /// assert_eq!(&buffered_data.unwrap(), b"ata");
     /// ```
     pub fn into_parts(self) -> (W, Result<Vec<u8>, WriterPanicked>) {
-        let mut this = ManuallyDrop::new(self);
-        let buf = mem::take(&mut this.buf);
-        let buf = if !this.panicked {
+        let (inner, buf, len, panicked) = self.into_raw_parts();
+        let mut buf = ManuallyDrop::new(Vec::from(buf));
+        // SAFETY: the first `len` bytes of the buffer are initialized, and
+        // `MaybeUninit<u8>` has the same layout as `u8`. The allocation is
+        // owned by the new vector, since `buf` is never dropped.
+        let buf = unsafe { Vec::from_raw_parts(buf.as_mut_ptr() as *mut u8, len, buf.capacity()) };
+        let buf = if !panicked {
             Ok(buf)
         } else {
             Err(WriterPanicked { buf })
         };
+        (inner, buf)
+    }
+}
 
-        // SAFETY: double-drops are prevented by putting `this` in a ManuallyDrop that is never dropped
-        let inner = unsafe { ptr::read(&this.inner) };
+impl<W: Write, const N: usize> ArrayBufWriter<W, N> {
+    /// Creates a new `ArrayBufWriter<W, N>` with a buffer of `N` bytes.
+    pub fn new(inner: W) -> ArrayBufWriter<W, N> {
+        GenericBufWriter::with_storage([MaybeUninit::uninit(); N], inner)
+    }
+}
 
-        (inner, buf)
+impl<W: Write, S: AsRef<[MaybeUninit<u8>]> + AsMut<[MaybeUninit<u8>]>> GenericBufWriter<W, S> {
+    /// Creates a new `GenericBufWriter` that buffers into `storage`. The
+    /// capacity is the length of `storage`.
+    pub fn with_storage(storage: S, inner: W) -> GenericBufWriter<W, S> {
+        GenericBufWriter {
+            buf: storage,
+            len: 0,
+            panicked: false,
+            inner,
+        }
+    }
+
+    /// Unwraps this `BufWriter<W>`, returning the underlying writer.
+    ///
+    /// The buffer is written out before returning the writer.
+    ///
+    /// # Errors
+    ///
+    /// An [`Err`] will be returned if an error occurs while flushing the buffer.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::io::BufWriter;
+    /// use std::net::TcpStream;
+    ///
+    /// let mut buffer = BufWriter::new(TcpStream::connect("127.0.0.1:34254").unwrap());
+    ///
+    /// // unwrap the TcpStream and flush the buffer
+    /// let stream = buffer.into_inner().unwrap();
+    /// ```
+    pub fn into_inner(mut self) -> Result<W, IntoInnerError<GenericBufWriter<W, S>>> {
+        match self.flush_buf() {
+            Err(e) => Err(IntoInnerError::new(self, e)),
+            Ok(()) => Ok(self.into_raw_parts().0),
+        }
+    }
+
+    /// Takes the writer apart without flushing, returning the inner writer,
+    /// the buffer, the length of its initialized prefix and the panicked flag.
+    fn into_raw_parts(self) -> (W, S, usize, bool) {
+        let this = ManuallyDrop::new(self);
+        // SAFETY: double-drops are prevented by putting `this` in a ManuallyDrop that is never dropped
+        unsafe {
+            (
+                ptr::read(&this.inner),
+                ptr::read(&this.buf),
+                this.len,
+                this.panicked,
+            )
+        }
     }
 }
 
-impl<W: ?Sized + Write> BufWriter<W> {
+impl<W: ?Sized + Write, S: AsRef<[MaybeUninit<u8>]> + AsMut<[MaybeUninit<u8>]>>
+    GenericBufWriter<W, S>
+{
     /// Send data in our local buffer into the inner writer, looping as
     /// necessary until either it's all been sent or an error occurs.
     ///
@@ -192,18 +271,17 @@ impl<W: ?Sized + Write> BufWriter<W> {
         /// are complete. It tracks the number of written bytes and drains them
         /// all from the front of the buffer when dropped.
         struct BufGuard<'a> {
-            buffer: &'a mut Vec<u8>,
+            buffer: &'a mut [MaybeUninit<u8>],
+            len: &'a mut usize,
             written: usize,
         }
 
-        impl<'a> BufGuard<'a> {
-            fn new(buffer: &'a mut Vec<u8>) -> Self {
-                Self { buffer, written: 0 }
-            }
-
+        impl BufGuard<'_> {
             /// The unwritten part of the buffer
             fn remaining(&self) -> &[u8] {
-                &self.buffer[self.written..]
+                let remaining = &self.buffer[self.written..*self.len];
+                // SAFETY: the first `len` bytes of the buffer are initialized.
+                unsafe { &*(remaining as *const [MaybeUninit<u8>] as *const [u8]) }
             }
 
             /// Flag some bytes as removed from the front of the buffer
@@ -213,19 +291,24 @@ impl<W: ?Sized + Write> BufWriter<W> {
 
             /// true if all of the bytes have been written
             fn done(&self) -> bool {
-                self.written >= self.buffer.len()
+                self.written >= *self.len
             }
         }
 
         impl Drop for BufGuard<'_> {
             fn drop(&mut self) {
                 if self.written > 0 {
-                    self.buffer.drain(..self.written);
+                    self.buffer.copy_within(self.written..*self.len, 0);
+                    *self.len -= self.written;
                 }
             }
         }
 
-        let mut guard = BufGuard::new(&mut self.buf);
+        let mut guard = BufGuard {
+            buffer: self.buf.as_mut(),
+            len: &mut self.len,
+            written: 0,
+        };
         while !guard.done() {
             self.panicked = true;
             let r = self.inner.write(guard.remaining());
@@ -233,7 +316,7 @@ impl<W: ?Sized + Write> BufWriter<W> {
 
             match r {
                 Ok(0) => {
-                    return Err(io::const_io_error!(
+                    return Err(const_io_error!(
                         ErrorKind::WriteZero,
                         "failed to write the buffered data",
                     ));
@@ -249,6 +332,7 @@ impl<W: ?Sized + Write> BufWriter<W> {
     /// Buffer some data without flushing it, regardless of the size of the
     /// data. Writes as much as possible without exceeding capacity. Returns
     /// the number of bytes written.
+    #[cfg(feature = "alloc")]
     pub(super) fn write_to_buf(&mut self, buf: &[u8]) -> usize {
         let available = self.spare_capacity();
         let amt_to_buffer = available.min(buf.len());
@@ -311,20 +395,29 @@ impl<W: ?Sized + Write> BufWriter<W> {
     /// let bytes_buffered = buf_writer.buffer().len();
     /// ```
     pub fn buffer(&self) -> &[u8] {
-        &self.buf
+        // SAFETY: the first `len` bytes of the buffer are initialized.
+        unsafe { core::slice::from_raw_parts(self.buf.as_ref().as_ptr() as *const u8, self.len) }
     }
 
-    /// Returns a mutable reference to the internal buffer.
+    /// Returns the unused part of the internal buffer.
     ///
     /// This can be used to write data directly into the buffer without triggering writers
-    /// to the underlying writer.
-    ///
-    /// That the buffer is a `Vec` is an implementation detail.
-    /// Callers should not modify the capacity as there currently is no public API to do so
-    /// and thus any capacity changes would be unexpected by the user.
-    #[allow(dead_code)]
-    pub(in crate::io) fn buffer_mut(&mut self) -> &mut Vec<u8> {
-        &mut self.buf
+    /// to the underlying writer. Call [`advance`](Self::advance) to add the written bytes to
+    /// the buffered data.
+    pub(in crate::io) fn spare_capacity_mut(&mut self) -> &mut [MaybeUninit<u8>] {
+        let len = self.len;
+        &mut self.buf.as_mut()[len..]
+    }
+
+    /// Adds `n` bytes written to [`spare_capacity_mut`](Self::spare_capacity_mut) to the
+    /// buffered data.
+    ///
+    /// # Safety
+    ///
+    /// The first `n` bytes of the spare capacity must have been initialized.
+    pub(in crate::io) unsafe fn advance(&mut self, n: usize) {
+        debug_assert!(n <= self.spare_capacity());
+        self.len += n;
     }
 
     /// Returns the number of bytes the internal buffer can hold without flushing.
@@ -343,7 +436,7 @@ impl<W: ?Sized + Write> BufWriter<W> {
     /// let without_flush = capacity - buf_writer.buffer().len();
     /// ```
     pub fn capacity(&self) -> usize {
-        self.buf.capacity()
+        self.buf.as_ref().len()
     }
 
     // Ensure this function does not get inlined into `write`, so that it
@@ -360,7 +453,7 @@ impl<W: ?Sized + Write> BufWriter<W> {
 
         // Why not len > capacity? To avoid a needless trip through the buffer when the input
         // exactly fills it. We'd just need to flush it to the underlying writer anyway.
-        if buf.len() >= self.buf.capacity() {
+        if buf.len() >= self.capacity() {
             self.panicked = true;
             let r = self.get_mut().write(buf);
             self.panicked = false;
@@ -402,7 +495,7 @@ impl<W: ?Sized + Write> BufWriter<W> {
 
         // Why not len > capacity? To avoid a needless trip through the buffer when the input
         // exactly fills it. We'd just need to flush it to the underlying writer anyway.
-        if buf.len() >= self.buf.capacity() {
+        if buf.len() >= self.capacity() {
             self.panicked = true;
             let r = self.get_mut().write_all(buf);
             self.panicked = false;
@@ -425,24 +518,24 @@ impl<W: ?Sized + Write> BufWriter<W> {
         }
     }
 
-    // SAFETY: Requires `buf.len() <= self.buf.capacity() - self.buf.len()`,
+    // SAFETY: Requires `buf.len() <= self.capacity() - self.len`,
     // i.e., that input buffer length is less than or equal to spare capacity.
     #[inline]
     unsafe fn write_to_buffer_unchecked(&mut self, buf: &[u8]) {
         debug_assert!(buf.len() <= self.spare_capacity());
-        let old_len = self.buf.len();
+        let old_len = self.len;
         let buf_len = buf.len();
         let src = buf.as_ptr();
         unsafe {
-            let dst = self.buf.as_mut_ptr().add(old_len);
+            let dst = self.buf.as_mut().as_mut_ptr().add(old_len) as *mut u8;
             ptr::copy_nonoverlapping(src, dst, buf_len);
-            self.buf.set_len(old_len + buf_len);
         }
+        self.len = old_len + buf_len;
     }
 
     #[inline]
     fn spare_capacity(&self) -> usize {
-        self.buf.capacity() - self.buf.len()
+        self.capacity() - self.len
     }
 }
 
@@ -471,10 +564,12 @@ impl<W: ?Sized + Write> BufWriter<W> {
 /// assert!(matches!(recovered_writer, PanickingWriter));
 /// assert_eq!(buffered_data.unwrap_err().into_inner(), b"some data");
 /// ```
+#[cfg(feature = "alloc")]
 pub struct WriterPanicked {
     buf: Vec<u8>,
 }
 
+#[cfg(feature = "alloc")]
 impl WriterPanicked {
     /// Returns the perhaps-unwritten data.  Some of this data may have been written by the
     /// panicking call(s) to the underlying writer, so simply writing it again is not a good idea.
@@ -486,17 +581,20 @@ impl WriterPanicked {
     const DESCRIPTION: &'static str =
         "BufWriter inner writer panicked, what data remains unwritten is not known";
 }
+#[cfg(feature = "alloc")]
 impl error::Error for WriterPanicked {
     #[allow(deprecated, deprecated_in_future)]
     fn description(&self) -> &str {
         Self::DESCRIPTION
     }
 }
+#[cfg(feature = "alloc")]
 impl fmt::Display for WriterPanicked {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", Self::DESCRIPTION)
     }
 }
+#[cfg(feature = "alloc")]
 impl fmt::Debug for WriterPanicked {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("WriterPanicked")
@@ -507,7 +605,9 @@ impl fmt::Debug for WriterPanicked {
             .finish()
     }
 }
-impl<W: ?Sized + Write> Write for BufWriter<W> {
+impl<W: ?Sized + Write, S: AsRef<[MaybeUninit<u8>]> + AsMut<[MaybeUninit<u8>]>> Write
+    for GenericBufWriter<W, S>
+{
     #[inline]
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
         // Use < instead of <= to avoid a needless trip through the buffer in some cases.
@@ -539,6 +639,96 @@ impl<W: ?Sized + Write> Write for BufWriter<W> {
             self.write_all_cold(buf)
         }
     }
+
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        // FIXME: Consider applying `#[inline]` / `#[inline(never)]` optimizations already applied
+        // to `write` and `write_all`. The performance benefits can be significant. See #79930.
+        if self.get_ref().is_write_vectored() {
+            // We have to handle the possibility that the total length of the buffers overflows
+            // `usize` (even though this can only happen if multiple `IoSlice`s reference the
+            // same underlying buffer, as otherwise the buffers wouldn't fit in memory). If the
+            // computation overflows, then surely the input cannot fit in our buffer, so we forward
+            // to the inner writer's `write_vectored` method to let it handle it appropriately.
+            let mut saturated_total_len: usize = 0;
+
+            for buf in bufs {
+                saturated_total_len = saturated_total_len.saturating_add(buf.len());
+
+                if saturated_total_len > self.spare_capacity() && self.len != 0 {
+                    // Flush if the total length of the input exceeds our buffer's spare capacity.
+                    // If we would have overflowed, this condition also holds, and we need to flush.
+                    self.flush_buf()?;
+                }
+
+                if saturated_total_len >= self.capacity() {
+                    // Forward to our inner writer if the total length of the input is greater than or
+                    // equal to our buffer capacity. If we would have overflowed, this condition also
+                    // holds, and we punt to the inner writer.
+                    self.panicked = true;
+                    let r = self.get_mut().write_vectored(bufs);
+                    self.panicked = false;
+                    return r;
+                }
+            }
+
+            // `saturated_total_len < self.capacity()` implies that we did not saturate.
+
+            // SAFETY: We checked whether or not the spare capacity was large enough above. If
+            // it was, then we're safe already. If it wasn't, we flushed, making sufficient
+            // room for any input <= the buffer size, which includes this input.
+            unsafe {
+                bufs.iter().for_each(|b| self.write_to_buffer_unchecked(b));
+            };
+
+            Ok(saturated_total_len)
+        } else {
+            let mut iter = bufs.iter();
+            let mut total_written = if let Some(buf) = iter.by_ref().find(|&buf| !buf.is_empty()) {
+                // This is the first non-empty slice to write, so if it does
+                // not fit in the buffer, we still get to flush and proceed.
+                if buf.len() > self.spare_capacity() {
+                    self.flush_buf()?;
+                }
+                if buf.len() >= self.capacity() {
+                    // The slice is at least as large as the buffering capacity,
+                    // so it's better to write it directly, bypassing the buffer.
+                    self.panicked = true;
+                    let r = self.get_mut().write(buf);
+                    self.panicked = false;
+                    return r;
+                } else {
+                    // SAFETY: We checked whether or not the spare capacity was large enough above.
+                    // If it was, then we're safe already. If it wasn't, we flushed, making
+                    // sufficient room for any input <= the buffer size, which includes this input.
+                    unsafe {
+                        self.write_to_buffer_unchecked(buf);
+                    }
+
+                    buf.len()
+                }
+            } else {
+                return Ok(0);
+            };
+            debug_assert!(total_written != 0);
+            for buf in iter {
+                if buf.len() <= self.spare_capacity() {
+                    // SAFETY: safe by above conditional.
+                    unsafe {
+                        self.write_to_buffer_unchecked(buf);
+                    }
+
+                    // This cannot overflow `usize`. If we are here, we've written all of the bytes
+                    // so far to our buffer, and we've ensured that we never exceed the buffer's
+                    // capacity. Therefore, `total_written` <= `self.capacity()` <= `usize::MAX`.
+                    total_written += buf.len();
+                } else {
+                    break;
+                }
+            }
+            Ok(total_written)
+        }
+    }
+
     fn is_write_vectored(&self) -> bool {
         true
     }
@@ -547,21 +737,21 @@ impl<W: ?Sized + Write> Write for BufWriter<W> {
         self.flush_buf().and_then(|()| self.get_mut().flush())
     }
 }
-impl<W: ?Sized + Write> fmt::Debug for BufWriter<W>
+impl<W: ?Sized + Write, S> fmt::Debug for GenericBufWriter<W, S>
 where
     W: fmt::Debug,
+    S: AsRef<[MaybeUninit<u8>]> + AsMut<[MaybeUninit<u8>]>,
 {
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
         fmt.debug_struct("BufWriter")
             .field("writer", &&self.inner)
-            .field(
-                "buffer",
-                &format_args!("{}/{}", self.buf.len(), self.buf.capacity()),
-            )
+            .field("buffer", &format_args!("{}/{}", self.len, self.capacity()))
             .finish()
     }
 }
-impl<W: ?Sized + Write + Seek> Seek for BufWriter<W> {
+impl<W: ?Sized + Write + Seek, S: AsRef<[MaybeUninit<u8>]> + AsMut<[MaybeUninit<u8>]>> Seek
+    for GenericBufWriter<W, S>
+{
     /// Seek to the offset, in bytes, in the underlying writer.
     ///
     /// Seeking always writes out the internal buffer before seeking.
@@ -570,7 +760,9 @@ impl<W: ?Sized + Write + Seek> Seek for BufWriter<W> {
         self.get_mut().seek(pos)
     }
 }
-impl<W: ?Sized + Write> Drop for BufWriter<W> {
+impl<W: ?Sized + Write, S: AsRef<[MaybeUninit<u8>]> + AsMut<[MaybeUninit<u8>]>> Drop
+    for GenericBufWriter<W, S>
+{
     fn drop(&mut self) {
         if !self.panicked {
             // dtors should not panic, so we ignore a failed flush
@@ -1,5 +1,5 @@
-use crate::io::{self, buffered::LineWriterShim, BufWriter, IntoInnerError, Write};
-use alloc::fmt;
+use crate::io::{self, buffered::LineWriterShim, BufWriter, IntoInnerError, IoSlice, Write};
+use core::fmt;
 /// Wraps a writer and buffers output to it, flushing whenever a newline
 /// (`0x0a`, `'\n'`) is detected.
 ///
@@ -189,6 +189,11 @@ impl<W: ?Sized + Write> Write for LineWriter<W> {
     fn flush(&mut self) -> io::Result<()> {
         self.inner.flush()
     }
+
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        LineWriterShim::new(&mut self.inner).write_vectored(bufs)
+    }
+
     fn is_write_vectored(&self) -> bool {
         self.inner.is_write_vectored()
     }
@@ -196,6 +201,11 @@ impl<W: ?Sized + Write> Write for LineWriter<W> {
     fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
         LineWriterShim::new(&mut self.inner).write_all(buf)
     }
+
+    fn write_all_vectored(&mut self, bufs: &mut [IoSlice<'_>]) -> io::Result<()> {
+        LineWriterShim::new(&mut self.inner).write_all_vectored(bufs)
+    }
+
     fn write_fmt(&mut self, fmt: fmt::Arguments<'_>) -> io::Result<()> {
         LineWriterShim::new(&mut self.inner).write_fmt(fmt)
     }
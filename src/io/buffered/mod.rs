@@ -1,17 +1,33 @@
 //! Buffering wrappers for I/O traits
 
+mod buffer;
 mod bufreader;
 mod bufwriter;
+#[cfg(feature = "alloc")]
 mod linewriter;
+#[cfg(feature = "alloc")]
 mod linewritershim;
-#[cfg(test)]
+#[cfg(feature = "alloc")]
+mod options;
+// The tests need `alloc`.
+#[cfg(all(test, feature = "alloc"))]
 mod tests;
 
 use crate::io::Error;
+use buffer::Buffer;
 
-pub use self::{bufreader::BufReader, bufwriter::BufWriter, linewriter::LineWriter};
+#[cfg(feature = "alloc")]
+pub use self::{
+    bufreader::BufReader, bufwriter::BufWriter, linewriter::LineWriter, options::BufOptions,
+};
+pub use self::{
+    bufreader::{ArrayBufReader, GenericBufReader},
+    bufwriter::{ArrayBufWriter, GenericBufWriter},
+};
+#[cfg(feature = "alloc")]
 use linewritershim::LineWriterShim;
 
+#[cfg(feature = "alloc")]
 pub use bufwriter::WriterPanicked;
 
 /// An error returned by [`BufWriter::into_inner`] which combines an error that
@@ -48,6 +64,7 @@ impl<W> IntoInnerError<W> {
 
     /// Helper to construct a new IntoInnerError; intended to help with
     /// adapters that wrap other adapters
+    #[cfg(feature = "alloc")]
     fn new_wrapped<W2>(self, f: impl FnOnce(W) -> W2) -> IntoInnerError<W2> {
         let Self(writer, error) = self;
         IntoInnerError::new(f(writer), error)
@@ -0,0 +1,74 @@
+use super::{BufReader, BufWriter, LineWriter};
+use crate::io::{Read, Write, DEFAULT_BUF_SIZE};
+
+/// Builder for buffered readers and writers with a chosen capacity.
+///
+/// Scripts that tune their buffer sizes usually want the same size in many
+/// places; a `BufOptions` can be kept in a `const` and used for every
+/// [`BufReader`], [`BufWriter`] and [`LineWriter`] they create. A fresh
+/// `BufOptions` uses the same capacity as [`BufReader::new`].
+///
+/// For a buffer whose size is fixed at compile time and which does not
+/// allocate, see [`ArrayBufReader`] and [`ArrayBufWriter`].
+///
+/// [`ArrayBufReader`]: crate::io::ArrayBufReader
+/// [`ArrayBufWriter`]: crate::io::ArrayBufWriter
+///
+/// # Examples
+///
+/// ```no_run
+/// use ckb_rust_std::io::{self, ckb::{self, Source}, BufOptions, BufRead};
+///
+/// const LARGE: BufOptions = BufOptions::new().capacity(64 * 1024);
+///
+/// fn main() -> io::Result<()> {
+///     let data = ckb::cell_data(0, Source::GroupInput)?;
+///     let mut reader = LARGE.reader(data);
+///     let chunk = reader.fill_buf()?;
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BufOptions {
+    capacity: usize,
+}
+
+impl BufOptions {
+    /// Creates a `BufOptions` with the default buffer capacity.
+    pub const fn new() -> BufOptions {
+        BufOptions {
+            capacity: DEFAULT_BUF_SIZE,
+        }
+    }
+
+    /// Sets the buffer capacity, in bytes.
+    pub const fn capacity(self, capacity: usize) -> BufOptions {
+        BufOptions { capacity }
+    }
+
+    /// Returns the buffer capacity, in bytes.
+    pub const fn get_capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Wraps `inner` in a [`BufReader`] with this capacity.
+    pub fn reader<R: Read>(&self, inner: R) -> BufReader<R> {
+        BufReader::with_capacity(self.capacity, inner)
+    }
+
+    /// Wraps `inner` in a [`BufWriter`] with this capacity.
+    pub fn writer<W: Write>(&self, inner: W) -> BufWriter<W> {
+        BufWriter::with_capacity(self.capacity, inner)
+    }
+
+    /// Wraps `inner` in a [`LineWriter`] with this capacity.
+    pub fn line_writer<W: Write>(&self, inner: W) -> LineWriter<W> {
+        LineWriter::with_capacity(self.capacity, inner)
+    }
+}
+
+impl Default for BufOptions {
+    fn default() -> BufOptions {
+        BufOptions::new()
+    }
+}
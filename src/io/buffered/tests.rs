@@ -6,7 +6,10 @@ use alloc::vec::Vec;
 use crate::io;
 use crate::io::error::ErrorKind;
 use crate::io::{prelude::*, Error};
-use crate::io::{BorrowedBuf, BufReader, BufWriter, LineWriter, SeekFrom};
+use crate::io::{
+    ArrayBufReader, ArrayBufWriter, BorrowedBuf, BufOptions, BufReader, BufWriter,
+    GenericBufReader, GenericBufWriter, IoSlice, LineWriter, SeekFrom,
+};
 use core::mem::MaybeUninit;
 use core::panic;
 
@@ -833,6 +836,7 @@ impl Write for WriteRecorder {
 /// LineWriter::write_all results in two writes: one of the buffered data,
 /// and another of the final substring in the formatted set
 #[test]
+#[allow(clippy::write_literal)]
 fn single_formatted_write() {
     let writer = WriteRecorder::default();
     let mut writer = LineWriter::new(writer);
@@ -853,7 +857,7 @@ fn bufreader_full_initialize() {
     struct OneByteReader;
     impl Read for OneByteReader {
         fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
-            if buf.len() > 0 {
+            if !buf.is_empty() {
                 buf[0] = 0;
                 Ok(1)
             } else {
@@ -881,3 +885,443 @@ fn bufwriter_aliasing() {
     let w = BufWriter::new(Box::new(c));
     let _ = w.into_parts();
 }
+
+#[test]
+fn test_buffered_writer_vectored() {
+    let inner = Vec::new();
+    let mut writer = BufWriter::with_capacity(4, inner);
+
+    // Small slices are coalesced into the buffer.
+    assert_eq!(
+        writer
+            .write_vectored(&[IoSlice::new(&[0]), IoSlice::new(&[1, 2])])
+            .unwrap(),
+        3
+    );
+    assert_eq!(writer.buffer(), [0, 1, 2]);
+    assert_eq!(*writer.get_ref(), []);
+
+    // Input that does not fit flushes the buffer and goes straight through.
+    assert_eq!(
+        writer
+            .write_vectored(&[IoSlice::new(&[3, 4]), IoSlice::new(&[5, 6, 7])])
+            .unwrap(),
+        5
+    );
+    assert_eq!(writer.buffer(), []);
+    assert_eq!(*writer.get_ref(), [0, 1, 2, 3, 4, 5, 6, 7]);
+
+    assert_eq!(writer.write_vectored(&[]).unwrap(), 0);
+    writer.flush().unwrap();
+    assert_eq!(*writer.get_ref(), [0, 1, 2, 3, 4, 5, 6, 7]);
+}
+
+#[test]
+fn test_line_buffer_vectored() {
+    let mut a = LineWriter::new(Vec::new());
+    assert_eq!(
+        a.write_vectored(&[
+            IoSlice::new(&[]),
+            IoSlice::new(b"\n"),
+            IoSlice::new(&[]),
+            IoSlice::new(b"a"),
+        ])
+        .unwrap(),
+        2,
+    );
+    assert_eq!(a.get_ref(), b"\n");
+
+    assert_eq!(
+        a.write_vectored(&[
+            IoSlice::new(&[]),
+            IoSlice::new(b"b"),
+            IoSlice::new(&[]),
+            IoSlice::new(b"a"),
+            IoSlice::new(&[]),
+            IoSlice::new(b"c"),
+        ])
+        .unwrap(),
+        3,
+    );
+    assert_eq!(a.get_ref(), b"\n");
+    a.flush().unwrap();
+    assert_eq!(a.get_ref(), b"\nabac");
+    assert_eq!(a.write_vectored(&[]).unwrap(), 0);
+    assert_eq!(
+        a.write_vectored(&[
+            IoSlice::new(&[]),
+            IoSlice::new(&[]),
+            IoSlice::new(&[]),
+            IoSlice::new(&[]),
+        ])
+        .unwrap(),
+        0,
+    );
+    assert_eq!(a.write_vectored(&[IoSlice::new(b"a\nb"),]).unwrap(), 3);
+    assert_eq!(a.get_ref(), b"\nabaca\nb");
+}
+
+#[test]
+fn test_line_buffer_write_all_vectored() {
+    let mut a = LineWriter::new(Vec::new());
+    a.write_all_vectored(&mut [
+        IoSlice::new(b"Line 1\nLine "),
+        IoSlice::new(b"2\n"),
+        IoSlice::new(b"Partial"),
+    ])
+    .unwrap();
+    assert_eq!(a.get_ref(), b"Line 1\nLine 2\n");
+    a.flush().unwrap();
+    assert_eq!(a.get_ref(), b"Line 1\nLine 2\nPartial");
+}
+
+#[test]
+fn test_array_buffered_reader() {
+    let inner: &[u8] = &[5, 6, 7, 0, 1, 2, 3, 4];
+    let mut reader = ArrayBufReader::<_, 2>::new(inner);
+    assert_eq!(reader.capacity(), 2);
+
+    let mut buf = [0, 0, 0];
+    assert_eq!(reader.read(&mut buf).unwrap(), 3);
+    assert_eq!(buf, [5, 6, 7]);
+    assert_eq!(reader.buffer(), []);
+
+    let mut buf = [0];
+    assert_eq!(reader.read(&mut buf).unwrap(), 1);
+    assert_eq!(buf, [0]);
+    assert_eq!(reader.buffer(), [1]);
+
+    let mut buf = [0, 0, 0];
+    assert_eq!(reader.read(&mut buf).unwrap(), 1);
+    assert_eq!(buf, [1, 0, 0]);
+    reader.read_exact(&mut buf).unwrap();
+    assert_eq!(buf, [2, 3, 4]);
+    assert_eq!(reader.read(&mut buf).unwrap(), 0);
+}
+
+#[test]
+fn test_array_buffered_reader_seek() {
+    let inner: &[u8] = &[5, 6, 7, 0, 1, 2, 3, 4];
+    let mut reader = ArrayBufReader::<_, 2>::new(io::Cursor::new(inner));
+
+    assert_eq!(reader.seek(SeekFrom::Start(3)).ok(), Some(3));
+    assert_eq!(reader.fill_buf().ok(), Some(&[0, 1][..]));
+    assert_eq!(reader.stream_position().ok(), Some(3));
+    assert_eq!(reader.seek(SeekFrom::Current(1)).ok(), Some(4));
+    assert_eq!(reader.fill_buf().ok(), Some(&[1, 2][..]));
+    reader.consume(1);
+    assert_eq!(reader.seek(SeekFrom::Current(-2)).ok(), Some(3));
+
+    // relative seeking within the buffer keeps it
+    assert_eq!(reader.fill_buf().ok(), Some(&[0, 1][..]));
+    assert!(reader.seek_relative(1).is_ok());
+    assert_eq!(reader.stream_position().ok(), Some(4));
+    assert_eq!(reader.buffer(), &[1][..]);
+    assert!(reader.seek_relative(-1).is_ok());
+    assert_eq!(reader.buffer(), &[0, 1][..]);
+    // relative seeking outside the buffer discards it
+    assert!(reader.seek_relative(2).is_ok());
+    assert_eq!(reader.stream_position().ok(), Some(5));
+    assert_eq!(reader.buffer(), &[][..]);
+}
+
+#[test]
+fn test_array_buffered_reader_lines() {
+    let inner: &[u8] = b"a\nbb\nccc";
+    let reader = ArrayBufReader::<_, 2>::new(inner);
+    let lines: Vec<String> = reader.lines().map(|l| l.unwrap()).collect();
+    assert_eq!(lines, ["a", "bb", "ccc"]);
+}
+
+#[test]
+fn test_array_buffered_reader_initialized() {
+    let mut reader = ArrayBufReader::<_, 8>::new(ShortReader {
+        lengths: vec![1, 0],
+    });
+    assert_eq!(reader.initialized(), 0);
+    assert_eq!(reader.fill_buf().unwrap().len(), 1);
+    assert_eq!(reader.initialized(), reader.capacity());
+}
+
+#[test]
+fn test_array_buffered_writer() {
+    let inner = Vec::new();
+    let mut writer = ArrayBufWriter::<_, 2>::new(inner);
+
+    writer.write(&[0, 1]).unwrap();
+    assert_eq!(writer.buffer(), []);
+    assert_eq!(*writer.get_ref(), [0, 1]);
+
+    writer.write(&[2]).unwrap();
+    assert_eq!(writer.buffer(), [2]);
+    assert_eq!(*writer.get_ref(), [0, 1]);
+
+    writer.write(&[3]).unwrap();
+    assert_eq!(writer.buffer(), [2, 3]);
+    assert_eq!(*writer.get_ref(), [0, 1]);
+
+    writer.flush().unwrap();
+    assert_eq!(writer.buffer(), []);
+    assert_eq!(*writer.get_ref(), [0, 1, 2, 3]);
+
+    writer.write(&[4]).unwrap();
+    writer.write(&[5]).unwrap();
+    assert_eq!(writer.buffer(), [4, 5]);
+
+    writer.write(&[6]).unwrap();
+    assert_eq!(writer.buffer(), [6]);
+    assert_eq!(*writer.get_ref(), [0, 1, 2, 3, 4, 5]);
+
+    writer.write(&[7, 8]).unwrap();
+    assert_eq!(writer.buffer(), []);
+    assert_eq!(*writer.get_ref(), [0, 1, 2, 3, 4, 5, 6, 7, 8]);
+
+    writer.write(&[9, 10, 11]).unwrap();
+    assert_eq!(writer.buffer(), []);
+    assert_eq!(*writer.get_ref(), [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11]);
+}
+
+#[test]
+fn test_array_buffered_writer_into_inner_and_drop() {
+    let mut w = ArrayBufWriter::<_, 3>::new(Vec::new());
+    w.write(&[0, 1]).unwrap();
+    assert_eq!(*w.get_ref(), []);
+    let w = w.into_inner().unwrap();
+    assert_eq!(w, [0, 1]);
+
+    let mut out = [0u8; 4];
+    {
+        let mut w = ArrayBufWriter::<_, 8>::new(&mut out[..]);
+        w.write_all(b"abc").unwrap();
+    }
+    assert_eq!(&out, b"abc\0");
+}
+
+#[test]
+fn test_buffered_with_borrowed_storage() {
+    let mut storage = [MaybeUninit::uninit(); 4];
+    let mut reader = GenericBufReader::with_storage(&mut storage[..], &b"abcdef"[..]);
+    assert_eq!(reader.capacity(), 4);
+    assert_eq!(reader.fill_buf().unwrap(), b"abcd");
+    reader.consume(4);
+    assert_eq!(reader.fill_buf().unwrap(), b"ef");
+
+    let mut storage = [MaybeUninit::uninit(); 4];
+    let mut writer = GenericBufWriter::with_storage(&mut storage[..], Vec::new());
+    writer.write_all(b"abc").unwrap();
+    assert_eq!(writer.buffer(), b"abc");
+    assert_eq!(*writer.get_ref(), b"");
+    writer.write_all(b"de").unwrap();
+    assert_eq!(writer.buffer(), b"de");
+    assert_eq!(writer.into_inner().unwrap(), b"abcde");
+}
+
+#[test]
+fn test_array_buffered_writer_seek() {
+    let mut w = ArrayBufWriter::<_, 3>::new(io::Cursor::new(Vec::new()));
+    w.write_all(&[0, 1, 2, 3, 4, 5]).unwrap();
+    w.write_all(&[6, 7]).unwrap();
+    assert_eq!(w.stream_position().ok(), Some(8));
+    assert_eq!(&w.get_ref().get_ref()[..], &[0, 1, 2, 3, 4, 5, 6, 7][..]);
+    assert_eq!(w.seek(SeekFrom::Start(2)).ok(), Some(2));
+    w.write_all(&[8, 9]).unwrap();
+    assert_eq!(
+        &w.into_inner().unwrap().into_inner()[..],
+        &[0, 1, 8, 9, 4, 5, 6, 7]
+    );
+}
+
+#[test]
+fn test_array_buffered_writer_partial_flush() {
+    // Accepts one byte per call, then fails.
+    #[derive(Debug)]
+    struct OneThenFail(Vec<u8>, usize);
+    impl Write for OneThenFail {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            if self.1 == 0 {
+                return Err(io::Error::from(ErrorKind::Other));
+            }
+            self.1 -= 1;
+            self.0.push(buf[0]);
+            Ok(1)
+        }
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let mut w = ArrayBufWriter::<_, 8>::new(OneThenFail(Vec::new(), 2));
+    w.write_all(b"hello").unwrap();
+    assert!(w.flush().is_err());
+    // The written prefix is dropped from the buffer, the rest is kept.
+    assert_eq!(w.get_ref().0, b"he");
+    assert_eq!(w.buffer(), b"llo");
+
+    w.get_mut().1 = usize::MAX;
+    let w = w.into_inner().unwrap();
+    assert_eq!(w.0, b"hello");
+}
+
+#[test]
+fn test_array_buffered_writer_vectored() {
+    let mut w = ArrayBufWriter::<_, 4>::new(Vec::new());
+    assert_eq!(
+        w.write_vectored(&[IoSlice::new(b"ab"), IoSlice::new(b"c")])
+            .unwrap(),
+        3
+    );
+    assert_eq!(w.buffer(), b"abc");
+    assert_eq!(
+        w.write_vectored(&[IoSlice::new(b"de"), IoSlice::new(b"fgh")])
+            .unwrap(),
+        5
+    );
+    assert_eq!(w.buffer(), b"");
+    assert_eq!(*w.get_ref(), b"abcdefgh");
+}
+
+#[test]
+fn test_buf_options() {
+    assert_eq!(
+        BufOptions::new().reader(&[][..]).capacity(),
+        BufReader::new(&[][..]).capacity()
+    );
+    assert_eq!(BufOptions::default(), BufOptions::new());
+
+    const SMALL: BufOptions = BufOptions::new().capacity(4);
+    assert_eq!(SMALL.get_capacity(), 4);
+    assert_eq!(SMALL.reader(&b"hello"[..]).capacity(), 4);
+    assert_eq!(SMALL.writer(Vec::new()).capacity(), 4);
+
+    let mut reader = SMALL.reader(&b"hello"[..]);
+    assert_eq!(reader.fill_buf().unwrap(), b"hell");
+
+    let mut writer = SMALL.line_writer(Vec::new());
+    writer.write_all(b"ab").unwrap();
+    assert_eq!(writer.get_ref(), b"");
+    writer.write_all(b"c\nd").unwrap();
+    assert_eq!(writer.get_ref(), b"abc\n");
+}
+
+/// Hands out `data` a few bytes per read, with an `Interrupted` error every
+/// other call.
+struct Trickle<'a> {
+    data: &'a [u8],
+    step: usize,
+    interrupt: bool,
+}
+
+impl<'a> Trickle<'a> {
+    fn new(data: &'a [u8], step: usize) -> Trickle<'a> {
+        Trickle {
+            data,
+            step,
+            interrupt: false,
+        }
+    }
+}
+
+impl Read for Trickle<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.interrupt = !self.interrupt;
+        if self.interrupt {
+            return Err(ErrorKind::Interrupted.into());
+        }
+        let n = self.step.min(buf.len()).min(self.data.len());
+        buf[..n].copy_from_slice(&self.data[..n]);
+        self.data = &self.data[n..];
+        Ok(n)
+    }
+}
+
+#[test]
+fn test_buffered_reader_peek() {
+    let mut reader = BufReader::with_capacity(8, Trickle::new(b"0123456789abc", 3));
+    assert_eq!(reader.peek(5).unwrap(), b"01234");
+    // Peeking does not consume.
+    assert_eq!(reader.peek(2).unwrap(), b"01");
+    assert_eq!(reader.buffer(), b"012345");
+
+    // Not enough room after the buffered data: it is moved to the front.
+    reader.consume(4);
+    assert_eq!(reader.peek(8).unwrap(), b"456789ab");
+    assert_eq!(reader.buffer(), b"456789ab");
+
+    let mut rest = Vec::new();
+    reader.read_to_end(&mut rest).unwrap();
+    assert_eq!(rest, b"456789abc");
+
+    // At EOF, peek returns what is left.
+    let mut reader = BufReader::with_capacity(8, Trickle::new(b"xyz", 1));
+    assert_eq!(reader.peek(6).unwrap(), b"xyz");
+    reader.consume(3);
+    assert_eq!(reader.peek(6).unwrap(), b"");
+}
+
+#[test]
+#[should_panic = "exceeds the buffer capacity"]
+fn test_buffered_reader_peek_too_large() {
+    let mut reader = BufReader::with_capacity(4, &b"0123456789"[..]);
+    let _ = reader.peek(5);
+}
+
+#[test]
+fn test_array_buffered_reader_peek() {
+    let mut reader = ArrayBufReader::<_, 6>::new(Trickle::new(b"abcdefgh", 2));
+    assert_eq!(reader.peek(3).unwrap(), b"abc");
+    reader.consume(2);
+    assert_eq!(reader.peek(6).unwrap(), b"cdefgh");
+    assert_eq!(reader.fill_at_least(100).unwrap(), b"cdefgh");
+}
+
+#[test]
+fn test_fill_at_least() {
+    // `BufReader` gathers up to its capacity.
+    let mut reader = BufReader::with_capacity(16, Trickle::new(b"0123456789", 3));
+    assert_eq!(reader.fill_at_least(4).unwrap(), b"012345");
+    assert_eq!(reader.fill_at_least(32).unwrap(), b"0123456789");
+
+    let mut reader = BufReader::with_capacity(4, Trickle::new(b"0123456789", 3));
+    assert_eq!(reader.fill_at_least(100).unwrap(), b"0123");
+
+    // Through `Take`, limited to the window.
+    let mut take = BufReader::with_capacity(16, Trickle::new(b"0123456789", 1)).take(5);
+    assert_eq!(take.fill_at_least(8).unwrap(), b"01234");
+    take.consume(4);
+    assert_eq!(take.fill_at_least(8).unwrap(), b"4");
+
+    // In-memory readers return everything; through `&mut`.
+    let mut slice: &[u8] = b"abc";
+    assert_eq!((&mut slice).fill_at_least(2).unwrap(), b"abc");
+
+    // `Chain` gathers within each of its halves.
+    let first = BufReader::with_capacity(8, Trickle::new(b"0123", 1));
+    let second = BufReader::with_capacity(8, Trickle::new(b"4567", 1));
+    let mut chain = first.chain(second);
+    assert_eq!(chain.fill_at_least(3).unwrap(), b"012");
+    assert_eq!(chain.fill_at_least(8).unwrap(), b"0123");
+    chain.consume(4);
+    assert_eq!(chain.fill_at_least(2).unwrap(), b"45");
+
+    // The default implementation is a single `fill_buf`, so `n` is only a
+    // hint: two bytes are asked for while only one is returned before EOF.
+    struct OneByte<'a>(&'a [u8]);
+
+    impl Read for OneByte<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.0.read(buf)
+        }
+    }
+
+    impl BufRead for OneByte<'_> {
+        fn fill_buf(&mut self) -> io::Result<&[u8]> {
+            Ok(&self.0[..self.0.len().min(1)])
+        }
+
+        fn consume(&mut self, amt: usize) {
+            self.0 = &self.0[amt..];
+        }
+    }
+
+    assert_eq!(OneByte(b"ab").fill_at_least(2).unwrap(), b"a");
+}
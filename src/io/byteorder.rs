@@ -0,0 +1,185 @@
+//! Reading and writing integers and floats in a chosen byte order.
+//!
+//! [`ReadExt`] and [`WriteExt`] extend every [`Read`] and [`Write`] with
+//! methods such as [`read_u32`] and [`write_u32`], which take the byte order
+//! as a type parameter:
+//!
+//! ```no_run
+//! use ckb_rust_std::io::{self, byteorder::{ReadExt, WriteExt, BE, LE}, Cursor};
+//!
+//! fn main() -> io::Result<()> {
+//!     let mut reader = Cursor::new([1u8, 0, 0, 0, 0, 2]);
+//!     assert_eq!(reader.read_u32::<LE>()?, 1);
+//!     assert_eq!(reader.read_u16::<BE>()?, 2);
+//!
+//!     let mut writer = Vec::new();
+//!     writer.write_u32::<LE>(1)?;
+//!     writer.write_u16::<BE>(2)?;
+//!     assert_eq!(writer, [1, 0, 0, 0, 0, 2]);
+//!     Ok(())
+//! }
+//! ```
+//!
+//! Each value is read with a single [`read_exact`] into a fixed-size array,
+//! so in-memory readers such as [`Cursor`] and `&[u8]`, whose `read_exact`
+//! is a bounds check and a copy, never go through the generic read loop.
+//!
+//! [`read_u32`]: ReadExt::read_u32
+//! [`write_u32`]: WriteExt::write_u32
+//! [`read_exact`]: Read::read_exact
+//! [`Cursor`]: crate::io::Cursor
+#[cfg(test)]
+mod tests;
+
+use crate::io::{self, Read, Write};
+
+mod private {
+    pub trait Sealed {}
+}
+
+macro_rules! byte_order_methods {
+    ($($ty:ident: $read:ident, $write:ident, $n:literal;)*) => {
+        /// A byte order for [`ReadExt`] and [`WriteExt`].
+        ///
+        /// This trait is sealed: it is implemented by [`LittleEndian`] and
+        /// [`BigEndian`] only.
+        pub trait ByteOrder: private::Sealed {
+            $(
+                #[doc = concat!("Decodes a `", stringify!($ty), "` in this byte order.")]
+                fn $read(bytes: [u8; $n]) -> $ty;
+
+                #[doc = concat!("Encodes a `", stringify!($ty), "` in this byte order.")]
+                fn $write(n: $ty) -> [u8; $n];
+            )*
+        }
+
+        impl ByteOrder for LittleEndian {
+            $(
+                #[inline]
+                fn $read(bytes: [u8; $n]) -> $ty {
+                    $ty::from_le_bytes(bytes)
+                }
+
+                #[inline]
+                fn $write(n: $ty) -> [u8; $n] {
+                    n.to_le_bytes()
+                }
+            )*
+        }
+
+        impl ByteOrder for BigEndian {
+            $(
+                #[inline]
+                fn $read(bytes: [u8; $n]) -> $ty {
+                    $ty::from_be_bytes(bytes)
+                }
+
+                #[inline]
+                fn $write(n: $ty) -> [u8; $n] {
+                    n.to_be_bytes()
+                }
+            )*
+        }
+
+        /// Extends [`Read`] with methods for reading numbers.
+        ///
+        /// This trait is implemented for every reader; see the
+        /// [module documentation](self) for an example.
+        pub trait ReadExt: Read {
+            /// Reads an unsigned 8-bit integer.
+            #[inline]
+            fn read_u8(&mut self) -> io::Result<u8> {
+                read_array::<_, 1>(self).map(|[b]| b)
+            }
+
+            /// Reads a signed 8-bit integer.
+            #[inline]
+            fn read_i8(&mut self) -> io::Result<i8> {
+                read_array::<_, 1>(self).map(|[b]| b as i8)
+            }
+
+            $(
+                #[doc = concat!("Reads a `", stringify!($ty), "` in the byte order `B`.")]
+                ///
+                /// # Errors
+                ///
+                /// Fails with [`ErrorKind::UnexpectedEof`] if the reader ends
+                /// early, and otherwise returns any error of
+                /// [`read_exact`](Read::read_exact).
+                ///
+                /// [`ErrorKind::UnexpectedEof`]: crate::io::ErrorKind::UnexpectedEof
+                #[inline]
+                fn $read<B: ByteOrder>(&mut self) -> io::Result<$ty> {
+                    read_array(self).map(B::$read)
+                }
+            )*
+        }
+
+        /// Extends [`Write`] with methods for writing numbers.
+        ///
+        /// This trait is implemented for every writer; see the
+        /// [module documentation](self) for an example.
+        pub trait WriteExt: Write {
+            /// Writes an unsigned 8-bit integer.
+            #[inline]
+            fn write_u8(&mut self, n: u8) -> io::Result<()> {
+                self.write_all(&[n])
+            }
+
+            /// Writes a signed 8-bit integer.
+            #[inline]
+            fn write_i8(&mut self, n: i8) -> io::Result<()> {
+                self.write_all(&[n as u8])
+            }
+
+            $(
+                #[doc = concat!("Writes a `", stringify!($ty), "` in the byte order `B`.")]
+                #[inline]
+                fn $write<B: ByteOrder>(&mut self, n: $ty) -> io::Result<()> {
+                    self.write_all(&B::$write(n))
+                }
+            )*
+        }
+    };
+}
+
+byte_order_methods! {
+    u16: read_u16, write_u16, 2;
+    u32: read_u32, write_u32, 4;
+    u64: read_u64, write_u64, 8;
+    u128: read_u128, write_u128, 16;
+    i16: read_i16, write_i16, 2;
+    i32: read_i32, write_i32, 4;
+    i64: read_i64, write_i64, 8;
+    i128: read_i128, write_i128, 16;
+    f32: read_f32, write_f32, 4;
+    f64: read_f64, write_f64, 8;
+}
+
+/// Little-endian byte order, as used by CKB and molecule.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum LittleEndian {}
+
+/// Big-endian byte order, also known as network byte order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum BigEndian {}
+
+/// A short alias for [`LittleEndian`].
+pub type LE = LittleEndian;
+
+/// A short alias for [`BigEndian`].
+pub type BE = BigEndian;
+
+impl private::Sealed for LittleEndian {}
+impl private::Sealed for BigEndian {}
+
+impl<R: Read + ?Sized> ReadExt for R {}
+
+impl<W: Write + ?Sized> WriteExt for W {}
+
+#[inline]
+fn read_array<R: Read + ?Sized, const N: usize>(reader: &mut R) -> io::Result<[u8; N]> {
+    let mut buf = [0u8; N];
+    reader.read_exact(&mut buf)?;
+    Ok(buf)
+}
@@ -0,0 +1,105 @@
+use super::*;
+use crate::io::{Cursor, ErrorKind};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+/// A reader which returns at most one byte per call, forcing `read_exact`
+/// through its generic loop.
+struct OneByte<'a>(&'a [u8]);
+
+impl Read for OneByte<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = buf.len().min(self.0.len()).min(1);
+        buf[..n].copy_from_slice(&self.0[..n]);
+        self.0 = &self.0[n..];
+        Ok(n)
+    }
+}
+
+#[test]
+fn read_little_and_big_endian() {
+    let data = [0x01, 0x02, 0x03, 0x04];
+    assert_eq!(Cursor::new(&data).read_u32::<LE>().unwrap(), 0x04030201);
+    assert_eq!(Cursor::new(&data).read_u32::<BE>().unwrap(), 0x01020304);
+    assert_eq!((&data[..]).read_u16::<LittleEndian>().unwrap(), 0x0201);
+    assert_eq!((&data[..]).read_u16::<BigEndian>().unwrap(), 0x0102);
+    assert_eq!(OneByte(&data).read_i32::<BE>().unwrap(), 0x01020304);
+}
+
+#[test]
+fn read_advances() {
+    let mut reader: &[u8] = &[0xff, 0xfe, 0xff, 1, 2];
+    assert_eq!(reader.read_i8().unwrap(), -1);
+    assert_eq!(reader.read_i16::<LE>().unwrap(), -2);
+    assert_eq!(reader.read_u8().unwrap(), 1);
+    assert_eq!(reader, [2]);
+}
+
+#[test]
+fn read_eof() {
+    let mut reader: &[u8] = &[1, 2, 3];
+    let err = reader.read_u32::<LE>().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+
+    let mut cursor = Cursor::new([1, 2, 3]);
+    cursor.read_u8().unwrap();
+    let err = cursor.read_u64::<BE>().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+    assert_eq!(cursor.position(), 3);
+
+    let err = OneByte(&[1]).read_u16::<LE>().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn round_trip() {
+    let mut buf = Vec::new();
+    buf.write_u8(0xab).unwrap();
+    buf.write_i8(-3).unwrap();
+    buf.write_u16::<LE>(0x1234).unwrap();
+    buf.write_u32::<BE>(0x12345678).unwrap();
+    buf.write_u64::<LE>(u64::MAX - 1).unwrap();
+    buf.write_u128::<BE>(1 << 100).unwrap();
+    buf.write_i16::<BE>(-300).unwrap();
+    buf.write_i32::<LE>(i32::MIN).unwrap();
+    buf.write_i64::<BE>(-1).unwrap();
+    buf.write_i128::<LE>(i128::MIN + 7).unwrap();
+    buf.write_f32::<LE>(1.5).unwrap();
+    buf.write_f64::<BE>(-0.25).unwrap();
+    assert_eq!(buf.len(), 1 + 1 + 2 + 4 + 8 + 16 + 2 + 4 + 8 + 16 + 4 + 8);
+    assert_eq!(buf[2..8], [0x34, 0x12, 0x12, 0x34, 0x56, 0x78]);
+
+    let mut reader = Cursor::new(&buf);
+    assert_eq!(reader.read_u8().unwrap(), 0xab);
+    assert_eq!(reader.read_i8().unwrap(), -3);
+    assert_eq!(reader.read_u16::<LE>().unwrap(), 0x1234);
+    assert_eq!(reader.read_u32::<BE>().unwrap(), 0x12345678);
+    assert_eq!(reader.read_u64::<LE>().unwrap(), u64::MAX - 1);
+    assert_eq!(reader.read_u128::<BE>().unwrap(), 1 << 100);
+    assert_eq!(reader.read_i16::<BE>().unwrap(), -300);
+    assert_eq!(reader.read_i32::<LE>().unwrap(), i32::MIN);
+    assert_eq!(reader.read_i64::<BE>().unwrap(), -1);
+    assert_eq!(reader.read_i128::<LE>().unwrap(), i128::MIN + 7);
+    assert_eq!(reader.read_f32::<LE>().unwrap(), 1.5);
+    assert_eq!(reader.read_f64::<BE>().unwrap(), -0.25);
+    assert!(reader.read_u8().is_err());
+}
+
+#[test]
+fn write_into_slice() {
+    let mut out = [0u8; 6];
+    let mut writer = &mut out[..];
+    writer.write_u32::<BE>(0xdeadbeef).unwrap();
+    writer.write_u16::<LE>(0x0102).unwrap();
+    let err = writer.write_u8(0).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::WriteZero);
+    assert_eq!(out, [0xde, 0xad, 0xbe, 0xef, 0x02, 0x01]);
+}
+
+#[test]
+fn byte_order_arrays() {
+    assert_eq!(LE::write_u32(1), [1, 0, 0, 0]);
+    assert_eq!(BE::read_u16([1, 0]), 256);
+    assert_eq!(BE::read_f32(BE::write_f32(2.0)), 2.0);
+}
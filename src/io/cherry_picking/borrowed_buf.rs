@@ -1,4 +1,4 @@
-use alloc::fmt::{self, Debug, Formatter};
+use core::fmt::{self, Debug, Formatter};
 use core::mem::{self, MaybeUninit};
 use core::{cmp, ptr};
 
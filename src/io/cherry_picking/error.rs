@@ -1,5 +1,6 @@
 use core::fmt::{Debug, Display};
 
+#[cfg(feature = "alloc")]
 use alloc::{boxed::Box, fmt, string::String};
 
 #[allow(dead_code)]
@@ -22,7 +23,7 @@ pub trait Error: Debug + Display {
     }
 }
 
-impl<'a, T: Error + ?Sized> Error for &'a T {
+impl<T: Error + ?Sized> Error for &T {
     #[allow(deprecated, deprecated_in_future)]
     fn description(&self) -> &str {
         Error::description(&**self)
@@ -38,18 +39,21 @@ impl<'a, T: Error + ?Sized> Error for &'a T {
     }
 }
 
+#[cfg(feature = "alloc")]
 impl<'a, E: Error + 'a> From<E> for Box<dyn Error + 'a> {
     fn from(err: E) -> Box<dyn Error + 'a> {
         Box::new(err)
     }
 }
 
+#[cfg(feature = "alloc")]
 impl<'a, E: Error + Send + Sync + 'a> From<E> for Box<dyn Error + Send + Sync + 'a> {
     fn from(err: E) -> Box<dyn Error + Send + Sync + 'a> {
         Box::new(err)
     }
 }
 
+#[cfg(feature = "alloc")]
 impl<'a> From<String> for Box<dyn Error + Send + Sync + 'a> {
     #[inline]
     fn from(err: String) -> Box<dyn Error + Send + Sync + 'a> {
@@ -79,6 +83,7 @@ impl<'a> From<String> for Box<dyn Error + Send + Sync + 'a> {
     }
 }
 
+#[cfg(feature = "alloc")]
 impl<'a> From<String> for Box<dyn Error + 'a> {
     fn from(str_err: String) -> Box<dyn Error + 'a> {
         let err1: Box<dyn Error + Send + Sync> = From::from(str_err);
@@ -87,18 +92,21 @@ impl<'a> From<String> for Box<dyn Error + 'a> {
     }
 }
 
+#[cfg(feature = "alloc")]
 impl<'a> From<&str> for Box<dyn Error + Send + Sync + 'a> {
     #[inline]
     fn from(err: &str) -> Box<dyn Error + Send + Sync + 'a> {
         From::from(String::from(err))
     }
 }
+#[cfg(feature = "alloc")]
 impl<'a> From<&str> for Box<dyn Error + 'a> {
     fn from(err: &str) -> Box<dyn Error + 'a> {
         From::from(String::from(err))
     }
 }
 
+#[cfg(feature = "alloc")]
 impl<T: Error> Error for Box<T> {
     #[allow(deprecated, deprecated_in_future)]
     fn description(&self) -> &str {
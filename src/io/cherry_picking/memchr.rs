@@ -1,4 +1,46 @@
-pub const fn memchr(x: u8, text: &[u8]) -> Option<usize> {
+// Original implementation taken from rust-memchr, by way of `core::slice::memchr`.
+// Copyright 2015 Andrew Gallant, bluss and Nicolas Koch
+// The tests need `alloc`.
+#[cfg(all(test, feature = "alloc"))]
+mod tests;
+
+use core::mem;
+
+const LO_USIZE: usize = usize::MAX / 0xff;
+const HI_USIZE: usize = LO_USIZE << 7;
+const USIZE_BYTES: usize = mem::size_of::<usize>();
+
+/// Returns `true` if `x` contains any zero byte.
+///
+/// From *Matters Computational*, J. Arndt:
+///
+/// "The idea is to subtract one from each of the bytes and then look for
+/// bytes where the borrow propagated all the way to the most significant
+/// bit."
+#[inline]
+const fn contains_zero_byte(x: usize) -> bool {
+    x.wrapping_sub(LO_USIZE) & !x & HI_USIZE != 0
+}
+
+/// Returns `x` repeated in every byte of a `usize`.
+#[inline]
+const fn repeat_byte(x: u8) -> usize {
+    x as usize * LO_USIZE
+}
+
+/// Returns the first index matching the byte `x` in `text`.
+#[inline]
+pub fn memchr(x: u8, text: &[u8]) -> Option<usize> {
+    // Fast path for small slices.
+    if text.len() < 2 * USIZE_BYTES {
+        return memchr_naive(x, text);
+    }
+
+    memchr_aligned(x, text)
+}
+
+#[inline]
+const fn memchr_naive(x: u8, text: &[u8]) -> Option<usize> {
     let mut i = 0;
     while i < text.len() {
         if text[i] == x {
@@ -11,7 +53,112 @@ pub const fn memchr(x: u8, text: &[u8]) -> Option<usize> {
     None
 }
 
+fn memchr_aligned(x: u8, text: &[u8]) -> Option<usize> {
+    // Scan for a single byte value by reading two `usize` words at a time.
+    //
+    // Split `text` in three parts
+    // - unaligned initial part, before the first word aligned address in text
+    // - body, scan by 2 words at a time
+    // - the last remaining part, < 2 word size
+
+    // search up to an aligned boundary
+    let len = text.len();
+    let ptr = text.as_ptr();
+    let mut offset = ptr.align_offset(USIZE_BYTES);
+
+    if offset > 0 {
+        offset = offset.min(len);
+        if let Some(index) = memchr_naive(x, &text[..offset]) {
+            return Some(index);
+        }
+    }
+
+    // search the body of the text
+    let repeated_x = repeat_byte(x);
+    while offset <= len - 2 * USIZE_BYTES {
+        // SAFETY: the while's predicate guarantees a distance of at least
+        // 2 * USIZE_BYTES between the offset and the end of the slice, and
+        // `ptr + offset` is word aligned.
+        let (u, v) = unsafe {
+            (
+                *(ptr.add(offset) as *const usize),
+                *(ptr.add(offset + USIZE_BYTES) as *const usize),
+            )
+        };
+
+        // break if there is a matching byte
+        let zu = contains_zero_byte(u ^ repeated_x);
+        let zv = contains_zero_byte(v ^ repeated_x);
+        if zu || zv {
+            break;
+        }
+        offset += USIZE_BYTES * 2;
+    }
+
+    // Find the byte after the point the body loop stopped.
+    memchr_naive(x, &text[offset..]).map(|i| offset + i)
+}
+
+/// Returns the last index matching the byte `x` in `text`.
+#[cfg(feature = "alloc")]
 pub fn memrchr(x: u8, text: &[u8]) -> Option<usize> {
+    // Scan for a single byte value by reading two `usize` words at a time.
+    //
+    // Split `text` in three parts:
+    // - unaligned tail, after the last word aligned address in text,
+    // - body, scanned by 2 words at a time,
+    // - the first remaining bytes, < 2 word size.
+    let len = text.len();
+    let ptr = text.as_ptr();
+    type Chunk = usize;
+
+    let (min_aligned_offset, max_aligned_offset) = {
+        // We call this just to obtain the length of the prefix and suffix.
+        // In the middle we always process two chunks at once.
+        // SAFETY: transmuting `[u8]` to `[usize]` is safe except for size
+        // differences which are handled by `align_to`.
+        let (prefix, _, suffix) = unsafe { text.align_to::<(Chunk, Chunk)>() };
+        (prefix.len(), len - suffix.len())
+    };
+
+    let mut offset = max_aligned_offset;
+    if let Some(index) = memrchr_naive(x, &text[offset..]) {
+        return Some(offset + index);
+    }
+
+    // Search the body of the text, make sure we don't cross
+    // min_aligned_offset. offset is always aligned, so just testing `>` is
+    // sufficient and avoids possible overflow.
+    let repeated_x = repeat_byte(x);
+    let chunk_bytes = mem::size_of::<Chunk>();
+
+    while offset > min_aligned_offset {
+        // SAFETY: offset starts at len - suffix.len(), as long as it is
+        // greater than min_aligned_offset (prefix.len()) the remaining
+        // distance is at least 2 * chunk_bytes.
+        let (u, v) = unsafe {
+            (
+                *(ptr.add(offset - 2 * chunk_bytes) as *const Chunk),
+                *(ptr.add(offset - chunk_bytes) as *const Chunk),
+            )
+        };
+
+        // Break if there is a matching byte.
+        let zu = contains_zero_byte(u ^ repeated_x);
+        let zv = contains_zero_byte(v ^ repeated_x);
+        if zu || zv {
+            break;
+        }
+        offset -= 2 * chunk_bytes;
+    }
+
+    // Find the byte before the point the body loop stopped.
+    memrchr_naive(x, &text[..offset])
+}
+
+#[cfg(feature = "alloc")]
+#[inline]
+fn memrchr_naive(x: u8, text: &[u8]) -> Option<usize> {
     let mut i = text.len();
     while i > 0 {
         i -= 1;
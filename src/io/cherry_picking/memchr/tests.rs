@@ -0,0 +1,84 @@
+use super::*;
+use alloc::vec::Vec;
+
+// Checks the word-at-a-time searches against the byte-at-a-time ones.
+fn check(x: u8, text: &[u8]) {
+    assert_eq!(
+        memchr(x, text),
+        memchr_naive(x, text),
+        "memchr({x}, {text:?})"
+    );
+    assert_eq!(
+        memrchr(x, text),
+        memrchr_naive(x, text),
+        "memrchr({x}, {text:?})"
+    );
+}
+
+// A small xorshift generator, so the tests need no dependencies.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
+
+#[test]
+fn every_position_and_alignment() {
+    // A backing buffer large enough to slice at every offset within a word.
+    let mut backing = [0xaa_u8; 96 + 2 * USIZE_BYTES];
+    for start in 0..USIZE_BYTES {
+        for len in 0..96 {
+            let text = &mut backing[start..start + len];
+            text.fill(0xaa);
+            check(b'\n', text);
+            for pos in 0..len {
+                text[pos] = b'\n';
+                check(b'\n', text);
+                text[pos] = 0xaa;
+            }
+            if len >= 2 {
+                text[0] = b'\n';
+                text[len - 1] = b'\n';
+                check(b'\n', text);
+            }
+        }
+    }
+}
+
+#[test]
+fn bytes_that_confuse_zero_detection() {
+    // The SWAR test subtracts 1 from every byte; borrows out of zero bytes
+    // and high bits in neighbours must not produce false matches.
+    for x in [0x00, 0x01, 0x7f, 0x80, 0x81, 0xfe, 0xff] {
+        for fill in [0x00, 0x01, 0x7f, 0x80, 0x81, 0xfe, 0xff] {
+            let mut text = [fill; 64];
+            check(x, &text);
+            text[37] = x;
+            check(x, &text);
+            text[5] = x;
+            check(x, &text);
+        }
+    }
+}
+
+#[test]
+fn random_against_naive() {
+    let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+    let mut text = Vec::new();
+    for _ in 0..2000 {
+        let len = (rng.next() % 300) as usize;
+        // Draw from a small alphabet, so matches are neither too rare nor
+        // too common.
+        let alphabet = 1 + (rng.next() % 64) as u8;
+        text.clear();
+        text.extend((0..len).map(|_| (rng.next() % alphabet as u64) as u8));
+        let start = (rng.next() % (USIZE_BYTES as u64 + 1)) as usize;
+        let text = &text[start.min(len)..];
+        check((rng.next() % alphabet as u64) as u8, text);
+    }
+}
@@ -0,0 +1,99 @@
+//! Mapping errors onto script exit codes.
+#[cfg(test)]
+mod tests;
+
+use crate::io::{Error, ErrorKind};
+use core::convert::Infallible;
+
+/// An error that can be reported as the exit code of a script.
+///
+/// CKB-VM only tells the outside world whether a script failed through its
+/// `i8` exit code, where `0` means success. This trait gives every error a
+/// fixed nonzero code, so that contracts built on this crate fail in the same,
+/// testable way. Implement it for your own error types to return them from
+/// the function passed to [`run`].
+///
+/// The codes of the types in this crate are:
+///
+/// * `1..=127` for [`Error::from_raw_os_error`] with a code in that range,
+///   i.e. the syscall return code is passed through unchanged, which covers
+///   [`INDEX_OUT_OF_BOUND`] through [`MAX_FDS_CREATED`].
+/// * `-1..=-41` for every other [`Error`], according to its [`kind`]: the
+///   code is the negated [`ErrorKind::to_u8`], so `-1` is [`NotFound`],
+///   `-22` is [`InvalidData`], `-38` is [`UnexpectedEof`], `-40` is
+///   [`Other`] and so on. Kinds added in the future get new codes below
+///   `-41`, existing codes never change.
+///
+/// Errors created with [`Error::new`] are reported by their kind, since the
+/// boxed payload is not inspected.
+///
+/// Codes from `-128` to `-42` are free for custom errors, and so are those
+/// positive codes the syscalls a script uses cannot return.
+///
+/// [`INDEX_OUT_OF_BOUND`]: super::INDEX_OUT_OF_BOUND
+/// [`MAX_FDS_CREATED`]: super::MAX_FDS_CREATED
+/// [`kind`]: Error::kind
+/// [`NotFound`]: ErrorKind::NotFound
+/// [`InvalidData`]: ErrorKind::InvalidData
+/// [`UnexpectedEof`]: ErrorKind::UnexpectedEof
+/// [`Other`]: ErrorKind::Other
+pub trait ExitCode {
+    /// Returns the exit code for this error. It must not be `0`.
+    fn exit_code(&self) -> i8;
+}
+
+impl ExitCode for ErrorKind {
+    fn exit_code(&self) -> i8 {
+        -(self.to_u8() as i8)
+    }
+}
+
+impl ExitCode for Error {
+    fn exit_code(&self) -> i8 {
+        match self.raw_os_error() {
+            Some(code @ 1..=127) => code as i8,
+            _ => self.kind().exit_code(),
+        }
+    }
+}
+
+impl ExitCode for Infallible {
+    fn exit_code(&self) -> i8 {
+        match *self {}
+    }
+}
+
+impl<E: ExitCode + ?Sized> ExitCode for &E {
+    fn exit_code(&self) -> i8 {
+        (**self).exit_code()
+    }
+}
+
+/// Runs the body of a script and returns its exit code.
+///
+/// Returns `0` if `main` succeeds and the [`ExitCode`] of its error
+/// otherwise. The error itself is dropped, so log it first if its message is
+/// needed.
+///
+/// # Examples
+///
+/// ```no_run
+/// use ckb_rust_std::io::{self, ckb, Read};
+///
+/// fn main() -> io::Result<()> {
+///     let mut args = [0; 20];
+///     ckb::script_args()?.read_exact(&mut args)?;
+///     Ok(())
+/// }
+///
+/// #[no_mangle]
+/// pub extern "C" fn program_entry() -> i8 {
+///     ckb::run(main)
+/// }
+/// ```
+pub fn run<E: ExitCode>(main: impl FnOnce() -> Result<(), E>) -> i8 {
+    match main() {
+        Ok(()) => 0,
+        Err(e) => e.exit_code(),
+    }
+}
@@ -0,0 +1,103 @@
+use super::*;
+#[cfg(feature = "error_context")]
+use crate::io::ckb::ITEM_MISSING;
+use crate::io::ckb::{INDEX_OUT_OF_BOUND, MAX_FDS_CREATED};
+use crate::io::{self, const_io_error, Read};
+#[cfg(feature = "error_context")]
+use crate::io::{ErrorExt, ResultExt};
+use core::fmt;
+
+#[test]
+fn kind_codes() {
+    assert_eq!(ErrorKind::NotFound.exit_code(), -1);
+    assert_eq!(ErrorKind::BrokenPipe.exit_code(), -12);
+    assert_eq!(ErrorKind::InvalidData.exit_code(), -22);
+    assert_eq!(ErrorKind::UnexpectedEof.exit_code(), -38);
+    assert_eq!(ErrorKind::Other.exit_code(), -40);
+    assert_eq!(ErrorKind::Uncategorized.exit_code(), -41);
+}
+
+#[test]
+fn error_codes() {
+    const EOF: Error = const_io_error!(ErrorKind::UnexpectedEof, "eof");
+    assert_eq!(EOF.exit_code(), -38);
+    assert_eq!(Error::from(ErrorKind::InvalidInput).exit_code(), -21);
+    #[cfg(feature = "alloc")]
+    assert_eq!(Error::new(ErrorKind::InvalidData, "bad").exit_code(), -22);
+
+    // Syscall codes pass through.
+    for code in [INDEX_OUT_OF_BOUND, MAX_FDS_CREATED, 127] {
+        assert_eq!(Error::from_raw_os_error(code).exit_code() as i64, code);
+    }
+    // Others are reported by kind.
+    assert_eq!(Error::from_raw_os_error(128).exit_code(), -40);
+    assert_eq!(Error::from_raw_os_error(-3).exit_code(), -40);
+}
+
+#[cfg(feature = "error_context")]
+#[test]
+fn context_codes() {
+    let err = Error::from_raw_os_error(ITEM_MISSING).context("loading cell");
+    assert_eq!(err.exit_code(), 2);
+    let err = Error::from(ErrorKind::WriteZero).context("a").context("b");
+    assert_eq!(err.exit_code(), -24);
+}
+
+#[derive(Debug)]
+enum ScriptError {
+    Io(io::Error),
+    WrongSignature,
+}
+
+impl From<io::Error> for ScriptError {
+    fn from(e: io::Error) -> ScriptError {
+        ScriptError::Io(e)
+    }
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("script error")
+    }
+}
+
+impl ExitCode for ScriptError {
+    fn exit_code(&self) -> i8 {
+        match self {
+            ScriptError::Io(e) => e.exit_code(),
+            ScriptError::WrongSignature => -100,
+        }
+    }
+}
+
+#[test]
+fn run_codes() {
+    assert_eq!(run(|| -> io::Result<()> { Ok(()) }), 0);
+    assert_eq!(
+        run(|| -> io::Result<()> {
+            let mut data: &[u8] = &[1];
+            data.read_exact(&mut [0; 2])?;
+            Ok(())
+        }),
+        -38
+    );
+    #[cfg(feature = "error_context")]
+    assert_eq!(
+        run(|| -> io::Result<()> {
+            Err(Error::from_raw_os_error(INDEX_OUT_OF_BOUND)).context("witness")
+        }),
+        1
+    );
+    assert_eq!(
+        run(|| -> Result<(), ScriptError> { Err(ScriptError::WrongSignature) }),
+        -100
+    );
+    assert_eq!(
+        run(|| -> Result<(), ScriptError> { Err(Error::from(ErrorKind::NotFound).into()) }),
+        -1
+    );
+    assert_eq!(
+        run(|| -> Result<(), core::convert::Infallible> { Ok(()) }),
+        0
+    );
+}
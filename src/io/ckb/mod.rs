@@ -0,0 +1,444 @@
+//! Readers over data loaded through CKB syscalls.
+//!
+//! Scripts running on CKB-VM fetch cells, witnesses and their own script via
+//! syscalls that support *partial loading*: the caller passes an offset and a
+//! buffer length, and the VM copies only that window. [`SyscallReader`] wraps
+//! one such data source in [`Read`] and [`Seek`], so the rest of this crate
+//! ([`BufReader`], [`Take`], [`read_exact`], ...) can be used on it without
+//! ever loading more than what is actually read.
+//!
+//! The syscalls themselves sit behind the [`Loader`] trait. [`Syscalls`] is
+//! the real implementation; tests and off-chain tools can plug in their own.
+//!
+//! [`pipe()`], [`PipeReader`] and [`PipeWriter`] expose the pipes that
+//! connect spawned scripts as byte streams, behind the [`Pipes`] trait.
+//!
+//! [`run`] turns the result of a script's body into its exit code, using the
+//! fixed mapping described by [`ExitCode`].
+//!
+//! Nonzero syscall return codes surface as [`Error::from_raw_os_error`], and
+//! the well-known codes below are mapped onto an [`ErrorKind`] so callers can
+//! branch on [`Error::kind`] instead of matching numbers.
+//!
+//! [`Error::from_raw_os_error`]: crate::io::Error::from_raw_os_error
+//! [`Error::kind`]: crate::io::Error::kind
+//! [`BufReader`]: crate::io::BufReader
+//! [`Take`]: crate::io::Take
+//! [`read_exact`]: Read::read_exact
+mod exit;
+mod pipe;
+pub(crate) mod syscalls;
+// The tests need `alloc`.
+#[cfg(all(test, feature = "alloc"))]
+mod tests;
+
+pub use self::exit::{run, ExitCode};
+pub use self::pipe::{
+    inherited_fds, inherited_fds_with, pipe, pipe_with, InheritedFds, PipeReader, PipeWriter,
+    Pipes, MAX_FDS,
+};
+
+use crate::io::{self, const_io_error, ErrorKind, Read, ReadAt, Seek, SeekFrom};
+use core::cmp;
+
+const INVALID_SCRIPT: io::Error = const_io_error!(
+    ErrorKind::InvalidData,
+    "script is not a valid molecule table"
+);
+
+/// The requested index is past the end of its source.
+pub const INDEX_OUT_OF_BOUND: i64 = 1;
+/// The requested field is absent, e.g. a cell without a type script.
+pub const ITEM_MISSING: i64 = 2;
+/// The requested offset is past the end of the data.
+pub const SLICE_OUT_OF_BOUND: i64 = 3;
+/// The data does not have the expected format.
+pub const WRONG_FORMAT: i64 = 4;
+/// `wait` was called on a process that is not a child of the caller.
+pub const WAIT_FAILURE: i64 = 5;
+/// The file descriptor is invalid, or not owned by the caller.
+pub const INVALID_FD: i64 = 6;
+/// The other end of the pipe has been closed.
+pub const OTHER_END_CLOSED: i64 = 7;
+/// The maximum number of spawned VMs has been reached.
+pub const MAX_VMS_SPAWNED: i64 = 8;
+/// The maximum number of file descriptors has been reached.
+pub const MAX_FDS_CREATED: i64 = 9;
+
+/// Maps a syscall return code onto an [`ErrorKind`].
+pub(crate) fn decode_error_kind(code: i64) -> ErrorKind {
+    match code {
+        INDEX_OUT_OF_BOUND | ITEM_MISSING => ErrorKind::NotFound,
+        SLICE_OUT_OF_BOUND => ErrorKind::UnexpectedEof,
+        WRONG_FORMAT => ErrorKind::InvalidData,
+        WAIT_FAILURE | INVALID_FD => ErrorKind::InvalidInput,
+        OTHER_END_CLOSED => ErrorKind::BrokenPipe,
+        MAX_VMS_SPAWNED | MAX_FDS_CREATED => ErrorKind::ResourceBusy,
+        _ => ErrorKind::Other,
+    }
+}
+
+/// Returns a description of a syscall return code, if it is a known one.
+pub(crate) fn error_string(code: i64) -> Option<&'static str> {
+    Some(match code {
+        INDEX_OUT_OF_BOUND => "index out of bound",
+        ITEM_MISSING => "item missing",
+        SLICE_OUT_OF_BOUND => "slice out of bound",
+        WRONG_FORMAT => "wrong format",
+        WAIT_FAILURE => "wait failure",
+        INVALID_FD => "invalid file descriptor",
+        OTHER_END_CLOSED => "other end closed",
+        MAX_VMS_SPAWNED => "max vms spawned",
+        MAX_FDS_CREATED => "max fds created",
+        _ => return None,
+    })
+}
+
+/// Where a syscall looks for the cell, input, header or witness at a given
+/// index.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[repr(u64)]
+pub enum Source {
+    Input = 1,
+    Output = 2,
+    CellDep = 3,
+    HeaderDep = 4,
+    GroupInput = 0x0100000000000001,
+    GroupOutput = 0x0100000000000002,
+}
+
+/// A field of a cell, as accepted by `ckb_load_cell_by_field`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[repr(u64)]
+pub enum CellField {
+    Capacity = 0,
+    DataHash = 1,
+    Lock = 2,
+    LockHash = 3,
+    Type = 4,
+    TypeHash = 5,
+    OccupiedCapacity = 6,
+}
+
+/// A field of a header, as accepted by `ckb_load_header_by_field`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[repr(u64)]
+pub enum HeaderField {
+    EpochNumber = 0,
+    EpochStartBlockNumber = 1,
+    EpochLength = 2,
+}
+
+/// A field of an input, as accepted by `ckb_load_input_by_field`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[repr(u64)]
+pub enum InputField {
+    OutPoint = 0,
+    Since = 1,
+}
+
+/// The piece of transaction data a syscall loads.
+///
+/// Each variant corresponds to exactly one partial-loading syscall together
+/// with its `(index, source, field)` arguments.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Target {
+    /// `ckb_load_transaction`
+    Transaction,
+    /// `ckb_load_script`
+    Script,
+    /// `ckb_load_tx_hash`
+    TxHash,
+    /// `ckb_load_script_hash`
+    ScriptHash,
+    /// `ckb_load_cell`
+    Cell(usize, Source),
+    /// `ckb_load_header`
+    Header(usize, Source),
+    /// `ckb_load_input`
+    Input(usize, Source),
+    /// `ckb_load_witness`
+    Witness(usize, Source),
+    /// `ckb_load_cell_by_field`
+    CellByField(usize, Source, CellField),
+    /// `ckb_load_header_by_field`
+    HeaderByField(usize, Source, HeaderField),
+    /// `ckb_load_input_by_field`
+    InputByField(usize, Source, InputField),
+    /// `ckb_load_cell_data`
+    CellData(usize, Source),
+}
+
+impl Target {
+    /// The syscall number and the `a3`..`a5` arguments for this target.
+    fn syscall_args(&self) -> (u64, u64, u64, u64) {
+        use syscalls::*;
+        match *self {
+            Target::Transaction => (SYS_LOAD_TRANSACTION, 0, 0, 0),
+            Target::Script => (SYS_LOAD_SCRIPT, 0, 0, 0),
+            Target::TxHash => (SYS_LOAD_TX_HASH, 0, 0, 0),
+            Target::ScriptHash => (SYS_LOAD_SCRIPT_HASH, 0, 0, 0),
+            Target::Cell(i, s) => (SYS_LOAD_CELL, i as u64, s as u64, 0),
+            Target::Header(i, s) => (SYS_LOAD_HEADER, i as u64, s as u64, 0),
+            Target::Input(i, s) => (SYS_LOAD_INPUT, i as u64, s as u64, 0),
+            Target::Witness(i, s) => (SYS_LOAD_WITNESS, i as u64, s as u64, 0),
+            Target::CellByField(i, s, f) => (SYS_LOAD_CELL_BY_FIELD, i as u64, s as u64, f as u64),
+            Target::HeaderByField(i, s, f) => {
+                (SYS_LOAD_HEADER_BY_FIELD, i as u64, s as u64, f as u64)
+            }
+            Target::InputByField(i, s, f) => {
+                (SYS_LOAD_INPUT_BY_FIELD, i as u64, s as u64, f as u64)
+            }
+            Target::CellData(i, s) => (SYS_LOAD_CELL_DATA, i as u64, s as u64, 0),
+        }
+    }
+}
+
+/// A backend able to perform partial-loading syscalls.
+///
+/// `load` copies at most `buf.len()` bytes of `target`, starting at `offset`,
+/// into `buf`, and returns the full length of the data available from
+/// `offset` (which may exceed `buf.len()`). This mirrors the semantics of the
+/// CKB syscalls: calling it with an empty `buf` is how the length of a
+/// target is queried.
+///
+/// Failures are reported as [`Error::from_raw_os_error`] with the syscall's
+/// return code.
+///
+/// [`Error::from_raw_os_error`]: crate::io::Error::from_raw_os_error
+pub trait Loader {
+    fn load(&self, target: &Target, buf: &mut [u8], offset: usize) -> io::Result<usize>;
+}
+
+impl<L: Loader + ?Sized> Loader for &L {
+    #[inline]
+    fn load(&self, target: &Target, buf: &mut [u8], offset: usize) -> io::Result<usize> {
+        (**self).load(target, buf, offset)
+    }
+}
+
+/// The [`Loader`] issuing real CKB-VM syscalls.
+///
+/// On targets other than `riscv64` every load fails with
+/// [`ErrorKind::Unsupported`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Syscalls;
+
+impl Loader for Syscalls {
+    #[inline]
+    fn load(&self, target: &Target, buf: &mut [u8], offset: usize) -> io::Result<usize> {
+        let (syscall_num, a3, a4, a5) = target.syscall_args();
+        syscalls::load(buf, offset, a3, a4, a5, syscall_num)
+    }
+}
+
+/// A [`Read`] + [`Seek`] view of a window into one syscall [`Target`].
+///
+/// Every `read` turns into a single partial-loading syscall that copies
+/// exactly the bytes requested, so seeking is O(1) and nothing outside the
+/// window is ever loaded. Like [`Cursor`], seeking beyond the end of the
+/// window is allowed; subsequent reads simply return `Ok(0)`.
+///
+/// [`Cursor`]: crate::io::Cursor
+///
+/// # Examples
+///
+/// ```no_run
+/// use ckb_rust_std::io::{self, ckb::{self, Source}, BufRead, BufReader};
+///
+/// fn main() -> io::Result<()> {
+///     let witness = ckb::witness(0, Source::GroupInput)?;
+///     let mut reader = BufReader::new(witness);
+///     let header = reader.fill_buf()?;
+///     // ...
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct SyscallReader<L = Syscalls> {
+    loader: L,
+    target: Target,
+    // Offset of the window inside the target's data.
+    start: u64,
+    // Length of the window.
+    len: u64,
+    // Position relative to `start`. May exceed `len` after seeking.
+    pos: u64,
+}
+
+impl SyscallReader<Syscalls> {
+    /// Creates a reader over the whole of `target`, querying its length with
+    /// one zero-length syscall.
+    pub fn new(target: Target) -> io::Result<SyscallReader<Syscalls>> {
+        SyscallReader::with_loader(Syscalls, target)
+    }
+}
+
+impl<L: Loader> SyscallReader<L> {
+    /// Creates a reader over the whole of `target` using a custom [`Loader`].
+    pub fn with_loader(loader: L, target: Target) -> io::Result<SyscallReader<L>> {
+        let len = loader.load(&target, &mut [], 0)? as u64;
+        Ok(SyscallReader {
+            loader,
+            target,
+            start: 0,
+            len,
+            pos: 0,
+        })
+    }
+
+    /// Creates a reader over the `args` field of the current script.
+    ///
+    /// Only the molecule header of the `Script` table and the length prefix of
+    /// `args` are loaded to locate the window.
+    pub fn script_args_with_loader(loader: L) -> io::Result<SyscallReader<L>> {
+        // Script is a table of { code_hash, hash_type, args }: a total size
+        // followed by three field offsets, all little-endian u32.
+        let mut header = [0u8; 16];
+        let total = loader.load(&Target::Script, &mut header, 0)?;
+        let read_u32 =
+            |b: &[u8], i: usize| u32::from_le_bytes([b[i], b[i + 1], b[i + 2], b[i + 3]]);
+        if total < header.len() || read_u32(&header, 0) as usize != total {
+            return Err(INVALID_SCRIPT);
+        }
+        let args_offset = read_u32(&header, 12) as usize;
+        if args_offset > total - 4 {
+            return Err(INVALID_SCRIPT);
+        }
+        let mut args_len = [0u8; 4];
+        loader.load(&Target::Script, &mut args_len, args_offset)?;
+        let args_len = read_u32(&args_len, 0) as usize;
+        if args_len > total - args_offset - 4 {
+            return Err(INVALID_SCRIPT);
+        }
+        Ok(SyscallReader {
+            loader,
+            target: Target::Script,
+            start: (args_offset + 4) as u64,
+            len: args_len as u64,
+            pos: 0,
+        })
+    }
+
+    /// Narrows this reader to `len` bytes starting at `offset`, relative to
+    /// the current window. The position is reset to the start of the new
+    /// window.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ErrorKind::InvalidInput`] if the new window does not lie
+    /// within the current one.
+    pub fn window(self, offset: u64, len: u64) -> io::Result<SyscallReader<L>> {
+        match offset.checked_add(len) {
+            Some(end) if end <= self.len => Ok(SyscallReader {
+                start: self.start + offset,
+                len,
+                pos: 0,
+                ..self
+            }),
+            _ => Err(const_io_error!(
+                ErrorKind::InvalidInput,
+                "window exceeds the bounds of the syscall data",
+            )),
+        }
+    }
+}
+
+impl<L> SyscallReader<L> {
+    /// Returns the syscall target this reader loads from.
+    pub fn target(&self) -> &Target {
+        &self.target
+    }
+
+    /// Returns the length of the window in bytes.
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Returns `true` if the window is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Gets a reference to the underlying loader.
+    pub fn get_ref(&self) -> &L {
+        &self.loader
+    }
+
+    /// Consumes this reader, returning the underlying loader.
+    pub fn into_inner(self) -> L {
+        self.loader
+    }
+}
+
+impl<L: Loader> Read for SyscallReader<L> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.read_at(buf, self.pos)?;
+        self.pos += n as u64;
+        Ok(n)
+    }
+
+    fn read_exact(&mut self, buf: &mut [u8]) -> io::Result<()> {
+        let remaining = self.len.saturating_sub(self.pos);
+        if buf.len() as u64 > remaining {
+            // Match `Cursor`: a failed `read_exact` leaves the reader at EOF.
+            self.pos = cmp::max(self.pos, self.len);
+            return Err(io::Error::READ_EXACT_EOF.at_caller());
+        }
+        io::default_read_exact(self, buf)
+    }
+}
+
+/// Reads at an offset into the window, leaving the reader's position
+/// untouched.
+impl<L: Loader> ReadAt for SyscallReader<L> {
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        let remaining = self.len.saturating_sub(offset);
+        let amt = cmp::min(buf.len() as u64, remaining) as usize;
+        if amt == 0 {
+            return Ok(0);
+        }
+        let offset = (self.start + offset) as usize;
+        let available = self.loader.load(&self.target, &mut buf[..amt], offset)?;
+        Ok(cmp::min(amt, available))
+    }
+
+    fn size(&self) -> Option<u64> {
+        Some(self.len)
+    }
+}
+
+impl<L> Seek for SyscallReader<L> {
+    fn seek(&mut self, style: SeekFrom) -> io::Result<u64> {
+        self.pos = io::seek_position(style, self.pos, || Ok(self.len))?;
+        Ok(self.pos)
+    }
+
+    fn stream_len(&mut self) -> io::Result<u64> {
+        Ok(self.len)
+    }
+
+    fn stream_position(&mut self) -> io::Result<u64> {
+        Ok(self.pos)
+    }
+}
+
+/// Creates a reader over the data of the cell at `index` in `source`.
+pub fn cell_data(index: usize, source: Source) -> io::Result<SyscallReader> {
+    SyscallReader::new(Target::CellData(index, source))
+}
+
+/// Creates a reader over the witness at `index` in `source`.
+pub fn witness(index: usize, source: Source) -> io::Result<SyscallReader> {
+    SyscallReader::new(Target::Witness(index, source))
+}
+
+/// Creates a reader over the molecule-encoded script being executed.
+pub fn script() -> io::Result<SyscallReader> {
+    SyscallReader::new(Target::Script)
+}
+
+/// Creates a reader over the `args` of the script being executed.
+pub fn script_args() -> io::Result<SyscallReader> {
+    SyscallReader::script_args_with_loader(Syscalls)
+}
@@ -0,0 +1,288 @@
+//! Byte streams between spawned scripts.
+//!
+//! Since the second hardfork (CKB-VM v2), a script can `spawn` child scripts
+//! and talk to them over pipes. [`pipe()`] creates a connected
+//! [`PipeReader`] and [`PipeWriter`]; a child receives the fds passed to
+//! `spawn` through [`inherited_fds()`]. Both handles own their fd and close
+//! it when dropped.
+// The tests need `alloc`.
+#[cfg(all(test, feature = "alloc"))]
+mod tests;
+
+use super::{syscalls, Syscalls, OTHER_END_CLOSED};
+use crate::io::{self, const_io_error, ErrorKind, Read, Write};
+use core::mem::ManuallyDrop;
+use core::{cmp, fmt, ptr};
+
+const WOULD_BLOCK: io::Error = const_io_error!(
+    ErrorKind::WouldBlock,
+    "pipe transferred no data while its other end is open"
+);
+
+/// The largest number of fds a script can hold, and so inherit.
+pub const MAX_FDS: usize = 64;
+
+/// A backend for the pipe syscalls.
+///
+/// [`Syscalls`] issues the real syscalls; tests can plug in an in-process
+/// implementation. Failures are reported as [`Error::from_raw_os_error`]
+/// with the syscall's return code, e.g. [`OTHER_END_CLOSED`]. A backend
+/// that cannot block returns `Ok(0)` when it transfers nothing while the
+/// other end is still open; the pipe ends report that as
+/// [`ErrorKind::WouldBlock`].
+///
+/// [`Error::from_raw_os_error`]: crate::io::Error::from_raw_os_error
+pub trait Pipes {
+    /// Creates a pipe, returning its read and write fds.
+    fn pipe(&self) -> io::Result<(u64, u64)>;
+
+    /// Reads from the read end `fd`, returning the number of bytes read.
+    fn read(&self, fd: u64, buf: &mut [u8]) -> io::Result<usize>;
+
+    /// Writes to the write end `fd`, returning the number of bytes written.
+    fn write(&self, fd: u64, buf: &[u8]) -> io::Result<usize>;
+
+    /// Closes `fd`.
+    fn close(&self, fd: u64) -> io::Result<()>;
+
+    /// Copies the fds inherited from the parent into `fds`, returning how
+    /// many there are in total.
+    fn inherited_fds(&self, fds: &mut [u64]) -> io::Result<usize>;
+}
+
+impl<P: Pipes + ?Sized> Pipes for &P {
+    #[inline]
+    fn pipe(&self) -> io::Result<(u64, u64)> {
+        (**self).pipe()
+    }
+
+    #[inline]
+    fn read(&self, fd: u64, buf: &mut [u8]) -> io::Result<usize> {
+        (**self).read(fd, buf)
+    }
+
+    #[inline]
+    fn write(&self, fd: u64, buf: &[u8]) -> io::Result<usize> {
+        (**self).write(fd, buf)
+    }
+
+    #[inline]
+    fn close(&self, fd: u64) -> io::Result<()> {
+        (**self).close(fd)
+    }
+
+    #[inline]
+    fn inherited_fds(&self, fds: &mut [u64]) -> io::Result<usize> {
+        (**self).inherited_fds(fds)
+    }
+}
+
+impl Pipes for Syscalls {
+    #[inline]
+    fn pipe(&self) -> io::Result<(u64, u64)> {
+        syscalls::pipe()
+    }
+
+    #[inline]
+    fn read(&self, fd: u64, buf: &mut [u8]) -> io::Result<usize> {
+        syscalls::transfer(fd, buf.as_mut_ptr(), buf.len(), syscalls::SYS_READ)
+    }
+
+    #[inline]
+    fn write(&self, fd: u64, buf: &[u8]) -> io::Result<usize> {
+        syscalls::transfer(fd, buf.as_ptr(), buf.len(), syscalls::SYS_WRITE)
+    }
+
+    #[inline]
+    fn close(&self, fd: u64) -> io::Result<()> {
+        syscalls::close(fd)
+    }
+
+    #[inline]
+    fn inherited_fds(&self, fds: &mut [u64]) -> io::Result<usize> {
+        syscalls::inherited_fds(fds)
+    }
+}
+
+macro_rules! pipe_end {
+    ($name:ident, $what:literal) => {
+        impl $name<Syscalls> {
+            #[doc = concat!("Takes ownership of the ", $what, " end `fd`, e.g. one returned by")]
+            /// [`inherited_fds()`].
+            pub fn from_raw_fd(fd: u64) -> $name {
+                $name::from_raw_fd_with(fd, Syscalls)
+            }
+        }
+
+        impl<P: Pipes> $name<P> {
+            #[doc = concat!("Takes ownership of the ", $what, " end `fd` of `pipes`.")]
+            pub fn from_raw_fd_with(fd: u64, pipes: P) -> $name<P> {
+                $name { fd, pipes }
+            }
+
+            /// Returns the fd, e.g. to pass it to a spawned script.
+            pub fn as_raw_fd(&self) -> u64 {
+                self.fd
+            }
+
+            /// Consumes the handle and returns the fd without closing it.
+            pub fn into_raw_fd(self) -> u64 {
+                let this = ManuallyDrop::new(self);
+                // SAFETY: `this` is never dropped, so `pipes` is read exactly
+                // once.
+                drop(unsafe { ptr::read(&this.pipes) });
+                this.fd
+            }
+
+            /// Closes the fd, reporting any error that dropping the handle
+            /// would ignore.
+            pub fn close(self) -> io::Result<()> {
+                let this = ManuallyDrop::new(self);
+                // SAFETY: as in `into_raw_fd`.
+                let pipes = unsafe { ptr::read(&this.pipes) };
+                pipes.close(this.fd)
+            }
+        }
+
+        impl<P: Pipes> Drop for $name<P> {
+            fn drop(&mut self) {
+                let _ = self.pipes.close(self.fd);
+            }
+        }
+
+        impl<P: Pipes> fmt::Debug for $name<P> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.debug_struct(stringify!($name))
+                    .field("fd", &self.fd)
+                    .finish_non_exhaustive()
+            }
+        }
+    };
+}
+
+/// The read end of a pipe.
+///
+/// Reads block until the other end writes some data. Once the write end has
+/// been closed and all data consumed, reads return `Ok(0)`. If the backend
+/// returns without data while the write end is open, reads fail with
+/// [`ErrorKind::WouldBlock`].
+pub struct PipeReader<P: Pipes = Syscalls> {
+    fd: u64,
+    pipes: P,
+}
+
+pipe_end!(PipeReader, "read");
+
+impl<P: Pipes> Read for PipeReader<P> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        match self.pipes.read(self.fd, buf) {
+            Ok(0) => Err(WOULD_BLOCK),
+            Err(e) if e.raw_os_error() == Some(OTHER_END_CLOSED) => Ok(0),
+            result => result,
+        }
+    }
+}
+
+/// The write end of a pipe.
+///
+/// Writes block until the other end reads the data. Once the read end has
+/// been closed, writes fail with [`ErrorKind::BrokenPipe`]. If the backend
+/// accepts nothing while the read end is open, writes fail with
+/// [`ErrorKind::WouldBlock`].
+pub struct PipeWriter<P: Pipes = Syscalls> {
+    fd: u64,
+    pipes: P,
+}
+
+pipe_end!(PipeWriter, "write");
+
+impl<P: Pipes> Write for PipeWriter<P> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        match self.pipes.write(self.fd, buf) {
+            Ok(0) => Err(WOULD_BLOCK),
+            result => result,
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Creates a pipe.
+///
+/// # Examples
+///
+/// ```no_run
+/// use ckb_rust_std::io::{self, ckb, Write};
+///
+/// fn main() -> io::Result<()> {
+///     let (reader, mut writer) = ckb::pipe()?;
+///     // Pass `reader.as_raw_fd()` to a spawned script, then:
+///     writer.write_all(b"hello")?;
+///     Ok(())
+/// }
+/// ```
+pub fn pipe() -> io::Result<(PipeReader, PipeWriter)> {
+    pipe_with(Syscalls)
+}
+
+/// Creates a pipe through `pipes`.
+pub fn pipe_with<P: Pipes + Clone>(pipes: P) -> io::Result<(PipeReader<P>, PipeWriter<P>)> {
+    let (read_fd, write_fd) = pipes.pipe()?;
+    Ok((
+        PipeReader::from_raw_fd_with(read_fd, pipes.clone()),
+        PipeWriter::from_raw_fd_with(write_fd, pipes),
+    ))
+}
+
+/// An iterator over the fds inherited from the parent script.
+///
+/// Created by [`inherited_fds()`]. Whether each fd is a read or a write end
+/// is up to the parent; wrap it with [`PipeReader::from_raw_fd`] or
+/// [`PipeWriter::from_raw_fd`] accordingly.
+#[derive(Clone, Debug)]
+pub struct InheritedFds {
+    fds: [u64; MAX_FDS],
+    pos: usize,
+    len: usize,
+}
+
+impl Iterator for InheritedFds {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        let fd = *self.fds[..self.len].get(self.pos)?;
+        self.pos += 1;
+        Some(fd)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = self.len - self.pos;
+        (n, Some(n))
+    }
+}
+
+impl ExactSizeIterator for InheritedFds {}
+
+/// Returns the fds the parent passed to `spawn`, in order.
+pub fn inherited_fds() -> io::Result<InheritedFds> {
+    inherited_fds_with(Syscalls)
+}
+
+/// Returns the fds inherited from the parent through `pipes`.
+pub fn inherited_fds_with<P: Pipes>(pipes: P) -> io::Result<InheritedFds> {
+    let mut fds = [0u64; MAX_FDS];
+    let len = pipes.inherited_fds(&mut fds)?;
+    Ok(InheritedFds {
+        fds,
+        pos: 0,
+        len: cmp::min(len, MAX_FDS),
+    })
+}
@@ -0,0 +1,244 @@
+use super::*;
+use crate::io::ckb::INVALID_FD;
+use crate::io::{BufRead, BufReader, Error, ErrorKind, LineWriter};
+use alloc::collections::VecDeque;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+
+#[derive(Debug, Default)]
+struct Channel {
+    data: VecDeque<u8>,
+    reader_open: bool,
+    writer_open: bool,
+}
+
+/// An in-process pipe backend. Channel `i` has the read fd `2 * i` and the
+/// write fd `2 * i + 1`. Since nothing can block, reading an empty pipe or
+/// writing a full one while the other end is open transfers nothing.
+#[derive(Debug, Default)]
+struct MockPipes {
+    channels: RefCell<Vec<Channel>>,
+    inherited: Vec<u64>,
+    // The most bytes a single write accepts, to exercise short writes.
+    max_write: usize,
+    // The most bytes a channel holds.
+    capacity: usize,
+}
+
+impl MockPipes {
+    fn new() -> MockPipes {
+        MockPipes {
+            max_write: 7,
+            capacity: 1024,
+            ..MockPipes::default()
+        }
+    }
+
+    fn is_open(&self, fd: u64) -> bool {
+        let channels = self.channels.borrow();
+        let channel = &channels[fd as usize / 2];
+        if fd % 2 == 0 {
+            channel.reader_open
+        } else {
+            channel.writer_open
+        }
+    }
+}
+
+fn invalid_fd() -> Error {
+    Error::from_raw_os_error(INVALID_FD)
+}
+
+impl Pipes for MockPipes {
+    fn pipe(&self) -> io::Result<(u64, u64)> {
+        let mut channels = self.channels.borrow_mut();
+        channels.push(Channel {
+            data: VecDeque::new(),
+            reader_open: true,
+            writer_open: true,
+        });
+        let i = channels.len() as u64 - 1;
+        Ok((2 * i, 2 * i + 1))
+    }
+
+    fn read(&self, fd: u64, buf: &mut [u8]) -> io::Result<usize> {
+        let mut channels = self.channels.borrow_mut();
+        let channel = match channels.get_mut(fd as usize / 2) {
+            Some(channel) if fd % 2 == 0 && channel.reader_open => channel,
+            _ => return Err(invalid_fd()),
+        };
+        if channel.data.is_empty() {
+            return if channel.writer_open {
+                Ok(0)
+            } else {
+                Err(Error::from_raw_os_error(OTHER_END_CLOSED))
+            };
+        }
+        channel.data.read(buf)
+    }
+
+    fn write(&self, fd: u64, buf: &[u8]) -> io::Result<usize> {
+        let mut channels = self.channels.borrow_mut();
+        let channel = match channels.get_mut(fd as usize / 2) {
+            Some(channel) if fd % 2 != 0 && channel.writer_open => channel,
+            _ => return Err(invalid_fd()),
+        };
+        if !channel.reader_open {
+            return Err(Error::from_raw_os_error(OTHER_END_CLOSED));
+        }
+        let room = self.capacity - channel.data.len();
+        let n = cmp::min(buf.len(), cmp::min(self.max_write, room));
+        channel.data.extend(&buf[..n]);
+        Ok(n)
+    }
+
+    fn close(&self, fd: u64) -> io::Result<()> {
+        let mut channels = self.channels.borrow_mut();
+        let channel = channels.get_mut(fd as usize / 2).ok_or_else(invalid_fd)?;
+        let open = if fd % 2 == 0 {
+            &mut channel.reader_open
+        } else {
+            &mut channel.writer_open
+        };
+        if !*open {
+            return Err(invalid_fd());
+        }
+        *open = false;
+        Ok(())
+    }
+
+    fn inherited_fds(&self, fds: &mut [u64]) -> io::Result<usize> {
+        let n = cmp::min(fds.len(), self.inherited.len());
+        fds[..n].copy_from_slice(&self.inherited[..n]);
+        Ok(self.inherited.len())
+    }
+}
+
+#[test]
+fn round_trip_to_eof() {
+    let pipes = MockPipes::new();
+    let (mut reader, mut writer) = pipe_with(&pipes).unwrap();
+    writer.write_all(b"hello, child script").unwrap();
+    drop(writer);
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data).unwrap();
+    assert_eq!(data, b"hello, child script");
+    assert_eq!(reader.read(&mut [0; 4]).unwrap(), 0);
+}
+
+#[test]
+fn would_block_while_writer_is_open() {
+    let pipes = MockPipes::new();
+    let (mut reader, mut writer) = pipe_with(&pipes).unwrap();
+    let err = reader.read(&mut [0; 4]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::WouldBlock);
+    writer.write_all(b"ab").unwrap();
+    let mut buf = [0; 4];
+    assert_eq!(reader.read(&mut buf).unwrap(), 2);
+    // An empty read never reaches the backend.
+    assert_eq!(reader.read(&mut []).unwrap(), 0);
+}
+
+#[test]
+fn would_block_while_reader_is_open() {
+    let pipes = MockPipes {
+        capacity: 4,
+        ..MockPipes::new()
+    };
+    let (mut reader, mut writer) = pipe_with(&pipes).unwrap();
+    assert_eq!(writer.write(b"abcdef").unwrap(), 4);
+    let err = writer.write(b"ef").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::WouldBlock);
+    let err = writer.write_all(b"ef").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::WouldBlock);
+    assert_eq!(reader.read(&mut [0; 3]).unwrap(), 3);
+    assert_eq!(writer.write(b"ef").unwrap(), 2);
+    // An empty write never reaches the backend.
+    assert_eq!(writer.write(&[]).unwrap(), 0);
+}
+
+#[test]
+fn broken_pipe() {
+    let pipes = MockPipes::new();
+    let (reader, mut writer) = pipe_with(&pipes).unwrap();
+    drop(reader);
+    let err = writer.write(b"x").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::BrokenPipe);
+    assert_eq!(err.raw_os_error(), Some(OTHER_END_CLOSED));
+    let err = writer.write_all(b"xyz").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::BrokenPipe);
+}
+
+#[test]
+fn buffered_lines() {
+    let pipes = MockPipes::new();
+    let (reader, writer) = pipe_with(&pipes).unwrap();
+    let mut writer = LineWriter::new(writer);
+    writeln!(writer, "first line, longer than one write").unwrap();
+    write!(writer, "second").unwrap();
+    writer.flush().unwrap();
+    drop(writer);
+
+    let lines: Vec<String> = BufReader::new(reader)
+        .lines()
+        .collect::<io::Result<_>>()
+        .unwrap();
+    assert_eq!(lines, ["first line, longer than one write", "second"]);
+}
+
+#[test]
+fn copy_through_pipe() {
+    let pipes = MockPipes::new();
+    let (mut reader, mut writer) = pipe_with(&pipes).unwrap();
+    let data: Vec<u8> = (0..=255).collect();
+    assert_eq!(io::copy(&mut &data[..], &mut writer).unwrap(), 256);
+    drop(writer);
+    let mut out = Vec::new();
+    assert_eq!(io::copy(&mut reader, &mut out).unwrap(), 256);
+    assert_eq!(out, data);
+}
+
+#[test]
+fn ownership_of_fds() {
+    let pipes = MockPipes::new();
+    let (reader, writer) = pipe_with(&pipes).unwrap();
+    let (read_fd, write_fd) = (reader.as_raw_fd(), writer.as_raw_fd());
+
+    assert_eq!(reader.into_raw_fd(), read_fd);
+    assert!(pipes.is_open(read_fd));
+    drop(writer);
+    assert!(!pipes.is_open(write_fd));
+
+    let reader = PipeReader::from_raw_fd_with(read_fd, &pipes);
+    reader.close().unwrap();
+    assert!(!pipes.is_open(read_fd));
+    let err = PipeReader::from_raw_fd_with(read_fd, &pipes)
+        .close()
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+}
+
+#[test]
+fn inherited() {
+    let pipes = MockPipes {
+        inherited: vec![3, 6],
+        ..MockPipes::new()
+    };
+    let fds = inherited_fds_with(&pipes).unwrap();
+    assert_eq!(fds.len(), 2);
+    assert_eq!(fds.collect::<Vec<_>>(), [3, 6]);
+    assert_eq!(inherited_fds_with(MockPipes::new()).unwrap().count(), 0);
+}
+
+#[test]
+fn syscalls_unsupported_on_host() {
+    if cfg!(target_arch = "riscv64") {
+        return;
+    }
+    let err = pipe().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Unsupported);
+    let err = inherited_fds().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Unsupported);
+}
@@ -0,0 +1,200 @@
+//! Raw CKB-VM syscalls.
+//!
+//! Only the RISC-V build issues real `ecall`s. On every other target the
+//! functions in this module report [`Error::UNSUPPORTED_PLATFORM`], so code
+//! depending on them still builds (and can be unit tested through a mock
+//! backend) on the host.
+#[cfg(feature = "alloc")]
+use core::ffi::CStr;
+
+use crate::io::{Error, Result};
+
+pub(crate) const SYS_LOAD_TRANSACTION: u64 = 2051;
+pub(crate) const SYS_LOAD_SCRIPT: u64 = 2052;
+pub(crate) const SYS_LOAD_TX_HASH: u64 = 2061;
+pub(crate) const SYS_LOAD_SCRIPT_HASH: u64 = 2062;
+pub(crate) const SYS_LOAD_CELL: u64 = 2071;
+pub(crate) const SYS_LOAD_HEADER: u64 = 2072;
+pub(crate) const SYS_LOAD_INPUT: u64 = 2073;
+pub(crate) const SYS_LOAD_WITNESS: u64 = 2074;
+pub(crate) const SYS_LOAD_CELL_BY_FIELD: u64 = 2081;
+pub(crate) const SYS_LOAD_HEADER_BY_FIELD: u64 = 2082;
+pub(crate) const SYS_LOAD_INPUT_BY_FIELD: u64 = 2083;
+pub(crate) const SYS_LOAD_CELL_DATA: u64 = 2092;
+#[cfg(target_arch = "riscv64")]
+pub(crate) const SYS_PIPE: u64 = 2604;
+pub(crate) const SYS_WRITE: u64 = 2605;
+pub(crate) const SYS_READ: u64 = 2606;
+#[cfg(target_arch = "riscv64")]
+pub(crate) const SYS_INHERITED_FDS: u64 = 2607;
+#[cfg(target_arch = "riscv64")]
+pub(crate) const SYS_CLOSE: u64 = 2608;
+#[cfg(all(feature = "alloc", target_arch = "riscv64"))]
+pub(crate) const SYS_DEBUG: u64 = 2177;
+
+#[cfg(target_arch = "riscv64")]
+#[inline(always)]
+unsafe fn syscall(mut a0: u64, a1: u64, a2: u64, a3: u64, a4: u64, a5: u64, a7: u64) -> u64 {
+    // SAFETY: the caller guarantees that every pointer passed in the argument
+    // registers is valid for the access the syscall `a7` performs.
+    unsafe {
+        core::arch::asm!(
+            "ecall",
+            inout("a0") a0,
+            in("a1") a1,
+            in("a2") a2,
+            in("a3") a3,
+            in("a4") a4,
+            in("a5") a5,
+            in("a7") a7,
+        );
+    }
+    a0
+}
+
+/// Issues one of the partial-loading syscalls.
+///
+/// At most `buf.len()` bytes starting at `offset` are copied into `buf`. On
+/// success the full length of the data available from `offset` is returned,
+/// which may be larger than `buf.len()`.
+#[cfg(target_arch = "riscv64")]
+pub(crate) fn load(
+    buf: &mut [u8],
+    offset: usize,
+    a3: u64,
+    a4: u64,
+    a5: u64,
+    syscall_num: u64,
+) -> Result<usize> {
+    let mut len = buf.len() as u64;
+    // SAFETY: `buf` is valid for `len` bytes of writes and `len` outlives the
+    // call. Syscalls which do not take `a3`..`a5` ignore them.
+    let ret = unsafe {
+        syscall(
+            buf.as_mut_ptr() as u64,
+            &mut len as *mut u64 as u64,
+            offset as u64,
+            a3,
+            a4,
+            a5,
+            syscall_num,
+        )
+    };
+    match ret {
+        0 => Ok(len as usize),
+        code => Err(Error::from_raw_os_error(code as i64)),
+    }
+}
+
+#[cfg(not(target_arch = "riscv64"))]
+pub(crate) fn load(
+    _buf: &mut [u8],
+    _offset: usize,
+    _a3: u64,
+    _a4: u64,
+    _a5: u64,
+    _syscall_num: u64,
+) -> Result<usize> {
+    Err(Error::UNSUPPORTED_PLATFORM)
+}
+
+/// Prints `msg` to the CKB-VM debug output.
+#[cfg(all(feature = "alloc", target_arch = "riscv64"))]
+pub(crate) fn debug(msg: &CStr) -> Result<()> {
+    // SAFETY: `msg` is a valid NUL-terminated string for the whole call.
+    unsafe {
+        syscall(msg.as_ptr() as u64, 0, 0, 0, 0, 0, SYS_DEBUG);
+    }
+    Ok(())
+}
+
+#[cfg(all(feature = "alloc", not(target_arch = "riscv64")))]
+pub(crate) fn debug(_msg: &CStr) -> Result<()> {
+    Err(Error::UNSUPPORTED_PLATFORM)
+}
+
+#[cfg(target_arch = "riscv64")]
+fn check(ret: u64) -> Result<()> {
+    match ret {
+        0 => Ok(()),
+        code => Err(Error::from_raw_os_error(code as i64)),
+    }
+}
+
+/// Creates a pipe, returning its read and write ends.
+#[cfg(target_arch = "riscv64")]
+pub(crate) fn pipe() -> Result<(u64, u64)> {
+    let mut fds = [0u64; 2];
+    // SAFETY: `fds` is valid for two `u64` writes.
+    check(unsafe { syscall(fds.as_mut_ptr() as u64, 0, 0, 0, 0, 0, SYS_PIPE) })?;
+    Ok((fds[0], fds[1]))
+}
+
+/// Issues `read` or `write` on a pipe end, returning the number of bytes
+/// transferred.
+#[cfg(target_arch = "riscv64")]
+pub(crate) fn transfer(fd: u64, buf: *const u8, len: usize, syscall_num: u64) -> Result<usize> {
+    let mut len = len as u64;
+    // SAFETY: the caller passes a `buf` valid for `len` bytes of the access
+    // `syscall_num` performs, and `len` outlives the call.
+    check(unsafe {
+        syscall(
+            fd,
+            buf as u64,
+            &mut len as *mut u64 as u64,
+            0,
+            0,
+            0,
+            syscall_num,
+        )
+    })?;
+    Ok(len as usize)
+}
+
+/// Fills `fds` with the fds inherited from the parent, returning how many
+/// there are in total.
+#[cfg(target_arch = "riscv64")]
+pub(crate) fn inherited_fds(fds: &mut [u64]) -> Result<usize> {
+    let mut len = fds.len() as u64;
+    // SAFETY: `fds` is valid for `len` `u64` writes and `len` outlives the
+    // call.
+    check(unsafe {
+        syscall(
+            fds.as_mut_ptr() as u64,
+            &mut len as *mut u64 as u64,
+            0,
+            0,
+            0,
+            0,
+            SYS_INHERITED_FDS,
+        )
+    })?;
+    Ok(len as usize)
+}
+
+/// Closes a pipe end.
+#[cfg(target_arch = "riscv64")]
+pub(crate) fn close(fd: u64) -> Result<()> {
+    // SAFETY: `close` takes no pointers.
+    check(unsafe { syscall(fd, 0, 0, 0, 0, 0, SYS_CLOSE) })
+}
+
+#[cfg(not(target_arch = "riscv64"))]
+pub(crate) fn pipe() -> Result<(u64, u64)> {
+    Err(Error::UNSUPPORTED_PLATFORM)
+}
+
+#[cfg(not(target_arch = "riscv64"))]
+pub(crate) fn transfer(_fd: u64, _buf: *const u8, _len: usize, _syscall_num: u64) -> Result<usize> {
+    Err(Error::UNSUPPORTED_PLATFORM)
+}
+
+#[cfg(not(target_arch = "riscv64"))]
+pub(crate) fn inherited_fds(_fds: &mut [u64]) -> Result<usize> {
+    Err(Error::UNSUPPORTED_PLATFORM)
+}
+
+#[cfg(not(target_arch = "riscv64"))]
+pub(crate) fn close(_fd: u64) -> Result<()> {
+    Err(Error::UNSUPPORTED_PLATFORM)
+}
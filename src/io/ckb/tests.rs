@@ -0,0 +1,308 @@
+use super::*;
+use crate::io::{BufRead, BufReader, Error};
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cell::Cell;
+
+/// An in-memory backend with CKB partial-loading semantics that records how
+/// many bytes were copied out.
+#[derive(Debug)]
+struct MockLoader {
+    script: Vec<u8>,
+    witnesses: Vec<Vec<u8>>,
+    loaded: Cell<usize>,
+    calls: Cell<usize>,
+}
+
+impl MockLoader {
+    fn new(witnesses: Vec<Vec<u8>>) -> MockLoader {
+        MockLoader {
+            script: Vec::new(),
+            witnesses,
+            loaded: Cell::new(0),
+            calls: Cell::new(0),
+        }
+    }
+
+    fn with_script(script: Vec<u8>) -> MockLoader {
+        MockLoader {
+            script,
+            ..MockLoader::new(Vec::new())
+        }
+    }
+}
+
+impl Loader for MockLoader {
+    fn load(&self, target: &Target, buf: &mut [u8], offset: usize) -> io::Result<usize> {
+        self.calls.set(self.calls.get() + 1);
+        let data = match *target {
+            Target::Script => &self.script,
+            Target::Witness(i, Source::Input) => {
+                self.witnesses.get(i).ok_or(Error::from_raw_os_error(1))?
+            }
+            _ => return Err(Error::from_raw_os_error(2)),
+        };
+        if offset > data.len() {
+            return Err(Error::from_raw_os_error(3));
+        }
+        let available = data.len() - offset;
+        let n = cmp::min(available, buf.len());
+        buf[..n].copy_from_slice(&data[offset..offset + n]);
+        self.loaded.set(self.loaded.get() + n);
+        Ok(available)
+    }
+}
+
+fn sample() -> Vec<u8> {
+    (0..=255).collect()
+}
+
+fn script_bytes(args: &[u8]) -> Vec<u8> {
+    let code_hash = [0xaa; 32];
+    let hash_type = [1u8];
+    let header_len = 16u32;
+    let args_offset = header_len + code_hash.len() as u32 + hash_type.len() as u32;
+    let total = args_offset + 4 + args.len() as u32;
+    let mut v = Vec::new();
+    v.extend_from_slice(&total.to_le_bytes());
+    v.extend_from_slice(&header_len.to_le_bytes());
+    v.extend_from_slice(&(header_len + 32).to_le_bytes());
+    v.extend_from_slice(&args_offset.to_le_bytes());
+    v.extend_from_slice(&code_hash);
+    v.extend_from_slice(&hash_type);
+    v.extend_from_slice(&(args.len() as u32).to_le_bytes());
+    v.extend_from_slice(args);
+    v
+}
+
+#[test]
+fn read_whole_target() {
+    let loader = MockLoader::new(vec![sample()]);
+    let mut reader =
+        SyscallReader::with_loader(&loader, Target::Witness(0, Source::Input)).unwrap();
+    assert_eq!(reader.len(), 256);
+    let mut out = Vec::new();
+    reader.read_to_end(&mut out).unwrap();
+    assert_eq!(out, sample());
+    assert_eq!(reader.read(&mut [0; 4]).unwrap(), 0);
+}
+
+#[test]
+fn missing_target_reports_os_error() {
+    let loader = MockLoader::new(vec![]);
+    let err = SyscallReader::with_loader(&loader, Target::Witness(0, Source::Input)).unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(1));
+}
+
+#[test]
+fn read_only_loads_requested_bytes() {
+    let loader = MockLoader::new(vec![sample()]);
+    let mut reader =
+        SyscallReader::with_loader(&loader, Target::Witness(0, Source::Input)).unwrap();
+    assert_eq!(loader.loaded.get(), 0);
+
+    reader.seek(SeekFrom::Start(200)).unwrap();
+    let mut buf = [0; 8];
+    reader.read_exact(&mut buf).unwrap();
+    assert_eq!(buf, [200, 201, 202, 203, 204, 205, 206, 207]);
+    assert_eq!(loader.loaded.get(), 8);
+
+    // A large buffer is not filled past the end of the window.
+    let mut big = [0; 512];
+    assert_eq!(reader.read(&mut big).unwrap(), 48);
+    assert_eq!(loader.loaded.get(), 56);
+}
+
+#[test]
+fn seek() {
+    let loader = MockLoader::new(vec![sample()]);
+    let mut reader =
+        SyscallReader::with_loader(&loader, Target::Witness(0, Source::Input)).unwrap();
+    let calls = loader.calls.get();
+
+    assert_eq!(reader.seek(SeekFrom::End(-1)).unwrap(), 255);
+    assert_eq!(reader.seek(SeekFrom::Current(-5)).unwrap(), 250);
+    assert_eq!(reader.stream_position().unwrap(), 250);
+    assert_eq!(reader.stream_len().unwrap(), 256);
+    assert!(reader.seek(SeekFrom::Current(-251)).is_err());
+    assert_eq!(reader.stream_position().unwrap(), 250);
+    // Seeking never touches the backend.
+    assert_eq!(loader.calls.get(), calls);
+
+    let mut b = [0];
+    reader.read_exact(&mut b).unwrap();
+    assert_eq!(b, [250]);
+
+    // Seeking past the end is fine; reads return EOF without a syscall.
+    assert_eq!(reader.seek(SeekFrom::Start(1000)).unwrap(), 1000);
+    let calls = loader.calls.get();
+    assert_eq!(reader.read(&mut b).unwrap(), 0);
+    assert_eq!(loader.calls.get(), calls);
+}
+
+#[test]
+fn window() {
+    let loader = MockLoader::new(vec![sample()]);
+    let reader = SyscallReader::with_loader(&loader, Target::Witness(0, Source::Input)).unwrap();
+    let mut reader = reader.window(10, 20).unwrap().window(5, 10).unwrap();
+    assert_eq!(reader.len(), 10);
+
+    let mut out = Vec::new();
+    reader.read_to_end(&mut out).unwrap();
+    assert_eq!(out, (15..25).collect::<Vec<u8>>());
+    assert_eq!(loader.loaded.get(), 10);
+
+    assert_eq!(reader.seek(SeekFrom::End(-2)).unwrap(), 8);
+    let mut b = [0; 2];
+    reader.read_exact(&mut b).unwrap();
+    assert_eq!(b, [23, 24]);
+
+    let reader = SyscallReader::with_loader(&loader, Target::Witness(0, Source::Input)).unwrap();
+    let err = reader.window(250, 7).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+}
+
+#[test]
+fn read_exact_past_window() {
+    let loader = MockLoader::new(vec![sample()]);
+    let reader = SyscallReader::with_loader(&loader, Target::Witness(0, Source::Input)).unwrap();
+    let mut reader = reader.window(0, 4).unwrap();
+    let mut buf = [0; 5];
+    let err = reader.read_exact(&mut buf).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+    // Nothing was loaded for the failed request.
+    assert_eq!(loader.loaded.get(), 0);
+    assert_eq!(reader.stream_position().unwrap(), 4);
+}
+
+#[test]
+fn with_buf_reader_and_take() {
+    let mut data = b"hello\nworld\n".to_vec();
+    data.extend_from_slice(&[0; 100]);
+    let loader = MockLoader::new(vec![data]);
+    let reader = SyscallReader::with_loader(&loader, Target::Witness(0, Source::Input)).unwrap();
+    let mut reader = BufReader::with_capacity(4, reader.take(12));
+
+    let mut line = String::new();
+    reader.read_line(&mut line).unwrap();
+    assert_eq!(line, "hello\n");
+    line.clear();
+    reader.read_line(&mut line).unwrap();
+    assert_eq!(line, "world\n");
+    assert_eq!(reader.fill_buf().unwrap(), b"");
+    assert_eq!(loader.loaded.get(), 12);
+}
+
+#[test]
+fn script_args() {
+    let loader = MockLoader::with_script(script_bytes(b"some args"));
+    let mut reader = SyscallReader::script_args_with_loader(&loader).unwrap();
+    assert_eq!(reader.target(), &Target::Script);
+    assert_eq!(reader.len(), 9);
+
+    let mut out = Vec::new();
+    reader.read_to_end(&mut out).unwrap();
+    assert_eq!(out, b"some args");
+    assert_eq!(reader.seek(SeekFrom::Start(5)).unwrap(), 5);
+    out.clear();
+    reader.read_to_end(&mut out).unwrap();
+    assert_eq!(out, b"args");
+}
+
+#[test]
+fn script_args_malformed() {
+    let mut script = script_bytes(b"args");
+    // Claim more args than the table holds.
+    let len_at = script.len() - 8;
+    script[len_at..len_at + 4].copy_from_slice(&100u32.to_le_bytes());
+    let loader = MockLoader::with_script(script);
+    let err = SyscallReader::script_args_with_loader(&loader).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+    let loader = MockLoader::with_script(vec![0; 3]);
+    let err = SyscallReader::script_args_with_loader(&loader).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+}
+
+#[test]
+fn syscalls_unsupported_on_host() {
+    if cfg!(target_arch = "riscv64") {
+        return;
+    }
+    let err = cell_data(0, Source::GroupInput).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Unsupported);
+}
+
+#[test]
+fn os_error_kinds() {
+    let kind = |code| Error::from_raw_os_error(code).kind();
+    assert_eq!(kind(INDEX_OUT_OF_BOUND), ErrorKind::NotFound);
+    assert_eq!(kind(ITEM_MISSING), ErrorKind::NotFound);
+    assert_eq!(kind(SLICE_OUT_OF_BOUND), ErrorKind::UnexpectedEof);
+    assert_eq!(kind(WRONG_FORMAT), ErrorKind::InvalidData);
+    assert_eq!(kind(WAIT_FAILURE), ErrorKind::InvalidInput);
+    assert_eq!(kind(INVALID_FD), ErrorKind::InvalidInput);
+    assert_eq!(kind(OTHER_END_CLOSED), ErrorKind::BrokenPipe);
+    assert_eq!(kind(MAX_VMS_SPAWNED), ErrorKind::ResourceBusy);
+    assert_eq!(kind(MAX_FDS_CREATED), ErrorKind::ResourceBusy);
+    assert_eq!(kind(0), ErrorKind::Other);
+    assert_eq!(kind(-1), ErrorKind::Other);
+    assert_eq!(kind(100), ErrorKind::Other);
+}
+
+#[test]
+fn os_error_display() {
+    use alloc::format;
+    use alloc::string::{String, ToString};
+
+    // Appends the location that `error_location` adds, if any.
+    fn display(code: i64, expected: &str) -> (String, String) {
+        let err = Error::from_raw_os_error(code);
+        let expected = match err.location() {
+            Some(location) => format!("{expected} (at {location})"),
+            None => expected.to_string(),
+        };
+        (err.to_string(), expected)
+    }
+
+    let (actual, expected) = display(ITEM_MISSING, "item missing (os error 2)");
+    assert_eq!(actual, expected);
+    let (actual, expected) = display(OTHER_END_CLOSED, "other end closed (os error 7)");
+    assert_eq!(actual, expected);
+    let (actual, expected) = display(42, "os error 42");
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn missing_witness_is_not_found() {
+    let loader = MockLoader::new(vec![]);
+    let err = SyscallReader::with_loader(&loader, Target::Witness(0, Source::Input)).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::NotFound);
+}
+
+#[test]
+fn read_at_shares_one_reader() {
+    let loader = MockLoader::new(vec![sample()]);
+    let reader = SyscallReader::with_loader(&loader, Target::Witness(0, Source::Input))
+        .unwrap()
+        .window(16, 32)
+        .unwrap();
+
+    let mut buf = [0; 4];
+    assert_eq!(reader.read_at(&mut buf, 2).unwrap(), 4);
+    assert_eq!(buf, [18, 19, 20, 21]);
+    assert_eq!(reader.read_at(&mut buf, 30).unwrap(), 2);
+    assert_eq!(&buf[..2], [46, 47]);
+    assert_eq!(reader.read_at(&mut buf, 32).unwrap(), 0);
+    assert_eq!(reader.size(), Some(32));
+
+    // Independent cursors over the same reader.
+    let mut a = io::ReadAtCursor::new(&reader);
+    let mut b = io::ReadAtCursor::new(&reader);
+    b.seek(SeekFrom::End(-1)).unwrap();
+    assert_eq!(b.read(&mut buf).unwrap(), 1);
+    assert_eq!(buf[0], 47);
+    a.read_exact(&mut buf).unwrap();
+    assert_eq!(buf, [16, 17, 18, 19]);
+}
@@ -0,0 +1,299 @@
+//! Conversions between this crate's I/O types and those of `std::io`, for
+//! code shared between scripts and off-chain tools.
+#[cfg(test)]
+mod tests;
+
+use crate::io::error::Repr;
+use crate::io::{self, BufRead, Error, ErrorKind, Read, Seek, SeekFrom, Write};
+use std::io as std_io;
+
+/// Returns the `std::io` kind with the same meaning, if there is a stable one.
+fn std_kind(kind: ErrorKind) -> Option<std_io::ErrorKind> {
+    use std_io::ErrorKind as K;
+    Some(match kind {
+        ErrorKind::NotFound => K::NotFound,
+        ErrorKind::PermissionDenied => K::PermissionDenied,
+        ErrorKind::ConnectionRefused => K::ConnectionRefused,
+        ErrorKind::ConnectionReset => K::ConnectionReset,
+        ErrorKind::HostUnreachable => K::HostUnreachable,
+        ErrorKind::NetworkUnreachable => K::NetworkUnreachable,
+        ErrorKind::ConnectionAborted => K::ConnectionAborted,
+        ErrorKind::NotConnected => K::NotConnected,
+        ErrorKind::AddrInUse => K::AddrInUse,
+        ErrorKind::AddrNotAvailable => K::AddrNotAvailable,
+        ErrorKind::NetworkDown => K::NetworkDown,
+        ErrorKind::BrokenPipe => K::BrokenPipe,
+        ErrorKind::AlreadyExists => K::AlreadyExists,
+        ErrorKind::WouldBlock => K::WouldBlock,
+        ErrorKind::NotADirectory => K::NotADirectory,
+        ErrorKind::IsADirectory => K::IsADirectory,
+        ErrorKind::DirectoryNotEmpty => K::DirectoryNotEmpty,
+        ErrorKind::ReadOnlyFilesystem => K::ReadOnlyFilesystem,
+        ErrorKind::StaleNetworkFileHandle => K::StaleNetworkFileHandle,
+        ErrorKind::InvalidInput => K::InvalidInput,
+        ErrorKind::InvalidData => K::InvalidData,
+        ErrorKind::TimedOut => K::TimedOut,
+        ErrorKind::WriteZero => K::WriteZero,
+        ErrorKind::StorageFull => K::StorageFull,
+        ErrorKind::NotSeekable => K::NotSeekable,
+        ErrorKind::FilesystemQuotaExceeded => K::QuotaExceeded,
+        ErrorKind::FileTooLarge => K::FileTooLarge,
+        ErrorKind::ResourceBusy => K::ResourceBusy,
+        ErrorKind::ExecutableFileBusy => K::ExecutableFileBusy,
+        ErrorKind::Deadlock => K::Deadlock,
+        ErrorKind::CrossesDevices => K::CrossesDevices,
+        ErrorKind::TooManyLinks => K::TooManyLinks,
+        ErrorKind::InvalidFilename => K::InvalidFilename,
+        ErrorKind::ArgumentListTooLong => K::ArgumentListTooLong,
+        ErrorKind::Interrupted => K::Interrupted,
+        ErrorKind::Unsupported => K::Unsupported,
+        ErrorKind::UnexpectedEof => K::UnexpectedEof,
+        ErrorKind::OutOfMemory => K::OutOfMemory,
+        ErrorKind::Other => K::Other,
+        // Unstable in `std`.
+        ErrorKind::FilesystemLoop | ErrorKind::Uncategorized => return None,
+    })
+}
+
+impl From<ErrorKind> for std_io::ErrorKind {
+    /// Converts to the `std::io` kind with the same meaning, or `Other` for
+    /// the kinds that are unstable in `std`.
+    fn from(kind: ErrorKind) -> std_io::ErrorKind {
+        std_kind(kind).unwrap_or(std_io::ErrorKind::Other)
+    }
+}
+
+impl From<std_io::ErrorKind> for ErrorKind {
+    /// Converts to the kind with the same meaning, or `Uncategorized` for
+    /// kinds this crate does not know.
+    fn from(kind: std_io::ErrorKind) -> ErrorKind {
+        use std_io::ErrorKind as K;
+        match kind {
+            K::NotFound => ErrorKind::NotFound,
+            K::PermissionDenied => ErrorKind::PermissionDenied,
+            K::ConnectionRefused => ErrorKind::ConnectionRefused,
+            K::ConnectionReset => ErrorKind::ConnectionReset,
+            K::HostUnreachable => ErrorKind::HostUnreachable,
+            K::NetworkUnreachable => ErrorKind::NetworkUnreachable,
+            K::ConnectionAborted => ErrorKind::ConnectionAborted,
+            K::NotConnected => ErrorKind::NotConnected,
+            K::AddrInUse => ErrorKind::AddrInUse,
+            K::AddrNotAvailable => ErrorKind::AddrNotAvailable,
+            K::NetworkDown => ErrorKind::NetworkDown,
+            K::BrokenPipe => ErrorKind::BrokenPipe,
+            K::AlreadyExists => ErrorKind::AlreadyExists,
+            K::WouldBlock => ErrorKind::WouldBlock,
+            K::NotADirectory => ErrorKind::NotADirectory,
+            K::IsADirectory => ErrorKind::IsADirectory,
+            K::DirectoryNotEmpty => ErrorKind::DirectoryNotEmpty,
+            K::ReadOnlyFilesystem => ErrorKind::ReadOnlyFilesystem,
+            K::StaleNetworkFileHandle => ErrorKind::StaleNetworkFileHandle,
+            K::InvalidInput => ErrorKind::InvalidInput,
+            K::InvalidData => ErrorKind::InvalidData,
+            K::TimedOut => ErrorKind::TimedOut,
+            K::WriteZero => ErrorKind::WriteZero,
+            K::StorageFull => ErrorKind::StorageFull,
+            K::NotSeekable => ErrorKind::NotSeekable,
+            K::QuotaExceeded => ErrorKind::FilesystemQuotaExceeded,
+            K::FileTooLarge => ErrorKind::FileTooLarge,
+            K::ResourceBusy => ErrorKind::ResourceBusy,
+            K::ExecutableFileBusy => ErrorKind::ExecutableFileBusy,
+            K::Deadlock => ErrorKind::Deadlock,
+            K::CrossesDevices => ErrorKind::CrossesDevices,
+            K::TooManyLinks => ErrorKind::TooManyLinks,
+            K::InvalidFilename => ErrorKind::InvalidFilename,
+            K::ArgumentListTooLong => ErrorKind::ArgumentListTooLong,
+            K::Interrupted => ErrorKind::Interrupted,
+            K::Unsupported => ErrorKind::Unsupported,
+            K::UnexpectedEof => ErrorKind::UnexpectedEof,
+            K::OutOfMemory => ErrorKind::OutOfMemory,
+            K::Other => ErrorKind::Other,
+            _ => ErrorKind::Uncategorized,
+        }
+    }
+}
+
+impl From<Error> for std_io::Error {
+    /// Converts an error into a `std::io::Error` of the same kind.
+    ///
+    /// An error holding just a kind with a `std` equivalent and no context
+    /// converts into one without a payload, dropping its location if
+    /// `error_location` is enabled. Any other error becomes the payload of the
+    /// `std::io::Error`, and converting that back with `Error::from` returns
+    /// it unchanged. An error that was itself converted from a
+    /// `std::io::Error` is unwrapped.
+    fn from(error: Error) -> std_io::Error {
+        if let (Repr::Simple(kind), true) = (error.repr(), !error.has_context()) {
+            if let Some(kind) = std_kind(*kind) {
+                return kind.into();
+            }
+        }
+        let kind = error.kind().into();
+        match error.downcast::<std_io::Error>() {
+            Ok(error) => error,
+            Err(error) => std_io::Error::new(kind, error),
+        }
+    }
+}
+
+impl From<std_io::Error> for Error {
+    /// Converts a `std::io::Error` into an error of the same kind.
+    ///
+    /// The `std::io::Error` becomes the payload of the result, unless it was
+    /// itself converted from an [`Error`], which is then returned unchanged.
+    #[cfg_attr(feature = "error_location", track_caller)]
+    fn from(error: std_io::Error) -> Error {
+        if error.get_ref().is_some_and(|e| e.is::<Error>()) {
+            let inner = error.into_inner().unwrap();
+            return *inner.downcast::<Error>().unwrap();
+        }
+        Error::new(error.kind().into(), error)
+    }
+}
+
+impl From<SeekFrom> for std_io::SeekFrom {
+    fn from(pos: SeekFrom) -> std_io::SeekFrom {
+        match pos {
+            SeekFrom::Start(n) => std_io::SeekFrom::Start(n),
+            SeekFrom::End(n) => std_io::SeekFrom::End(n),
+            SeekFrom::Current(n) => std_io::SeekFrom::Current(n),
+        }
+    }
+}
+
+impl From<std_io::SeekFrom> for SeekFrom {
+    fn from(pos: std_io::SeekFrom) -> SeekFrom {
+        match pos {
+            std_io::SeekFrom::Start(n) => SeekFrom::Start(n),
+            std_io::SeekFrom::End(n) => SeekFrom::End(n),
+            std_io::SeekFrom::Current(n) => SeekFrom::Current(n),
+        }
+    }
+}
+
+/// Adapts a `std::io` reader, writer or stream to the [`Read`], [`Write`],
+/// [`Seek`] and [`BufRead`] traits of this crate.
+///
+/// Errors are converted with [`Error::from`].
+///
+/// # Examples
+///
+/// ```no_run
+/// use ckb_rust_std::io::{self, FromStd, Read};
+///
+/// // Parsing code shared with the on-chain script.
+/// fn parse(reader: &mut impl Read) -> io::Result<[u8; 4]> {
+///     let mut header = [0; 4];
+///     reader.read_exact(&mut header)?;
+///     Ok(header)
+/// }
+///
+/// let file = std::fs::File::open("tx.bin").unwrap();
+/// let header = parse(&mut FromStd::new(file)).unwrap();
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct FromStd<T> {
+    inner: T,
+}
+
+/// Adapts a reader, writer or stream of this crate to the `std::io` `Read`,
+/// `Write`, `Seek` and `BufRead` traits.
+///
+/// Errors are converted with `std::io::Error::from`.
+#[derive(Debug, Default, Clone)]
+pub struct IntoStd<T> {
+    inner: T,
+}
+
+macro_rules! adapter_methods {
+    ($name:ident) => {
+        impl<T> $name<T> {
+            /// Wraps `inner`.
+            pub const fn new(inner: T) -> $name<T> {
+                $name { inner }
+            }
+
+            /// Gets a reference to the wrapped value.
+            pub fn get_ref(&self) -> &T {
+                &self.inner
+            }
+
+            /// Gets a mutable reference to the wrapped value.
+            pub fn get_mut(&mut self) -> &mut T {
+                &mut self.inner
+            }
+
+            /// Unwraps this adapter, returning the wrapped value.
+            pub fn into_inner(self) -> T {
+                self.inner
+            }
+        }
+    };
+}
+
+adapter_methods!(FromStd);
+adapter_methods!(IntoStd);
+
+impl<R: std_io::Read> Read for FromStd<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        Ok(self.inner.read(buf)?)
+    }
+}
+
+impl<W: std_io::Write> Write for FromStd<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        Ok(self.inner.write(buf)?)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(self.inner.flush()?)
+    }
+}
+
+impl<S: std_io::Seek> Seek for FromStd<S> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        Ok(self.inner.seek(pos.into())?)
+    }
+}
+
+impl<B: std_io::BufRead> BufRead for FromStd<B> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        Ok(self.inner.fill_buf()?)
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.inner.consume(amt)
+    }
+}
+
+impl<R: Read> std_io::Read for IntoStd<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std_io::Result<usize> {
+        Ok(self.inner.read(buf)?)
+    }
+}
+
+impl<W: Write> std_io::Write for IntoStd<W> {
+    fn write(&mut self, buf: &[u8]) -> std_io::Result<usize> {
+        Ok(self.inner.write(buf)?)
+    }
+
+    fn flush(&mut self) -> std_io::Result<()> {
+        Ok(self.inner.flush()?)
+    }
+}
+
+impl<S: Seek> std_io::Seek for IntoStd<S> {
+    fn seek(&mut self, pos: std_io::SeekFrom) -> std_io::Result<u64> {
+        Ok(self.inner.seek(pos.into())?)
+    }
+}
+
+impl<B: BufRead> std_io::BufRead for IntoStd<B> {
+    fn fill_buf(&mut self) -> std_io::Result<&[u8]> {
+        Ok(self.inner.fill_buf()?)
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.inner.consume(amt)
+    }
+}
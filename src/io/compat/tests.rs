@@ -0,0 +1,139 @@
+use super::*;
+#[cfg(feature = "error_context")]
+use crate::io::ErrorExt;
+use crate::io::{const_io_error, Cursor};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use std::io::{BufRead as _, Read as _, Seek as _, Write as _};
+
+#[test]
+fn kinds_round_trip() {
+    for n in 1..=u8::MAX {
+        let Some(kind) = ErrorKind::from_u8(n) else {
+            continue;
+        };
+        let std_kind = std_io::ErrorKind::from(kind);
+        match kind {
+            ErrorKind::FilesystemLoop | ErrorKind::Uncategorized => {
+                assert_eq!(std_kind, std_io::ErrorKind::Other)
+            }
+            _ => assert_eq!(ErrorKind::from(std_kind), kind),
+        }
+    }
+    assert_eq!(
+        ErrorKind::from(std_io::ErrorKind::QuotaExceeded),
+        ErrorKind::FilesystemQuotaExceeded
+    );
+}
+
+#[test]
+fn errors_round_trip() {
+    const E: Error = const_io_error!(ErrorKind::InvalidData, "bad witness");
+
+    let std_error = std_io::Error::from(Error::from(ErrorKind::UnexpectedEof));
+    assert_eq!(std_error.kind(), std_io::ErrorKind::UnexpectedEof);
+    assert!(std_error.get_ref().is_none());
+
+    let std_error = std_io::Error::from(E);
+    assert_eq!(std_error.kind(), std_io::ErrorKind::InvalidData);
+    assert_eq!(std_error.to_string(), E.to_string());
+    let error = Error::from(std_error);
+    assert_eq!(error.message_id(), E.message_id());
+
+    let std_error = std_io::Error::from(Error::from_raw_os_error(2));
+    assert_eq!(std_error.kind(), std_io::ErrorKind::NotFound);
+    // CKB syscall codes are not errno values.
+    assert_eq!(std_error.raw_os_error(), None);
+    assert_eq!(Error::from(std_error).raw_os_error(), Some(2));
+
+    let std_error = std_io::Error::from(Error::from(ErrorKind::FilesystemLoop));
+    assert_eq!(std_error.kind(), std_io::ErrorKind::Other);
+    assert_eq!(Error::from(std_error).kind(), ErrorKind::FilesystemLoop);
+}
+
+#[cfg(feature = "error_context")]
+#[test]
+fn errors_with_context_round_trip() {
+    const E: Error = const_io_error!(ErrorKind::InvalidData, "bad witness");
+    let error = E.context("reading witness");
+    let std_error = std_io::Error::from(error);
+    assert_eq!(std_error.kind(), std_io::ErrorKind::InvalidData);
+    let error = Error::from(std_error);
+    assert_eq!(error.kind(), ErrorKind::InvalidData);
+    assert!(error.context_messages().eq(["reading witness"]));
+
+    // The context is kept even for an error holding just a kind.
+    let error = Error::from(ErrorKind::NotFound).context("loading cell");
+    let error = Error::from(std_io::Error::from(error));
+    assert!(error.context_messages().eq(["loading cell"]));
+}
+
+#[test]
+fn std_errors_round_trip() {
+    let std_error = std_io::Error::from_raw_os_error(2);
+    let error = Error::from(std_error);
+    assert_eq!(error.kind(), ErrorKind::NotFound);
+    let std_error = std_io::Error::from(error);
+    assert_eq!(std_error.raw_os_error(), Some(2));
+
+    let error = Error::from(std_io::Error::other("oh no"));
+    assert_eq!(error.kind(), ErrorKind::Other);
+    assert_eq!(error.get_ref().unwrap().to_string(), "oh no");
+    // The original `std::io::Error` comes back, not one wrapping it.
+    let std_error = std_io::Error::from(error);
+    assert_eq!(std_error.kind(), std_io::ErrorKind::Other);
+    assert!(!std_error.get_ref().unwrap().is::<Error>());
+    assert_eq!(std_error.to_string(), "oh no");
+}
+
+#[test]
+fn seek_from() {
+    for pos in [SeekFrom::Start(3), SeekFrom::End(-2), SeekFrom::Current(1)] {
+        assert_eq!(SeekFrom::from(std_io::SeekFrom::from(pos)), pos);
+    }
+}
+
+#[test]
+fn from_std() {
+    let mut reader = FromStd::new(std_io::Cursor::new(b"one\ntwo\n".to_vec()));
+    let mut line = String::new();
+    reader.read_line(&mut line).unwrap();
+    assert_eq!(line, "one\n");
+    assert_eq!(reader.seek(SeekFrom::Current(1)).unwrap(), 5);
+    let mut rest = Vec::new();
+    reader.read_to_end(&mut rest).unwrap();
+    assert_eq!(rest, b"wo\n");
+
+    let mut writer = FromStd::new(Vec::new());
+    writer.write_all(b"hello").unwrap();
+    writer.flush().unwrap();
+    assert_eq!(writer.into_inner(), b"hello");
+
+    let mut reader = FromStd::new(std_io::repeat(0).take(3));
+    let err = reader.read_exact(&mut [0; 4]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn into_std() {
+    let mut reader = IntoStd::new(Cursor::new(b"one\ntwo\n".to_vec()));
+    let mut line = String::new();
+    reader.read_line(&mut line).unwrap();
+    assert_eq!(line, "one\n");
+    assert_eq!(reader.seek(std_io::SeekFrom::End(-1)).unwrap(), 7);
+    let mut rest = Vec::new();
+    reader.read_to_end(&mut rest).unwrap();
+    assert_eq!(rest, b"\n");
+
+    let mut writer = IntoStd::new(Cursor::new([0u8; 4]));
+    writer.write_all(b"abcd").unwrap();
+    let err = writer.write_all(b"e").unwrap_err();
+    assert_eq!(err.kind(), std_io::ErrorKind::WriteZero);
+    // The original error comes back unchanged.
+    let err = Error::from(err);
+    assert_eq!(err.kind(), ErrorKind::WriteZero);
+
+    let mut copy = Vec::new();
+    std_io::copy(&mut IntoStd::new(&b"copied"[..]), &mut copy).unwrap();
+    assert_eq!(copy, b"copied");
+}
@@ -1,4 +1,10 @@
-use super::{BorrowedBuf, Read, Result, Write, DEFAULT_BUF_SIZE};
+// The tests need `alloc`.
+#[cfg(all(test, feature = "alloc"))]
+mod tests;
+
+use super::{BorrowedBuf, BufRead, GenericBufWriter, Read, Result, Write, COPY_BUF_SIZE};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
 use core::mem::MaybeUninit;
 /// Copies the entire contents of a reader into a writer.
 ///
@@ -49,15 +55,63 @@ use core::mem::MaybeUninit;
 /// Note that platform-specific behavior [may change in the future][changes].
 ///
 /// [changes]: crate::io#platform-specific-behavior
+///
+/// # Faster copies
+///
+/// Without specialization, `copy` cannot tell whether `reader` or `writer`
+/// already has a buffer, so it always goes through a stack buffer of its
+/// own. When the types are known to be buffered, [`copy_buf`] (for a
+/// [`BufRead`] reader) and [`WriteFrom::write_from`] (for a [`Vec<u8>`] or
+/// [`BufWriter`] writer) avoid that intermediate copy.
+///
+/// # Buffer size
+///
+/// The stack buffer is 1 KiB, or the value of the `CKB_RUST_STD_COPY_BUF_SIZE`
+/// environment variable at build time. Use [`copy_with_buffer`] to pick a
+/// size for a single call.
 pub fn copy<R: ?Sized + Read, W: ?Sized + Write>(reader: &mut R, writer: &mut W) -> Result<u64> {
-    stack_buffer_copy(reader, writer)
+    stack_buffer_copy::<COPY_BUF_SIZE, _, _>(reader, writer)
+}
+
+/// Copies the entire contents of a reader into a writer through a stack
+/// buffer of `N` bytes.
+///
+/// This is [`copy`] with an explicit buffer size: a larger `N` means fewer,
+/// larger reads and writes (and so fewer syscalls) at the cost of stack
+/// space. `N` must not be zero.
+///
+/// # Examples
+///
+/// ```no_run
+/// use ckb_rust_std::io::{self, ckb::{self, Source}};
+///
+/// fn main() -> io::Result<()> {
+///     let mut data = ckb::cell_data(0, Source::GroupInput)?;
+///     io::copy_with_buffer::<4096>(&mut data, &mut io::sink())?;
+///     Ok(())
+/// }
+/// ```
+pub fn copy_with_buffer<const N: usize>(
+    reader: &mut (impl Read + ?Sized),
+    writer: &mut (impl Write + ?Sized),
+) -> Result<u64> {
+    stack_buffer_copy::<N, _, _>(reader, writer)
 }
 
-pub fn stack_buffer_copy<R: Read + ?Sized, W: Write + ?Sized>(
+// Fails to compile for `N == 0`, without the inline `const` blocks that
+// need Rust 1.79.
+struct NonEmpty<const N: usize>;
+
+impl<const N: usize> NonEmpty<N> {
+    const OK: () = assert!(N > 0, "the copy buffer must not be empty");
+}
+
+pub fn stack_buffer_copy<const N: usize, R: Read + ?Sized, W: Write + ?Sized>(
     reader: &mut R,
     writer: &mut W,
 ) -> Result<u64> {
-    let buf: &mut [_] = &mut [MaybeUninit::uninit(); DEFAULT_BUF_SIZE];
+    let () = NonEmpty::<N>::OK;
+    let buf: &mut [_] = &mut [MaybeUninit::uninit(); N];
     let mut buf: BorrowedBuf<'_> = buf.into();
     let mut len = 0;
     loop {
@@ -75,3 +129,137 @@ pub fn stack_buffer_copy<R: Read + ?Sized, W: Write + ?Sized>(
     }
     Ok(len)
 }
+
+/// Copies the entire contents of a buffered reader into a writer.
+///
+/// Unlike [`copy`], this writes straight out of the reader's own buffer
+/// (as returned by [`fill_buf`]) instead of copying through an intermediate
+/// one. For in-memory readers such as `&[u8]` and [`Cursor`], the whole
+/// remaining contents are handed to `writer` in a single [`write_all`].
+///
+/// [`fill_buf`]: BufRead::fill_buf
+/// [`write_all`]: Write::write_all
+/// [`Cursor`]: crate::io::Cursor
+///
+/// # Errors
+///
+/// This function will return an error immediately if any call to
+/// [`fill_buf`] or [`write`] returns an error. All instances of
+/// [`ErrorKind::Interrupted`] are handled by this function and the underlying
+/// operation is retried.
+///
+/// [`write`]: Write::write
+/// [`ErrorKind::Interrupted`]: crate::io::ErrorKind::Interrupted
+///
+/// # Examples
+///
+/// ```no_run
+/// use ckb_rust_std::io::{self, ckb::{self, Source}, BufReader};
+///
+/// fn main() -> io::Result<()> {
+///     let data = ckb::cell_data(0, Source::GroupInput)?;
+///     let mut reader = BufReader::with_capacity(4096, data);
+///     io::copy_buf(&mut reader, &mut io::sink())?;
+///     Ok(())
+/// }
+/// ```
+pub fn copy_buf<R: ?Sized + BufRead, W: ?Sized + Write>(
+    reader: &mut R,
+    writer: &mut W,
+) -> Result<u64> {
+    let mut len = 0;
+    loop {
+        let buf = match reader.fill_buf() {
+            Ok(buf) => buf,
+            Err(e) if e.is_interrupted() => continue,
+            Err(e) => return Err(e),
+        };
+        if buf.is_empty() {
+            return Ok(len);
+        }
+        writer.write_all(buf)?;
+        let n = buf.len();
+        len += n as u64;
+        reader.consume(n);
+    }
+}
+
+/// A writer which can read data directly into its own storage.
+///
+/// This is the writer-side counterpart of [`copy_buf`]: instead of copying
+/// through a stack buffer like [`copy`], `write_from` lets the reader fill
+/// the writer's buffer in place.
+pub trait WriteFrom: Write {
+    /// Copies the entire contents of `reader` into this writer, returning the
+    /// number of bytes copied.
+    ///
+    /// Errors are handled as in [`copy`].
+    fn write_from<R: ?Sized + Read>(&mut self, reader: &mut R) -> Result<u64>;
+}
+
+/// Appends directly to the vector's spare capacity, growing it as needed.
+#[cfg(feature = "alloc")]
+impl WriteFrom for Vec<u8> {
+    fn write_from<R: ?Sized + Read>(&mut self, reader: &mut R) -> Result<u64> {
+        reader.read_to_end(self).map(|n| n as u64)
+    }
+}
+
+/// Reads directly into the unused part of the buffer, flushing it whenever
+/// it fills up.
+///
+/// If the buffer is smaller than the stack buffer [`copy`] would use, this
+/// falls back to [`copy`].
+impl<W, S> WriteFrom for GenericBufWriter<W, S>
+where
+    W: ?Sized + Write,
+    S: AsRef<[MaybeUninit<u8>]> + AsMut<[MaybeUninit<u8>]>,
+{
+    fn write_from<R: ?Sized + Read>(&mut self, reader: &mut R) -> Result<u64> {
+        if self.capacity() < COPY_BUF_SIZE {
+            return stack_buffer_copy::<COPY_BUF_SIZE, _, _>(reader, self);
+        }
+
+        let mut len = 0;
+        let mut init = 0;
+
+        loop {
+            let mut read_buf: BorrowedBuf<'_> = self.spare_capacity_mut().into();
+
+            // SAFETY: `init` bytes of the spare capacity were initialized by
+            // the previous read and have not been touched since.
+            unsafe {
+                read_buf.set_init(init);
+            }
+
+            if read_buf.capacity() >= COPY_BUF_SIZE {
+                let mut cursor = read_buf.unfilled();
+                match reader.read_buf(cursor.reborrow()) {
+                    Ok(()) => {
+                        let bytes_read = cursor.written();
+
+                        if bytes_read == 0 {
+                            return Ok(len);
+                        }
+
+                        init = read_buf.init_len() - bytes_read;
+                        len += bytes_read as u64;
+
+                        // SAFETY: `BorrowedBuf` guarantees all of its filled
+                        // bytes are initialized.
+                        unsafe { self.advance(bytes_read) };
+
+                        // Read again if the buffer still has enough capacity,
+                        // as BufWriter itself would do. This will occur if the
+                        // reader returns short reads.
+                    }
+                    Err(ref e) if e.is_interrupted() => {}
+                    Err(e) => return Err(e),
+                }
+            } else {
+                self.flush_buf()?;
+                init = 0;
+            }
+        }
+    }
+}
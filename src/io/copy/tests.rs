@@ -1,8 +1,8 @@
-use core::cmp::{max, min};
+use crate::io::{self, *};
 use alloc::collections::VecDeque;
-use crate as io;
-use io::*;
 use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::{max, min};
 
 struct ShortReader {
     cap: usize,
@@ -35,28 +35,85 @@ impl Write for WriteObserver {
 }
 
 #[test]
-fn copy_specializes_bufwriter() {
+fn copy_uses_stack_buffer() {
+    let cap = 10 * 1024;
+    let mut r = ShortReader {
+        cap,
+        observed_buffer: 0,
+        read_size: usize::MAX,
+    };
+    let mut w = WriteObserver { observed_buffer: 0 };
+    assert_eq!(io::copy(&mut r, &mut w).unwrap(), cap as u64);
+    assert_eq!(r.observed_buffer, COPY_BUF_SIZE);
+    assert_eq!(w.observed_buffer, COPY_BUF_SIZE);
+}
+
+#[test]
+fn copy_with_buffer_uses_requested_size() {
+    let cap = 10 * 1024;
+    let mut r = ShortReader {
+        cap,
+        observed_buffer: 0,
+        read_size: usize::MAX,
+    };
+    let mut w = WriteObserver { observed_buffer: 0 };
+    assert_eq!(
+        io::copy_with_buffer::<4096>(&mut r, &mut w).unwrap(),
+        cap as u64
+    );
+    assert_eq!(r.observed_buffer, 4096);
+    assert_eq!(w.observed_buffer, 4096);
+
+    let mut data: &[u8] = b"tiny buffers still copy everything";
+    let mut out = Vec::new();
+    assert_eq!(io::copy_with_buffer::<3>(&mut data, &mut out).unwrap(), 34);
+    assert_eq!(out, b"tiny buffers still copy everything");
+}
+
+#[test]
+fn write_from_bufwriter() {
     let cap = 117 * 1024;
     let buf_sz = 16 * 1024;
-    let mut r = ShortReader { cap, observed_buffer: 0, read_size: 1337 };
+    let mut r = ShortReader {
+        cap,
+        observed_buffer: 0,
+        read_size: 1337,
+    };
     let mut w = BufWriter::with_capacity(buf_sz, WriteObserver { observed_buffer: 0 });
     assert_eq!(
-        copy(&mut r, &mut w).unwrap(),
+        w.write_from(&mut r).unwrap(),
         cap as u64,
         "expected the whole capacity to be copied"
     );
-    assert_eq!(r.observed_buffer, buf_sz, "expected a large buffer to be provided to the reader");
-    assert!(w.get_mut().observed_buffer > DEFAULT_BUF_SIZE, "expected coalesced writes");
+    assert_eq!(
+        r.observed_buffer, buf_sz,
+        "expected a large buffer to be provided to the reader"
+    );
+    assert!(
+        w.get_mut().observed_buffer > DEFAULT_BUF_SIZE,
+        "expected coalesced writes"
+    );
 }
 
 #[test]
-fn copy_specializes_bufreader() {
+fn write_from_small_bufwriter() {
+    let mut r: &[u8] = &[7; 3000];
+    let mut w = BufWriter::with_capacity(16, Vec::new());
+    assert_eq!(w.write_from(&mut r).unwrap(), 3000);
+    assert_eq!(w.into_inner().unwrap(), [7; 3000]);
+}
+
+#[test]
+fn copy_buf_from_bufreader() {
     let mut source = vec![0; 768 * 1024];
     source[1] = 42;
     let mut buffered = BufReader::with_capacity(256 * 1024, Cursor::new(&mut source));
 
     let mut sink = Vec::new();
-    assert_eq!(io::copy(&mut buffered, &mut sink).unwrap(), source.len() as u64);
+    assert_eq!(
+        io::copy_buf(&mut buffered, &mut sink).unwrap(),
+        source.len() as u64
+    );
     assert_eq!(source.as_slice(), sink.as_slice());
 
     let buf_sz = 71 * 1024;
@@ -64,7 +121,10 @@ fn copy_specializes_bufreader() {
 
     let mut buffered = BufReader::with_capacity(buf_sz, Cursor::new(&mut source));
     let mut sink = WriteObserver { observed_buffer: 0 };
-    assert_eq!(io::copy(&mut buffered, &mut sink).unwrap(), source.len() as u64);
+    assert_eq!(
+        io::copy_buf(&mut buffered, &mut sink).unwrap(),
+        source.len() as u64
+    );
     assert_eq!(
         sink.observed_buffer, buf_sz,
         "expected a large buffer to be provided to the writer"
@@ -72,11 +132,15 @@ fn copy_specializes_bufreader() {
 }
 
 #[test]
-fn copy_specializes_to_vec() {
+fn write_from_to_vec() {
     let cap = DEFAULT_BUF_SIZE * 10;
-    let mut source = ShortReader { cap, observed_buffer: 0, read_size: DEFAULT_BUF_SIZE };
+    let mut source = ShortReader {
+        cap,
+        observed_buffer: 0,
+        read_size: DEFAULT_BUF_SIZE,
+    };
     let mut sink = Vec::new();
-    let copied = io::copy(&mut source, &mut sink).unwrap();
+    let copied = sink.write_from(&mut source).unwrap();
     assert_eq!(cap as u64, copied);
     assert_eq!(sink.len() as u64, copied);
     assert!(
@@ -87,7 +151,7 @@ fn copy_specializes_to_vec() {
 }
 
 #[test]
-fn copy_specializes_from_vecdeque() {
+fn copy_buf_from_vecdeque() {
     let mut source = VecDeque::with_capacity(100 * 1024);
     for _ in 0..20 * 1024 {
         source.push_front(0);
@@ -96,14 +160,25 @@ fn copy_specializes_from_vecdeque() {
         source.push_back(0);
     }
     let mut sink = WriteObserver { observed_buffer: 0 };
-    assert_eq!(40 * 1024u64, io::copy(&mut source, &mut sink).unwrap());
+    assert_eq!(40 * 1024u64, io::copy_buf(&mut source, &mut sink).unwrap());
     assert_eq!(20 * 1024, sink.observed_buffer);
 }
 
 #[test]
-fn copy_specializes_from_slice() {
+fn copy_buf_from_slice() {
     let mut source = [1; 60 * 1024].as_slice();
     let mut sink = WriteObserver { observed_buffer: 0 };
-    assert_eq!(60 * 1024u64, io::copy(&mut source, &mut sink).unwrap());
+    assert_eq!(60 * 1024u64, io::copy_buf(&mut source, &mut sink).unwrap());
     assert_eq!(60 * 1024, sink.observed_buffer);
+    assert!(source.is_empty());
+}
+
+#[test]
+fn copy_buf_from_cursor() {
+    let mut source = Cursor::new([1u8; 5000]);
+    source.set_position(1000);
+    let mut sink = WriteObserver { observed_buffer: 0 };
+    assert_eq!(io::copy_buf(&mut source, &mut sink).unwrap(), 4000);
+    assert_eq!(sink.observed_buffer, 4000);
+    assert_eq!(source.position(), 5000);
 }
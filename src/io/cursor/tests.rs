@@ -1,8 +1,16 @@
+// Kept as ported from `std`.
+#![allow(clippy::unnecessary_cast, clippy::useless_vec)]
+
 use crate::io::prelude::*;
-use crate::io::{Cursor, SeekFrom};
+#[cfg(feature = "alloc")]
+use crate::io::IoSliceMut;
+use crate::io::{Cursor, IoSlice, SeekFrom};
+#[cfg(feature = "alloc")]
 use alloc::vec;
+#[cfg(feature = "alloc")]
 use alloc::vec::Vec;
 
+#[cfg(feature = "alloc")]
 #[test]
 fn test_vec_writer() {
     let mut writer = Vec::new();
@@ -13,6 +21,22 @@ fn test_vec_writer() {
     assert_eq!(writer, b);
 }
 
+#[cfg(feature = "alloc")]
+#[test]
+fn test_vec_writer_vectored() {
+    let mut writer = Vec::new();
+    assert_eq!(writer.write_vectored(&[IoSlice::new(&[0])]).unwrap(), 1);
+    assert_eq!(
+        writer
+            .write_vectored(&[IoSlice::new(&[1, 2, 3]), IoSlice::new(&[4, 5, 6, 7])])
+            .unwrap(),
+        7,
+    );
+    let b: &[_] = &[0, 1, 2, 3, 4, 5, 6, 7];
+    assert_eq!(writer, b);
+}
+
+#[cfg(feature = "alloc")]
 #[test]
 fn test_mem_writer() {
     let mut writer = Cursor::new(Vec::new());
@@ -24,6 +48,25 @@ fn test_mem_writer() {
     assert_eq!(&writer.get_ref()[..7], &[0; 7]);
 }
 
+#[cfg(feature = "alloc")]
+#[test]
+fn test_mem_writer_vectored() {
+    let mut writer = Cursor::new(Vec::new());
+    assert_eq!(writer.position(), 0);
+    assert_eq!(writer.write_vectored(&[IoSlice::new(&[0])]).unwrap(), 1);
+    assert_eq!(writer.position(), 1);
+    assert_eq!(
+        writer
+            .write_vectored(&[IoSlice::new(&[1, 2, 3]), IoSlice::new(&[4, 5, 6, 7])])
+            .unwrap(),
+        7,
+    );
+    assert_eq!(writer.position(), 8);
+    let b: &[_] = &[0, 1, 2, 3, 4, 5, 6, 7];
+    assert_eq!(&writer.get_ref()[..], b);
+}
+
+#[cfg(feature = "alloc")]
 #[test]
 fn test_mem_writer_preallocated() {
     let mut writer = Cursor::new(vec![0, 0, 0, 0, 0, 0, 0, 0, 8, 9, 10]);
@@ -34,6 +77,7 @@ fn test_mem_writer_preallocated() {
     assert_eq!(&writer.get_ref()[..], b);
 }
 
+#[cfg(feature = "alloc")]
 #[test]
 fn test_mem_mut_writer() {
     let mut vec = Vec::new();
@@ -65,6 +109,29 @@ where
     assert_eq!(writer.get_ref().as_ref(), b);
 }
 
+#[test]
+fn test_slice_writer_vectored() {
+    let mut buf = [0u8; 9];
+    let mut writer = Cursor::new(&mut buf[..]);
+    assert_eq!(writer.write_vectored(&[IoSlice::new(&[0])]).unwrap(), 1);
+    assert_eq!(
+        writer
+            .write_vectored(&[IoSlice::new(&[1, 2, 3]), IoSlice::new(&[4, 5, 6, 7])])
+            .unwrap(),
+        7,
+    );
+    assert_eq!(writer.position(), 8);
+    assert_eq!(
+        writer
+            .write_vectored(&[IoSlice::new(&[8, 9]), IoSlice::new(&[10])])
+            .unwrap(),
+        1
+    );
+    assert_eq!(writer.write_vectored(&[IoSlice::new(&[10])]).unwrap(), 0);
+    assert_eq!(buf, [0, 1, 2, 3, 4, 5, 6, 7, 8]);
+}
+
+#[cfg(feature = "alloc")]
 #[test]
 fn test_box_slice_writer() {
     let mut writer = Cursor::new(vec![0u8; 9].into_boxed_slice());
@@ -121,6 +188,7 @@ fn test_buf_writer_error() {
     assert_eq!(writer.write(&[0, 0]).unwrap(), 0);
 }
 
+#[cfg(feature = "alloc")]
 #[test]
 fn test_mem_reader() {
     let mut reader = Cursor::new(vec![0, 1, 2, 3, 4, 5, 6, 7]);
@@ -143,6 +211,44 @@ fn test_mem_reader() {
     assert_eq!(reader.read(&mut buf).unwrap(), 0);
 }
 
+#[cfg(feature = "alloc")]
+#[test]
+fn test_mem_reader_vectored() {
+    let mut reader = Cursor::new(vec![0, 1, 2, 3, 4, 5, 6, 7]);
+    let mut buf = [];
+    assert_eq!(
+        reader
+            .read_vectored(&mut [IoSliceMut::new(&mut buf)])
+            .unwrap(),
+        0
+    );
+    assert_eq!(reader.position(), 0);
+    let mut buf = [0];
+    assert_eq!(
+        reader
+            .read_vectored(&mut [IoSliceMut::new(&mut []), IoSliceMut::new(&mut buf)])
+            .unwrap(),
+        1,
+    );
+    assert_eq!(reader.position(), 1);
+    let b: &[_] = &[0];
+    assert_eq!(buf, b);
+    let mut buf1 = [0; 4];
+    let mut buf2 = [0; 4];
+    assert_eq!(
+        reader
+            .read_vectored(&mut [IoSliceMut::new(&mut buf1), IoSliceMut::new(&mut buf2)])
+            .unwrap(),
+        7,
+    );
+    let b1: &[_] = &[1, 2, 3, 4];
+    let b2: &[_] = &[5, 6, 7];
+    assert_eq!(buf1, b1);
+    assert_eq!(&buf2[..3], b2);
+    assert_eq!(reader.read(&mut buf).unwrap(), 0);
+}
+
+#[cfg(feature = "alloc")]
 #[test]
 fn test_boxed_slice_reader() {
     let mut reader = Cursor::new(vec![0, 1, 2, 3, 4, 5, 6, 7].into_boxed_slice());
@@ -165,6 +271,7 @@ fn test_boxed_slice_reader() {
     assert_eq!(reader.read(&mut buf).unwrap(), 0);
 }
 
+#[cfg(feature = "alloc")]
 #[test]
 fn read_to_end() {
     let mut reader = Cursor::new(vec![0, 1, 2, 3, 4, 5, 6, 7]);
@@ -173,6 +280,7 @@ fn read_to_end() {
     assert_eq!(v, [0, 1, 2, 3, 4, 5, 6, 7]);
 }
 
+#[cfg(feature = "alloc")]
 #[test]
 fn test_slice_reader() {
     let in_buf = vec![0, 1, 2, 3, 4, 5, 6, 7];
@@ -195,6 +303,7 @@ fn test_slice_reader() {
     assert_eq!(reader.read(&mut buf).unwrap(), 0);
 }
 
+#[cfg(feature = "alloc")]
 #[test]
 fn test_read_exact() {
     let in_buf = vec![0, 1, 2, 3, 4, 5, 6, 7];
@@ -213,6 +322,7 @@ fn test_read_exact() {
     assert!(reader.read_exact(&mut buf).is_err());
 }
 
+#[cfg(feature = "alloc")]
 #[test]
 fn test_buf_reader() {
     let in_buf = vec![0, 1, 2, 3, 4, 5, 6, 7];
@@ -243,18 +353,24 @@ fn seek_past_end() {
     assert_eq!(r.seek(SeekFrom::Start(10)).unwrap(), 10);
     assert_eq!(r.read(&mut [0]).unwrap(), 0);
 
-    let mut r = Cursor::new(vec![10]);
-    assert_eq!(r.seek(SeekFrom::Start(10)).unwrap(), 10);
-    assert_eq!(r.read(&mut [0]).unwrap(), 0);
+    #[cfg(feature = "alloc")]
+    {
+        let mut r = Cursor::new(vec![10]);
+        assert_eq!(r.seek(SeekFrom::Start(10)).unwrap(), 10);
+        assert_eq!(r.read(&mut [0]).unwrap(), 0);
+    }
 
     let mut buf = [0];
     let mut r = Cursor::new(&mut buf[..]);
     assert_eq!(r.seek(SeekFrom::Start(10)).unwrap(), 10);
     assert_eq!(r.write(&[3]).unwrap(), 0);
 
-    let mut r = Cursor::new(vec![10].into_boxed_slice());
-    assert_eq!(r.seek(SeekFrom::Start(10)).unwrap(), 10);
-    assert_eq!(r.write(&[3]).unwrap(), 0);
+    #[cfg(feature = "alloc")]
+    {
+        let mut r = Cursor::new(vec![10].into_boxed_slice());
+        assert_eq!(r.seek(SeekFrom::Start(10)).unwrap(), 10);
+        assert_eq!(r.write(&[3]).unwrap(), 0);
+    }
 }
 
 #[test]
@@ -271,16 +387,19 @@ fn seek_past_i64() {
     assert!(r.seek(SeekFrom::Current(0x7ffffffffffffffd)).is_err());
     assert_eq!(r.seek(SeekFrom::Current(-0x8000000000000000)).unwrap(), 6);
 
-    let mut r = Cursor::new(vec![10]);
-    assert_eq!(r.seek(SeekFrom::Start(6)).unwrap(), 6);
-    assert_eq!(
-        r.seek(SeekFrom::Current(0x7ffffffffffffff0)).unwrap(),
-        0x7ffffffffffffff6
-    );
-    assert_eq!(r.seek(SeekFrom::Current(0x10)).unwrap(), 0x8000000000000006);
-    assert_eq!(r.seek(SeekFrom::Current(0)).unwrap(), 0x8000000000000006);
-    assert!(r.seek(SeekFrom::Current(0x7ffffffffffffffd)).is_err());
-    assert_eq!(r.seek(SeekFrom::Current(-0x8000000000000000)).unwrap(), 6);
+    #[cfg(feature = "alloc")]
+    {
+        let mut r = Cursor::new(vec![10]);
+        assert_eq!(r.seek(SeekFrom::Start(6)).unwrap(), 6);
+        assert_eq!(
+            r.seek(SeekFrom::Current(0x7ffffffffffffff0)).unwrap(),
+            0x7ffffffffffffff6
+        );
+        assert_eq!(r.seek(SeekFrom::Current(0x10)).unwrap(), 0x8000000000000006);
+        assert_eq!(r.seek(SeekFrom::Current(0)).unwrap(), 0x8000000000000006);
+        assert!(r.seek(SeekFrom::Current(0x7ffffffffffffffd)).is_err());
+        assert_eq!(r.seek(SeekFrom::Current(-0x8000000000000000)).unwrap(), 6);
+    }
 
     let mut buf = [0];
     let mut r = Cursor::new(&mut buf[..]);
@@ -294,16 +413,19 @@ fn seek_past_i64() {
     assert!(r.seek(SeekFrom::Current(0x7ffffffffffffffd)).is_err());
     assert_eq!(r.seek(SeekFrom::Current(-0x8000000000000000)).unwrap(), 6);
 
-    let mut r = Cursor::new(vec![10].into_boxed_slice());
-    assert_eq!(r.seek(SeekFrom::Start(6)).unwrap(), 6);
-    assert_eq!(
-        r.seek(SeekFrom::Current(0x7ffffffffffffff0)).unwrap(),
-        0x7ffffffffffffff6
-    );
-    assert_eq!(r.seek(SeekFrom::Current(0x10)).unwrap(), 0x8000000000000006);
-    assert_eq!(r.seek(SeekFrom::Current(0)).unwrap(), 0x8000000000000006);
-    assert!(r.seek(SeekFrom::Current(0x7ffffffffffffffd)).is_err());
-    assert_eq!(r.seek(SeekFrom::Current(-0x8000000000000000)).unwrap(), 6);
+    #[cfg(feature = "alloc")]
+    {
+        let mut r = Cursor::new(vec![10].into_boxed_slice());
+        assert_eq!(r.seek(SeekFrom::Start(6)).unwrap(), 6);
+        assert_eq!(
+            r.seek(SeekFrom::Current(0x7ffffffffffffff0)).unwrap(),
+            0x7ffffffffffffff6
+        );
+        assert_eq!(r.seek(SeekFrom::Current(0x10)).unwrap(), 0x8000000000000006);
+        assert_eq!(r.seek(SeekFrom::Current(0)).unwrap(), 0x8000000000000006);
+        assert!(r.seek(SeekFrom::Current(0x7ffffffffffffffd)).is_err());
+        assert_eq!(r.seek(SeekFrom::Current(-0x8000000000000000)).unwrap(), 6);
+    }
 }
 
 #[test]
@@ -312,17 +434,24 @@ fn seek_before_0() {
     let mut r = Cursor::new(&buf[..]);
     assert!(r.seek(SeekFrom::End(-2)).is_err());
 
-    let mut r = Cursor::new(vec![10]);
-    assert!(r.seek(SeekFrom::End(-2)).is_err());
+    #[cfg(feature = "alloc")]
+    {
+        let mut r = Cursor::new(vec![10]);
+        assert!(r.seek(SeekFrom::End(-2)).is_err());
+    }
 
     let mut buf = [0];
     let mut r = Cursor::new(&mut buf[..]);
     assert!(r.seek(SeekFrom::End(-2)).is_err());
 
-    let mut r = Cursor::new(vec![10].into_boxed_slice());
-    assert!(r.seek(SeekFrom::End(-2)).is_err());
+    #[cfg(feature = "alloc")]
+    {
+        let mut r = Cursor::new(vec![10].into_boxed_slice());
+        assert!(r.seek(SeekFrom::End(-2)).is_err());
+    }
 }
 
+#[cfg(feature = "alloc")]
 #[test]
 fn test_seekable_mem_writer() {
     let mut writer = Cursor::new(Vec::<u8>::new());
@@ -357,6 +486,7 @@ fn test_seekable_mem_writer() {
     assert_eq!(&writer.get_ref()[..], b);
 }
 
+#[cfg(feature = "alloc")]
 #[test]
 fn vec_seek_past_end() {
     let mut r = Cursor::new(Vec::new());
@@ -364,6 +494,7 @@ fn vec_seek_past_end() {
     assert_eq!(r.write(&[3]).unwrap(), 1);
 }
 
+#[cfg(feature = "alloc")]
 #[test]
 fn vec_seek_before_0() {
     let mut r = Cursor::new(Vec::new());
@@ -378,11 +509,13 @@ fn vec_seek_and_write_past_usize_max() {
     assert!(c.write_all(&[1, 2, 3]).is_err());
 }
 
+#[cfg(feature = "alloc")]
 #[test]
 fn test_partial_eq() {
     assert_eq!(Cursor::new(Vec::<u8>::new()), Cursor::new(Vec::<u8>::new()));
 }
 
+#[cfg(feature = "alloc")]
 #[test]
 fn test_eq() {
     struct AssertEq<T: Eq>(pub T);
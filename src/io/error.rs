@@ -1,4 +1,19 @@
-use alloc::{boxed::Box, fmt};
+#[cfg(test)]
+mod tests;
+
+#[cfg(feature = "error_context")]
+mod context;
+mod wire;
+
+#[cfg(feature = "error_context")]
+use self::context::Context;
+#[cfg(feature = "error_context")]
+pub use self::context::{ErrorExt, ResultExt, MAX_CONTEXT_DEPTH};
+pub use self::wire::WireError;
+#[cfg(feature = "alloc")]
+use alloc::boxed::Box;
+use core::fmt;
+use core::panic::Location;
 use core::result;
 
 pub mod core_error {
@@ -54,32 +69,317 @@ pub type Result<T> = result::Result<T, Error>;
 /// [`Read`]: crate::io::Read
 /// [`Write`]: crate::io::Write
 /// [`Seek`]: crate::io::Seek
-
+///
+/// With the `error_location` or `error_context` feature, `Error` is a struct
+/// instead, so its variants cannot be matched on.
+#[cfg(not(any(feature = "error_location", feature = "error_context")))]
 pub enum Error {
     Os(i64),
     Simple(ErrorKind),
     SimpleMessage(&'static SimpleMessage),
+    #[cfg(feature = "alloc")]
+    Custom(Box<Custom>),
+}
+
+#[cfg(not(any(feature = "error_location", feature = "error_context")))]
+pub(crate) type Repr = Error;
+
+/// The error type for I/O operations of the [`Read`], [`Write`], [`Seek`], and
+/// associated traits.
+///
+/// Errors mostly originate from the underlying OS, but custom instances of
+/// `Error` can be created with crafted error messages and a particular value of
+/// [`ErrorKind`].
+///
+/// [`Read`]: crate::io::Read
+/// [`Write`]: crate::io::Write
+/// [`Seek`]: crate::io::Seek
+///
+/// With the `error_location` feature, an `Error` also carries the
+/// [`Location`] it was created at. See [`Error::location`].
+///
+/// With the `error_context` feature, an `Error` also holds up to
+/// `MAX_CONTEXT_DEPTH` static context messages, added with
+/// `ErrorExt::context` or `ResultExt::context` without allocating.
+#[cfg(any(feature = "error_location", feature = "error_context"))]
+pub struct Error {
+    pub(crate) inner: Inner,
+    #[cfg(feature = "error_context")]
+    pub(crate) context: Context,
+}
+
+/// An [`Error`] without its context, which is what [`Error::source`]
+/// returns for an error with context.
+///
+/// [`Error::source`]: core_error::Error::source
+#[cfg(any(feature = "error_location", feature = "error_context"))]
+pub(crate) struct Inner {
+    pub(crate) repr: Repr,
+    #[cfg(feature = "error_location")]
+    location: &'static Location<'static>,
+}
+
+#[cfg(any(feature = "error_location", feature = "error_context"))]
+pub(crate) enum Repr {
+    Os(i64),
+    Simple(ErrorKind),
+    SimpleMessage(&'static SimpleMessage),
+    #[cfg(feature = "alloc")]
     Custom(Box<Custom>),
 }
 
+#[cfg(not(any(feature = "error_location", feature = "error_context")))]
+impl Error {
+    #[inline]
+    const fn from_repr(repr: Repr) -> Self {
+        repr
+    }
+
+    #[inline]
+    pub(crate) fn repr(&self) -> &Repr {
+        self
+    }
+
+    #[inline]
+    fn repr_mut(&mut self) -> &mut Repr {
+        self
+    }
+
+    #[cfg(feature = "alloc")]
+    #[inline]
+    fn into_repr(self) -> Repr {
+        self
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    pub(crate) fn has_context(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(any(feature = "error_location", feature = "error_context"))]
 impl Error {
+    // `Location::caller` is only `const` since Rust 1.79.
+    #[cfg_attr(feature = "error_location", track_caller, clippy::msrv = "1.79")]
+    const fn from_repr(repr: Repr) -> Self {
+        Self {
+            inner: Inner {
+                repr,
+                #[cfg(feature = "error_location")]
+                location: Location::caller(),
+            },
+            #[cfg(feature = "error_context")]
+            context: Context::EMPTY,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn repr(&self) -> &Repr {
+        &self.inner.repr
+    }
+
+    #[inline]
+    fn repr_mut(&mut self) -> &mut Repr {
+        &mut self.inner.repr
+    }
+
+    #[cfg(feature = "alloc")]
+    #[inline]
+    fn into_repr(self) -> Repr {
+        self.inner.repr
+    }
+
+    /// Returns `true` if context messages were added to the error.
+    #[inline]
+    pub(crate) fn has_context(&self) -> bool {
+        #[cfg(feature = "error_context")]
+        return !self.context.is_empty();
+        #[cfg(not(feature = "error_context"))]
+        false
+    }
+}
+
+impl Error {
+    #[cfg_attr(feature = "error_location", track_caller)]
     pub fn new_simple(kind: ErrorKind) -> Self {
-        Self::Simple(kind)
+        Self::from_repr(Repr::Simple(kind))
     }
+    #[cfg(feature = "alloc")]
+    #[cfg_attr(feature = "error_location", track_caller)]
     pub fn new_custom(custom: Box<Custom>) -> Self {
-        Self::Custom(custom)
+        Self::from_repr(Repr::Custom(custom))
     }
+    #[cfg_attr(feature = "error_location", track_caller)]
     pub const fn new_simple_message(msg: &'static SimpleMessage) -> Self {
-        Self::SimpleMessage(msg)
+        Self::from_repr(Repr::SimpleMessage(msg))
     }
+    #[cfg_attr(feature = "error_location", track_caller)]
     pub fn new_os(code: i64) -> Self {
-        Self::Os(code)
+        Self::from_repr(Repr::Os(code))
+    }
+
+    /// Returns the error with the location of the caller, for the errors
+    /// that `read_exact` and `write_all` return from a `const`.
+    #[cfg_attr(feature = "error_location", track_caller)]
+    #[inline]
+    pub(crate) fn at_caller(self) -> Self {
+        #[cfg(feature = "error_location")]
+        {
+            let mut error = self;
+            error.inner.location = Location::caller();
+            error
+        }
+        #[cfg(not(feature = "error_location"))]
+        self
     }
 }
 
+#[cfg(not(any(feature = "error_location", feature = "error_context")))]
 impl fmt::Debug for Error {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        fmt::Debug::fmt(&self, f)
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_debug(fmt, None)
+    }
+}
+
+#[cfg(all(feature = "error_location", not(feature = "error_context")))]
+impl fmt::Debug for Error {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.fmt(fmt)
+    }
+}
+
+#[cfg(feature = "error_context")]
+impl fmt::Debug for Error {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.context.is_empty() {
+            return self.inner.fmt(fmt);
+        }
+        let mut d = fmt.debug_struct("Context");
+        d.field("messages", &self.context);
+        if self.context.is_truncated() {
+            d.field("truncated", &true);
+        }
+        d.field("error", &self.inner).finish()
+    }
+}
+
+#[cfg(any(feature = "error_location", feature = "error_context"))]
+impl Inner {
+    fn location(&self) -> Option<&'static Location<'static>> {
+        #[cfg(feature = "error_location")]
+        return Some(self.location);
+        #[cfg(not(feature = "error_location"))]
+        None
+    }
+}
+
+#[cfg(any(feature = "error_location", feature = "error_context"))]
+impl fmt::Debug for Inner {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.repr.fmt_debug(fmt, self.location())
+    }
+}
+
+impl Repr {
+    fn fmt_debug(
+        &self,
+        fmt: &mut fmt::Formatter<'_>,
+        location: Option<&'static Location<'static>>,
+    ) -> fmt::Result {
+        match self {
+            Repr::Os(code) => {
+                let mut d = fmt.debug_struct("Os");
+                d.field("code", code)
+                    .field("kind", &crate::io::ckb::decode_error_kind(*code))
+                    .field(
+                        "message",
+                        &crate::io::ckb::error_string(*code).unwrap_or("unknown error"),
+                    );
+                if let Some(location) = location {
+                    d.field("location", &format_args!("{location}"));
+                }
+                d.finish()
+            }
+            #[cfg(feature = "alloc")]
+            Repr::Custom(c) => {
+                let mut d = fmt.debug_struct("Custom");
+                d.field("kind", &c.kind).field("error", &c.error);
+                if let Some(location) = location {
+                    d.field("location", &format_args!("{location}"));
+                }
+                d.finish()
+            }
+            Repr::Simple(kind) => {
+                let mut d = fmt.debug_tuple("Kind");
+                d.field(kind);
+                if let Some(location) = location {
+                    d.field(&format_args!("{location}"));
+                }
+                d.finish()
+            }
+            Repr::SimpleMessage(msg) => {
+                let mut d = fmt.debug_struct("Error");
+                d.field("kind", &msg.kind).field("message", &msg.message);
+                if let Some(location) = location {
+                    d.field("location", &format_args!("{location}"));
+                }
+                d.finish()
+            }
+        }
+    }
+
+    fn fmt_display(
+        &self,
+        fmt: &mut fmt::Formatter<'_>,
+        location: Option<&'static Location<'static>>,
+    ) -> fmt::Result {
+        match self {
+            Repr::Os(code) => match crate::io::ckb::error_string(*code) {
+                Some(detail) => write!(fmt, "{detail} (os error {code})"),
+                None => write!(fmt, "os error {code}"),
+            },
+            #[cfg(feature = "alloc")]
+            Repr::Custom(c) => fmt::Display::fmt(&c.error, fmt),
+            Repr::Simple(kind) => write!(fmt, "{}", kind.as_str()),
+            Repr::SimpleMessage(msg) => fmt::Display::fmt(msg.message, fmt),
+        }?;
+        if let Some(location) = location {
+            write!(fmt, " (at {location})")?;
+        }
+        Ok(())
+    }
+
+    #[allow(deprecated, deprecated_in_future)]
+    fn error_description(&self) -> &str {
+        match self {
+            Repr::Os(code) => crate::io::ckb::decode_error_kind(*code).as_str(),
+            Repr::Simple(kind) => kind.as_str(),
+            Repr::SimpleMessage(msg) => msg.message,
+            #[cfg(feature = "alloc")]
+            Repr::Custom(c) => c.error.description(),
+        }
+    }
+
+    #[allow(deprecated)]
+    fn error_cause(&self) -> Option<&dyn core_error::Error> {
+        match self {
+            Repr::Os(..) => None,
+            Repr::Simple(..) => None,
+            Repr::SimpleMessage(..) => None,
+            #[cfg(feature = "alloc")]
+            Repr::Custom(c) => c.error.cause(),
+        }
+    }
+
+    fn error_source(&self) -> Option<&(dyn core_error::Error + 'static)> {
+        match self {
+            Repr::Os(..) => None,
+            Repr::Simple(..) => None,
+            Repr::SimpleMessage(..) => None,
+            #[cfg(feature = "alloc")]
+            Repr::Custom(c) => c.error.source(),
+        }
     }
 }
 
@@ -104,6 +404,9 @@ impl Error {
     pub(crate) const READ_EXACT_EOF: Self =
         const_io_error!(ErrorKind::UnexpectedEof, "failed to fill whole buffer");
 
+    pub(crate) const UNKNOWN_ERROR_KIND: Self =
+        const_io_error!(ErrorKind::InvalidData, "unknown error kind");
+
     pub(crate) const UNKNOWN_THREAD_COUNT: Self = const_io_error!(
         ErrorKind::NotFound,
         "The number of hardware threads is not known for the target platform"
@@ -121,6 +424,7 @@ impl Error {
         const_io_error!(ErrorKind::InvalidInput, "cannot set a 0 duration timeout");
 }
 
+#[cfg(feature = "alloc")]
 impl From<alloc::ffi::NulError> for Error {
     /// Converts a [`alloc::ffi::NulError`] into a [`Error`].
     fn from(_: alloc::ffi::NulError) -> Error {
@@ -128,6 +432,7 @@ impl From<alloc::ffi::NulError> for Error {
     }
 }
 
+#[cfg(feature = "alloc")]
 impl From<alloc::collections::TryReserveError> for Error {
     /// Converts `TryReserveError` to an error with [`ErrorKind::OutOfMemory`].
     ///
@@ -161,7 +466,8 @@ pub struct SimpleMessage {
 }
 
 impl SimpleMessage {
-    pub(crate) const fn new(kind: ErrorKind, message: &'static str) -> Self {
+    #[doc(hidden)]
+    pub const fn new(kind: ErrorKind, message: &'static str) -> Self {
         Self { kind, message }
     }
 }
@@ -169,12 +475,22 @@ impl SimpleMessage {
 // As with `SimpleMessage`: `#[repr(align(4))]` here is just because
 // repr_bitpacked's encoding requires it. In practice it almost certainly be
 // already be this high or higher.
-#[derive(Debug)]
+#[cfg(feature = "alloc")]
 #[repr(align(4))]
 pub struct Custom {
     kind: ErrorKind,
     error: Box<dyn core_error::Error + Send + Sync>,
 }
+
+#[cfg(feature = "alloc")]
+impl fmt::Debug for Custom {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Custom")
+            .field("kind", &self.kind)
+            .field("error", &self.error)
+            .finish()
+    }
+}
 /// A list specifying general categories of I/O error.
 ///
 /// This list is intended to grow over time and it is not recommended to
@@ -377,6 +693,113 @@ pub enum ErrorKind {
 }
 
 impl ErrorKind {
+    /// Returns the stable number of this kind, for sending it to another
+    /// script.
+    ///
+    /// Numbers follow the order in which the variants are declared, from `1`
+    /// for [`NotFound`] to `41` for [`Uncategorized`]. They never change
+    /// across releases, and kinds added in the future get numbers above
+    /// `41`. `0` is never used.
+    ///
+    /// [`NotFound`]: ErrorKind::NotFound
+    /// [`Uncategorized`]: ErrorKind::Uncategorized
+    pub const fn to_u8(self) -> u8 {
+        use ErrorKind::*;
+        match self {
+            NotFound => 1,
+            PermissionDenied => 2,
+            ConnectionRefused => 3,
+            ConnectionReset => 4,
+            HostUnreachable => 5,
+            NetworkUnreachable => 6,
+            ConnectionAborted => 7,
+            NotConnected => 8,
+            AddrInUse => 9,
+            AddrNotAvailable => 10,
+            NetworkDown => 11,
+            BrokenPipe => 12,
+            AlreadyExists => 13,
+            WouldBlock => 14,
+            NotADirectory => 15,
+            IsADirectory => 16,
+            DirectoryNotEmpty => 17,
+            ReadOnlyFilesystem => 18,
+            FilesystemLoop => 19,
+            StaleNetworkFileHandle => 20,
+            InvalidInput => 21,
+            InvalidData => 22,
+            TimedOut => 23,
+            WriteZero => 24,
+            StorageFull => 25,
+            NotSeekable => 26,
+            FilesystemQuotaExceeded => 27,
+            FileTooLarge => 28,
+            ResourceBusy => 29,
+            ExecutableFileBusy => 30,
+            Deadlock => 31,
+            CrossesDevices => 32,
+            TooManyLinks => 33,
+            InvalidFilename => 34,
+            ArgumentListTooLong => 35,
+            Interrupted => 36,
+            Unsupported => 37,
+            UnexpectedEof => 38,
+            OutOfMemory => 39,
+            Other => 40,
+            Uncategorized => 41,
+        }
+    }
+
+    /// Returns the kind with the given number, as returned by
+    /// [`to_u8`](ErrorKind::to_u8), or [`None`] if there is none.
+    pub const fn from_u8(n: u8) -> Option<ErrorKind> {
+        use ErrorKind::*;
+        Some(match n {
+            1 => NotFound,
+            2 => PermissionDenied,
+            3 => ConnectionRefused,
+            4 => ConnectionReset,
+            5 => HostUnreachable,
+            6 => NetworkUnreachable,
+            7 => ConnectionAborted,
+            8 => NotConnected,
+            9 => AddrInUse,
+            10 => AddrNotAvailable,
+            11 => NetworkDown,
+            12 => BrokenPipe,
+            13 => AlreadyExists,
+            14 => WouldBlock,
+            15 => NotADirectory,
+            16 => IsADirectory,
+            17 => DirectoryNotEmpty,
+            18 => ReadOnlyFilesystem,
+            19 => FilesystemLoop,
+            20 => StaleNetworkFileHandle,
+            21 => InvalidInput,
+            22 => InvalidData,
+            23 => TimedOut,
+            24 => WriteZero,
+            25 => StorageFull,
+            26 => NotSeekable,
+            27 => FilesystemQuotaExceeded,
+            28 => FileTooLarge,
+            29 => ResourceBusy,
+            30 => ExecutableFileBusy,
+            31 => Deadlock,
+            32 => CrossesDevices,
+            33 => TooManyLinks,
+            34 => InvalidFilename,
+            35 => ArgumentListTooLong,
+            36 => Interrupted,
+            37 => Unsupported,
+            38 => UnexpectedEof,
+            39 => OutOfMemory,
+            40 => Other,
+            41 => Uncategorized,
+            _ => return None,
+        })
+    }
+
     pub(crate) fn as_str(&self) -> &'static str {
         use ErrorKind::*;
         // tidy-alphabetical-start
@@ -427,6 +850,16 @@ impl ErrorKind {
     }
 }
 
+impl TryFrom<u8> for ErrorKind {
+    type Error = Error;
+
+    /// Converts a number returned by [`ErrorKind::to_u8`] back into the
+    /// kind, failing with [`ErrorKind::InvalidData`] for unknown numbers.
+    fn try_from(n: u8) -> result::Result<ErrorKind, Error> {
+        ErrorKind::from_u8(n).ok_or(Error::UNKNOWN_ERROR_KIND)
+    }
+}
+
 impl fmt::Display for ErrorKind {
     /// Shows a human-readable description of the `ErrorKind`.
     ///
@@ -459,6 +892,7 @@ impl From<ErrorKind> for Error {
     /// assert_eq!("entity not found", format!("{error}"));
     /// ```
     #[inline]
+    #[cfg_attr(feature = "error_location", track_caller)]
     fn from(kind: ErrorKind) -> Error {
         Error::new_simple(kind)
     }
@@ -490,6 +924,8 @@ impl Error {
     /// // creating an error without payload (and without memory allocation)
     /// let eof_error = Error::from(ErrorKind::UnexpectedEof);
     /// ```
+    #[cfg(feature = "alloc")]
+    #[cfg_attr(feature = "error_location", track_caller)]
     #[inline(never)]
     pub fn new<E>(kind: ErrorKind, error: E) -> Error
     where
@@ -515,6 +951,8 @@ impl Error {
     /// // errors can also be created from other errors
     /// let custom_error2 = Error::other(custom_error);
     /// ```
+    #[cfg(feature = "alloc")]
+    #[cfg_attr(feature = "error_location", track_caller)]
     pub fn other<E>(error: E) -> Error
     where
         E: Into<Box<dyn core_error::Error + Send + Sync>>,
@@ -522,6 +960,8 @@ impl Error {
         Self::_new(ErrorKind::Other, error.into())
     }
 
+    #[cfg(feature = "alloc")]
+    #[cfg_attr(feature = "error_location", track_caller)]
     fn _new(kind: ErrorKind, error: Box<dyn core_error::Error + Send + Sync>) -> Error {
         Error::new_custom(Box::new(Custom { kind, error }))
     }
@@ -536,8 +976,10 @@ impl Error {
     ///
     /// This function should maybe change to `from_static_message<const MSG: &'static
     /// str>(kind: ErrorKind)` in the future, when const generics allow that.
+    #[doc(hidden)]
     #[inline]
-    pub(crate) const fn from_static_message(msg: &'static SimpleMessage) -> Error {
+    #[cfg_attr(feature = "error_location", track_caller)]
+    pub const fn from_static_message(msg: &'static SimpleMessage) -> Error {
         Error::new_simple_message(msg)
     }
 
@@ -567,6 +1009,7 @@ impl Error {
     /// assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
     /// # }
     /// ```
+    #[cfg_attr(feature = "error_location", track_caller)]
     pub fn from_raw_os_error(code: i64) -> Error {
         Error::new_os(code)
     }
@@ -602,11 +1045,12 @@ impl Error {
     /// ```
     #[inline]
     pub fn raw_os_error(&self) -> Option<i64> {
-        match self {
-            Error::Os(i) => Some(*i),
-            Error::Custom(..) => None,
-            Error::Simple(..) => None,
-            Error::SimpleMessage(..) => None,
+        match self.repr() {
+            Repr::Os(i) => Some(*i),
+            #[cfg(feature = "alloc")]
+            Repr::Custom(..) => None,
+            Repr::Simple(..) => None,
+            Repr::SimpleMessage(..) => None,
         }
     }
     /// Returns a reference to the inner error wrapped by this error (if any).
@@ -638,11 +1082,12 @@ impl Error {
     /// ```
     #[inline]
     pub fn get_ref(&self) -> Option<&(dyn core_error::Error + Send + Sync + 'static)> {
-        match self {
-            Error::Os(..) => None,
-            Error::Simple(..) => None,
-            Error::SimpleMessage(..) => None,
-            Error::Custom(c) => Some(&*c.error),
+        match self.repr() {
+            Repr::Os(..) => None,
+            Repr::Simple(..) => None,
+            Repr::SimpleMessage(..) => None,
+            #[cfg(feature = "alloc")]
+            Repr::Custom(c) => Some(&*c.error),
         }
     }
 
@@ -711,11 +1156,12 @@ impl Error {
     #[must_use]
     #[inline]
     pub fn get_mut(&mut self) -> Option<&mut (dyn core_error::Error + Send + Sync + 'static)> {
-        match self {
-            Error::Os(..) => None,
-            Error::Simple(..) => None,
-            Error::SimpleMessage(..) => None,
-            Error::Custom(c) => Some(&mut *c.error),
+        match self.repr_mut() {
+            Repr::Os(..) => None,
+            Repr::Simple(..) => None,
+            Repr::SimpleMessage(..) => None,
+            #[cfg(feature = "alloc")]
+            Repr::Custom(c) => Some(&mut *c.error),
         }
     }
     /// Consumes the `Error`, returning its inner error (if any).
@@ -745,14 +1191,15 @@ impl Error {
     ///     print_error(Error::new(ErrorKind::Other, "oh no!"));
     /// }
     /// ```
+    #[cfg(feature = "alloc")]
     #[must_use = "`self` will be dropped if the result is not used"]
     #[inline]
     pub fn into_inner(self) -> Option<Box<dyn core_error::Error + Send + Sync>> {
-        match self {
-            Error::Os(..) => None,
-            Error::Simple(..) => None,
-            Error::SimpleMessage(..) => None,
-            Error::Custom(c) => Some(c.error),
+        match self.into_repr() {
+            Repr::Os(..) => None,
+            Repr::Simple(..) => None,
+            Repr::SimpleMessage(..) => None,
+            Repr::Custom(c) => Some(c.error),
         }
     }
     /// Attempt to downcast the custom boxed error to `E`.
@@ -824,18 +1271,16 @@ impl Error {
     /// assert!(io_error.raw_os_error().is_none());
     /// # }
     /// ```
-    #[cfg(not(feature = "rust_before_181"))]
+    #[cfg(all(feature = "alloc", not(feature = "rust_before_181")))]
     pub fn downcast<E>(self) -> result::Result<E, Self>
     where
         E: core_error::Error + Send + Sync + 'static,
     {
-        match self {
-            Error::Custom(b) if b.error.is::<E>() => {
-                let res = b.error.downcast::<E>();
-                Ok(*res.unwrap())
-            }
-            err => Err(err),
+        if self.get_ref().is_some_and(|e| e.is::<E>()) {
+            let res = self.into_inner().unwrap().downcast::<E>();
+            return Ok(*res.unwrap());
         }
+        Err(self)
     }
 
     /// Returns the corresponding [`ErrorKind`] for this error.
@@ -867,68 +1312,139 @@ impl Error {
     #[must_use]
     #[inline]
     pub fn kind(&self) -> ErrorKind {
-        match self {
-            Error::Os(..) => ErrorKind::Other,
-            Error::Custom(c) => c.kind,
-            Error::Simple(kind) => *kind,
-            Error::SimpleMessage(m) => m.kind,
+        match self.repr() {
+            Repr::Os(code) => crate::io::ckb::decode_error_kind(*code),
+            #[cfg(feature = "alloc")]
+            Repr::Custom(c) => c.kind,
+            Repr::Simple(kind) => *kind,
+            Repr::SimpleMessage(m) => m.kind,
         }
     }
 
+    /// Returns the source location this error was created at.
+    ///
+    /// This is only recorded with the `error_location` feature, and is
+    /// [`None`] otherwise. Errors created by [`Error::new`], [`Error::other`],
+    /// [`Error::from_raw_os_error`], the conversion from [`ErrorKind`] and
+    /// `const_io_error!` record their caller. The errors that `read_exact`,
+    /// `read_buf_exact` and `write_all` fail with record the caller of those
+    /// methods.
+    ///
+    /// An error stored in a `const` item is created when the item is
+    /// evaluated, so it records where the item is defined rather than where
+    /// it is returned from. Return such errors through a `#[track_caller]`
+    /// function, or create them where they are returned, for a useful
+    /// location.
+    #[must_use]
+    #[inline]
+    pub fn location(&self) -> Option<&'static Location<'static>> {
+        #[cfg(feature = "error_location")]
+        return Some(self.inner.location);
+        #[cfg(not(feature = "error_location"))]
+        None
+    }
+
     #[inline]
     pub(crate) fn is_interrupted(&self) -> bool {
-        match self {
-            Error::Os(..) => false,
-            Error::Custom(c) => c.kind == ErrorKind::Interrupted,
-            Error::Simple(kind) => *kind == ErrorKind::Interrupted,
-            Error::SimpleMessage(m) => m.kind == ErrorKind::Interrupted,
+        match self.repr() {
+            Repr::Os(code) => crate::io::ckb::decode_error_kind(*code) == ErrorKind::Interrupted,
+            #[cfg(feature = "alloc")]
+            Repr::Custom(c) => c.kind == ErrorKind::Interrupted,
+            Repr::Simple(kind) => *kind == ErrorKind::Interrupted,
+            Repr::SimpleMessage(m) => m.kind == ErrorKind::Interrupted,
         }
     }
 }
 
+#[cfg(not(any(feature = "error_location", feature = "error_context")))]
 impl fmt::Display for Error {
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            Error::Os(code) => {
-                write!(fmt, "os error {code}")
+        self.fmt_display(fmt, None)
+    }
+}
+
+#[cfg(not(any(feature = "error_location", feature = "error_context")))]
+impl core_error::Error for Error {
+    #[allow(deprecated, deprecated_in_future)]
+    fn description(&self) -> &str {
+        self.error_description()
+    }
+
+    #[allow(deprecated)]
+    fn cause(&self) -> Option<&dyn core_error::Error> {
+        self.error_cause()
+    }
+
+    fn source(&self) -> Option<&(dyn core_error::Error + 'static)> {
+        self.error_source()
+    }
+}
+
+#[cfg(any(feature = "error_location", feature = "error_context"))]
+impl fmt::Display for Error {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        #[cfg(feature = "error_context")]
+        {
+            for message in self.context_messages() {
+                write!(fmt, "{message}: ")?;
+            }
+            if self.context.is_truncated() {
+                fmt.write_str("...: ")?;
             }
-            Error::Custom(ref c) => c.error.fmt(fmt),
-            Error::Simple(kind) => write!(fmt, "{}", kind.as_str()),
-            Error::SimpleMessage(msg) => msg.message.fmt(fmt),
         }
+        self.inner.fmt(fmt)
     }
 }
 
+#[cfg(any(feature = "error_location", feature = "error_context"))]
 impl core_error::Error for Error {
     #[allow(deprecated, deprecated_in_future)]
     fn description(&self) -> &str {
-        match self {
-            Error::Os(..) | Error::Simple(..) => self.kind().as_str(),
-            Error::SimpleMessage(msg) => msg.message,
-            Error::Custom(c) => c.error.description(),
-        }
+        self.inner.repr.error_description()
     }
 
     #[allow(deprecated)]
     fn cause(&self) -> Option<&dyn core_error::Error> {
-        match self {
-            Error::Os(..) => None,
-            Error::Simple(..) => None,
-            Error::SimpleMessage(..) => None,
-            Error::Custom(c) => c.error.cause(),
+        if self.has_context() {
+            Some(&self.inner)
+        } else {
+            self.inner.repr.error_cause()
         }
     }
 
     fn source(&self) -> Option<&(dyn core_error::Error + 'static)> {
-        match self {
-            Error::Os(..) => None,
-            Error::Simple(..) => None,
-            Error::SimpleMessage(..) => None,
-            Error::Custom(c) => c.error.source(),
+        if self.has_context() {
+            Some(&self.inner)
+        } else {
+            self.inner.repr.error_source()
         }
     }
 }
 
+#[cfg(any(feature = "error_location", feature = "error_context"))]
+impl fmt::Display for Inner {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.repr.fmt_display(fmt, self.location())
+    }
+}
+
+#[cfg(any(feature = "error_location", feature = "error_context"))]
+impl core_error::Error for Inner {
+    #[allow(deprecated, deprecated_in_future)]
+    fn description(&self) -> &str {
+        self.repr.error_description()
+    }
+
+    #[allow(deprecated)]
+    fn cause(&self) -> Option<&dyn core_error::Error> {
+        self.repr.error_cause()
+    }
+
+    fn source(&self) -> Option<&(dyn core_error::Error + 'static)> {
+        self.repr.error_source()
+    }
+}
+
 fn _assert_error_is_sync_send() {
     fn _is_sync_send<T: Sync + Send>() {}
     _is_sync_send::<Error>();
@@ -0,0 +1,142 @@
+//! Context chains for I/O errors that do not allocate.
+// The tests need `alloc`.
+#[cfg(all(test, feature = "alloc"))]
+mod tests;
+
+use super::Error;
+use core::fmt;
+
+/// The maximum number of context messages an [`Error`] records.
+///
+/// Adding a message to a full chain drops the innermost one, which shows up
+/// as `...` when the error is displayed.
+pub const MAX_CONTEXT_DEPTH: usize = 4;
+
+/// The context messages of an [`Error`], stored inline.
+#[derive(Clone, Copy)]
+pub(crate) struct Context {
+    // Innermost message first.
+    messages: [&'static str; MAX_CONTEXT_DEPTH],
+    depth: u8,
+    truncated: bool,
+}
+
+impl Context {
+    pub(crate) const EMPTY: Context = Context {
+        messages: [""; MAX_CONTEXT_DEPTH],
+        depth: 0,
+        truncated: false,
+    };
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.depth == 0
+    }
+
+    pub(crate) fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    fn messages(&self) -> impl DoubleEndedIterator<Item = &'static str> + '_ {
+        self.messages[..self.depth as usize].iter().rev().copied()
+    }
+
+    fn push(&mut self, message: &'static str) {
+        if (self.depth as usize) < MAX_CONTEXT_DEPTH {
+            self.messages[self.depth as usize] = message;
+            self.depth += 1;
+        } else {
+            self.messages.copy_within(1.., 0);
+            self.messages[MAX_CONTEXT_DEPTH - 1] = message;
+            self.truncated = true;
+        }
+    }
+}
+
+impl fmt::Debug for Context {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.messages()).finish()
+    }
+}
+
+impl Error {
+    /// Returns the context messages added to this error, outermost first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ckb_rust_std::io::{Error, ErrorExt, ErrorKind};
+    ///
+    /// let err = Error::from(ErrorKind::UnexpectedEof)
+    ///     .context("reading lock field")
+    ///     .context("reading witness 3");
+    /// assert!(err
+    ///     .context_messages()
+    ///     .eq(["reading witness 3", "reading lock field"]));
+    /// ```
+    pub fn context_messages(&self) -> impl DoubleEndedIterator<Item = &'static str> + '_ {
+        self.context.messages()
+    }
+
+    /// Returns `true` if inner context messages were dropped because the
+    /// chain already held [`MAX_CONTEXT_DEPTH`] of them.
+    pub fn is_context_truncated(&self) -> bool {
+        self.context.is_truncated()
+    }
+}
+
+/// Adds context to an [`Error`].
+///
+/// The messages are stored inline in the error, so unlike wrapping it with
+/// [`Error::new`], adding context never allocates. The error keeps its
+/// [`kind`](Error::kind), and the error without context is available through
+/// [`source`](super::core_error::Error::source).
+///
+/// When displayed, the outermost message comes first:
+///
+/// ```text
+/// reading witness 3: reading lock field: failed to fill whole buffer
+/// ```
+///
+/// # Examples
+///
+/// ```no_run
+/// use ckb_rust_std::io::{self, Read, ResultExt};
+///
+/// fn read_lock(reader: &mut impl Read) -> io::Result<[u8; 32]> {
+///     let mut lock = [0; 32];
+///     reader.read_exact(&mut lock).context("reading lock field")?;
+///     Ok(lock)
+/// }
+///
+/// fn read_witness(reader: &mut impl Read) -> io::Result<[u8; 32]> {
+///     read_lock(reader).context("reading witness 3")
+/// }
+/// ```
+pub trait ErrorExt: Sized {
+    /// Records `message` as the outermost context of the error.
+    ///
+    /// Once the error holds [`MAX_CONTEXT_DEPTH`] messages, the innermost
+    /// one is dropped to make room.
+    fn context(self, message: &'static str) -> Error;
+}
+
+impl ErrorExt for Error {
+    #[inline]
+    fn context(mut self, message: &'static str) -> Error {
+        self.context.push(message);
+        self
+    }
+}
+
+/// Adds context to the error of an [`io::Result`](super::Result).
+pub trait ResultExt<T> {
+    /// Maps the error with [`ErrorExt::context`], leaving `Ok` untouched.
+    fn context(self, message: &'static str) -> super::Result<T>;
+}
+
+impl<T> ResultExt<T> for super::Result<T> {
+    #[inline]
+    fn context(self, message: &'static str) -> super::Result<T> {
+        self.map_err(|e| e.context(message))
+    }
+}
@@ -0,0 +1,90 @@
+use super::*;
+use crate::io::error::core_error;
+use crate::io::{self, ErrorKind, Read, ResultExt};
+use alloc::string::{String, ToString};
+
+/// Renders `err` without the location that `error_location` appends.
+fn display(err: &dyn fmt::Display) -> String {
+    let s = err.to_string();
+    match s.find(" (at ") {
+        Some(i) if cfg!(feature = "error_location") => s[..i].to_string(),
+        _ => s,
+    }
+}
+
+fn read_lock(reader: &mut impl Read) -> io::Result<[u8; 4]> {
+    let mut lock = [0; 4];
+    reader.read_exact(&mut lock).context("reading lock field")?;
+    Ok(lock)
+}
+
+fn read_witness(reader: &mut impl Read) -> io::Result<[u8; 4]> {
+    read_lock(reader).context("reading witness 3")
+}
+
+#[test]
+fn context_chain() {
+    let mut data: &[u8] = &[1, 2];
+    let err = read_witness(&mut data).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+    assert_eq!(
+        display(&err),
+        "reading witness 3: reading lock field: failed to fill whole buffer"
+    );
+    assert!(err
+        .context_messages()
+        .eq(["reading witness 3", "reading lock field"]));
+    assert!(!err.is_context_truncated());
+
+    let mut data: &[u8] = &[1, 2, 3, 4];
+    assert_eq!(read_witness(&mut data).unwrap(), [1, 2, 3, 4]);
+}
+
+#[test]
+fn context_source() {
+    let err = Error::from_raw_os_error(2).context("loading cell");
+    assert_eq!(err.raw_os_error(), Some(2));
+    assert_eq!(err.kind(), ErrorKind::NotFound);
+    let source = core_error::Error::source(&err).unwrap();
+    assert_eq!(display(&source.to_string()), "item missing (os error 2)");
+    assert!(core_error::Error::source(source).is_none());
+}
+
+#[test]
+fn context_without_messages() {
+    let err = Error::from(ErrorKind::InvalidData);
+    assert_eq!(display(&err), "invalid data");
+    assert_eq!(err.context_messages().count(), 0);
+    assert!(core_error::Error::source(&err).is_none());
+}
+
+#[test]
+fn context_truncated() {
+    let mut err = Error::from(ErrorKind::Other).context("1");
+    for message in ["2", "3", "4", "5", "6"] {
+        err = err.context(message);
+    }
+    assert!(err.is_context_truncated());
+    assert!(err.context_messages().eq(["6", "5", "4", "3"]));
+    assert_eq!(display(&err), "6: 5: 4: 3: ...: other error");
+}
+
+#[test]
+fn context_debug() {
+    use crate::io::const_io_error;
+    use alloc::format;
+
+    const E: Error = const_io_error!(ErrorKind::NotFound, "hello");
+    let location = match E.location() {
+        Some(location) => format!(", location: {location}"),
+        None => String::new(),
+    };
+    let err = E.context("inner").context("outer");
+    assert_eq!(
+        format!("{err:?}"),
+        format!(
+            "Context {{ messages: [\"outer\", \"inner\"], \
+             error: Error {{ kind: NotFound, message: \"hello\"{location} }} }}"
+        )
+    );
+}
@@ -0,0 +1,335 @@
+#[cfg(feature = "alloc")]
+use super::SimpleMessage;
+use super::{Error, ErrorKind};
+use crate::io;
+#[cfg(feature = "alloc")]
+use crate::io::const_io_error;
+#[cfg(feature = "alloc")]
+use alloc::format;
+#[cfg(feature = "alloc")]
+use alloc::string::{String, ToString};
+#[cfg(feature = "alloc")]
+use core::fmt;
+
+#[test]
+fn test_size() {
+    #[cfg(not(any(feature = "error_location", feature = "error_context")))]
+    assert!(core::mem::size_of::<Error>() <= core::mem::size_of::<[usize; 2]>());
+    // The location adds one pointer.
+    #[cfg(all(feature = "error_location", not(feature = "error_context")))]
+    assert!(core::mem::size_of::<Error>() <= core::mem::size_of::<[usize; 3]>());
+    // The context messages and their count.
+    #[cfg(feature = "error_context")]
+    assert!(
+        core::mem::size_of::<Error>()
+            <= core::mem::size_of::<[usize; 3]>()
+                + core::mem::size_of::<([&str; super::MAX_CONTEXT_DEPTH], usize)>()
+    );
+}
+
+/// The field that `error_location` adds to the `Debug` output of `err`, or
+/// nothing without the feature.
+#[cfg(feature = "alloc")]
+fn location_field(err: &Error) -> String {
+    match err.location() {
+        Some(location) => format!(", location: {location}"),
+        None => String::new(),
+    }
+}
+
+/// The suffix that `error_location` adds to the `Display` output of `err`.
+#[cfg(feature = "alloc")]
+fn location_suffix(err: &Error) -> String {
+    match err.location() {
+        Some(location) => format!(" (at {location})"),
+        None => String::new(),
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_debug_error() {
+    use super::Custom;
+    use alloc::boxed::Box;
+
+    let code = 6;
+    let msg = "invalid file descriptor";
+    let kind = ErrorKind::InvalidInput;
+    let inner = Error::from_raw_os_error(code);
+    let inner_location = location_field(&inner);
+    let err = Error::new_custom(Box::new(Custom {
+        kind: ErrorKind::InvalidInput,
+        error: Box::new(inner),
+    }));
+    let expected = format!(
+        "Custom {{ \
+         kind: InvalidInput, \
+         error: Os {{ \
+         code: {:?}, \
+         kind: {:?}, \
+         message: {:?}{} \
+         }}{} \
+         }}",
+        code,
+        kind,
+        msg,
+        inner_location,
+        location_field(&err)
+    );
+    assert_eq!(format!("{err:?}"), expected);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_debug_simple() {
+    let err = Error::new_simple(ErrorKind::NotFound);
+    let location = match err.location() {
+        Some(location) => format!(", {location}"),
+        None => String::new(),
+    };
+    assert_eq!(format!("{err:?}"), format!("Kind(NotFound{location})"));
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_debug_simple_message() {
+    const E: Error = const_io_error!(ErrorKind::NotFound, "hello");
+    assert_eq!(
+        format!("{E:?}"),
+        format!(
+            "Error {{ kind: NotFound, message: \"hello\"{} }}",
+            location_field(&E)
+        )
+    );
+    let location = match E.location() {
+        Some(location) => format!("    location: {location},\n"),
+        None => String::new(),
+    };
+    assert_eq!(
+        format!("{E:#?}"),
+        format!("Error {{\n    kind: NotFound,\n    message: \"hello\",\n{location}}}")
+    );
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_debug_unknown_os_code() {
+    let err = Error::from_raw_os_error(42);
+    assert_eq!(
+        format!("{err:?}"),
+        format!(
+            "Os {{ code: 42, kind: Other, message: \"unknown error\"{} }}",
+            location_field(&err)
+        )
+    );
+}
+
+#[cfg(feature = "alloc")]
+#[derive(Debug)]
+struct TestError;
+
+#[cfg(feature = "alloc")]
+impl fmt::Display for TestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("asdf")
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl super::core_error::Error for TestError {}
+
+#[test]
+#[cfg(all(feature = "alloc", not(feature = "rust_before_181")))]
+fn test_downcasting() {
+    let err = Error::new(ErrorKind::Other, TestError);
+    assert!(err.get_ref().unwrap().is::<TestError>());
+    assert_eq!("asdf", err.get_ref().unwrap().to_string());
+    assert!(err.get_ref().unwrap().downcast_ref::<TestError>().is_some());
+    assert_eq!(
+        format!("{err:?}"),
+        format!(
+            "Custom {{ kind: Other, error: TestError{} }}",
+            location_field(&err)
+        )
+    );
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_const() {
+    const E: Error = const_io_error!(ErrorKind::NotFound, "hello");
+
+    assert_eq!(E.kind(), ErrorKind::NotFound);
+    assert_eq!(E.to_string(), format!("hello{}", location_suffix(&E)));
+    assert!(format!("{E:?}").contains("\"hello\""));
+    assert!(format!("{E:?}").contains("NotFound"));
+}
+
+#[cfg(not(any(feature = "error_location", feature = "error_context")))]
+#[test]
+fn test_variants() {
+    const E: Error = crate::io::const_io_error!(ErrorKind::NotFound, "hello");
+
+    assert!(matches!(
+        Error::from(ErrorKind::NotFound),
+        Error::Simple(ErrorKind::NotFound)
+    ));
+    assert!(matches!(Error::from_raw_os_error(2), Error::Os(2)));
+    assert!(matches!(E, Error::SimpleMessage(_)));
+}
+
+fn failing(err: Error) -> io::Result<u8> {
+    Err(err)
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_unwrap_err_formatting() {
+    let err = failing(Error::from_raw_os_error(2)).unwrap_err();
+    assert_eq!(
+        format!("{err:?}"),
+        format!(
+            "Os {{ code: 2, kind: NotFound, message: \"item missing\"{} }}",
+            location_field(&err)
+        )
+    );
+
+    let err = failing(Error::new(ErrorKind::InvalidData, "bad")).unwrap_err();
+    assert_eq!(
+        format!("{err:?}"),
+        format!(
+            "Custom {{ kind: InvalidData, error: \"bad\"{} }}",
+            location_field(&err)
+        )
+    );
+
+    let err = failing(Error::READ_EXACT_EOF).unwrap_err();
+    assert_eq!(
+        format!("{err:?}"),
+        format!(
+            "Error {{ kind: UnexpectedEof, message: \"failed to fill whole buffer\"{} }}",
+            location_field(&err)
+        )
+    );
+}
+
+// `error_location` adds a field after the message.
+#[test]
+#[should_panic(
+    expected = "called `Result::unwrap()` on an `Err` value: Os { code: 4, kind: InvalidData, message: \"wrong format\""
+)]
+fn test_unwrap_panic_message() {
+    failing(Error::from_raw_os_error(4)).unwrap();
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_simple_message_new() {
+    static MSG: SimpleMessage = SimpleMessage::new(ErrorKind::Other, "msg");
+    let err = Error::new_simple_message(&MSG);
+    assert_eq!(
+        format!("{err:?}"),
+        format!(
+            "Error {{ kind: Other, message: \"msg\"{} }}",
+            location_field(&err)
+        )
+    );
+    assert_eq!(err.to_string(), format!("msg{}", location_suffix(&err)));
+}
+
+#[test]
+#[cfg(all(feature = "alloc", feature = "error_location"))]
+fn test_location() {
+    const E: Error = const_io_error!(ErrorKind::NotFound, "hello");
+    let line = line!();
+    let errors = [
+        Error::from(ErrorKind::InvalidData),
+        const_io_error!(ErrorKind::NotFound, "hello"),
+        Error::new(ErrorKind::Other, "oh no"),
+        Error::other("oh no"),
+        Error::from_raw_os_error(2),
+    ];
+    for (i, err) in errors.iter().enumerate() {
+        let location = err.location().unwrap();
+        assert_eq!(location.file(), file!());
+        assert_eq!(location.line(), line + 2 + i as u32);
+    }
+    // A `const` is evaluated where it is defined, so that is all it can
+    // record.
+    assert_eq!(E.location().unwrap().line(), line - 1);
+
+    // `read_exact` and friends record their caller for the errors they
+    // return from a `const`, whichever reader implements them.
+    use crate::io::varint::{BufReadVarint, ReadVarint};
+    use crate::io::{BorrowedBuf, BufReader, Read, Write};
+    use core::mem::MaybeUninit;
+
+    let mut buf = [0; 4];
+    let mut storage = [MaybeUninit::uninit(); 4];
+    let mut borrowed = BorrowedBuf::from(&mut storage[..]);
+    let cursor = borrowed.unfilled();
+    let mut out = [0; 2];
+    let line = line!();
+    let errors = [
+        (&b"ab"[..]).read_exact(&mut buf).unwrap_err(),
+        BufReader::new(&b"ab"[..]).read_exact(&mut buf).unwrap_err(),
+        io::repeat(0).take(2).read_exact(&mut buf).unwrap_err(),
+        (&b"ab"[..]).read_buf_exact(cursor).unwrap_err(),
+        (&mut out[..]).write_all(b"abc").unwrap_err(),
+        io::Cursor::new(&mut out[..]).write_all(b"abc").unwrap_err(),
+        (&b"\x80"[..]).read_varint::<u32>().unwrap_err(),
+        (&b"\x80"[..]).read_varint_buffered::<u32>().unwrap_err(),
+    ];
+    for (i, err) in errors.iter().enumerate() {
+        let location = err.location().unwrap();
+        assert_eq!(location.file(), file!());
+        assert_eq!(location.line(), line + 2 + i as u32, "error {i}");
+    }
+
+    let err = failing(Error::from(ErrorKind::InvalidData)).unwrap_err();
+    let location = err.location().unwrap();
+    assert_eq!(err.to_string(), format!("invalid data (at {location})"));
+    assert_eq!(format!("{err:?}"), format!("Kind(InvalidData, {location})"));
+    let err = failing(Error::from_raw_os_error(4)).unwrap_err();
+    let location = err.location().unwrap();
+    assert_eq!(
+        format!("{err:?}"),
+        format!(
+            "Os {{ code: 4, kind: InvalidData, message: \"wrong format\", location: {location} }}"
+        )
+    );
+    let err = failing(Error::new(ErrorKind::InvalidData, "bad")).unwrap_err();
+    let location = err.location().unwrap();
+    assert_eq!(
+        format!("{err:?}"),
+        format!("Custom {{ kind: InvalidData, error: \"bad\", location: {location} }}")
+    );
+}
+
+#[test]
+#[cfg(not(feature = "error_location"))]
+fn test_no_location() {
+    assert!(Error::from(ErrorKind::InvalidData).location().is_none());
+}
+
+#[test]
+fn test_kind_numbers() {
+    for n in 0..=u8::MAX {
+        match ErrorKind::from_u8(n) {
+            Some(kind) => {
+                assert_eq!(kind.to_u8(), n);
+                assert_eq!(ErrorKind::try_from(n).unwrap(), kind);
+            }
+            None => {
+                assert!(n == 0 || n > 41);
+                let err = ErrorKind::try_from(n).unwrap_err();
+                assert_eq!(err.kind(), ErrorKind::InvalidData);
+            }
+        }
+    }
+    // These numbers are part of the wire format and must never change.
+    assert_eq!(ErrorKind::NotFound.to_u8(), 1);
+    assert_eq!(ErrorKind::InvalidData.to_u8(), 22);
+    assert_eq!(ErrorKind::UnexpectedEof.to_u8(), 38);
+    assert_eq!(ErrorKind::Uncategorized.to_u8(), 41);
+}
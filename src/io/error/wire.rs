@@ -0,0 +1,198 @@
+//! A byte encoding of errors for passing them between scripts.
+#[cfg(test)]
+mod tests;
+
+use super::{Error, ErrorKind, Repr, SimpleMessage};
+use crate::io::{self, const_io_error, Read, Write};
+use core::fmt;
+
+const HAS_OS_CODE: u8 = 1;
+const HAS_MESSAGE_ID: u8 = 2;
+
+const UNKNOWN_FLAGS: Error =
+    const_io_error!(ErrorKind::InvalidData, "unknown error encoding flags");
+
+/// The parts of an [`Error`] that can be sent to another script: its kind,
+/// its OS error code and, for errors created with `const_io_error!`, the id
+/// of its message.
+///
+/// The encoding written by [`write_to`](WireError::write_to) is fixed:
+///
+/// | bytes | content                                               |
+/// |-------|-------------------------------------------------------|
+/// | 1     | the kind, as [`ErrorKind::to_u8`]                     |
+/// | 1     | flags: `1` if an OS code follows, `2` if an id does    |
+/// | 8     | the OS code as a little-endian `i64`, if flagged       |
+/// | 4     | the message id as a little-endian `u32`, if flagged    |
+///
+/// Other details, such as the payload of [`Error::new`], are not sent. The
+/// receiver gets the message back by passing the errors it knows to
+/// [`to_error`](WireError::to_error).
+///
+/// # Examples
+///
+/// ```no_run
+/// use ckb_rust_std::const_io_error;
+/// use ckb_rust_std::io::{self, ErrorKind, Read, WireError, Write};
+///
+/// const BAD_SIGNATURE: io::Error = const_io_error!(ErrorKind::InvalidData, "bad signature");
+///
+/// // In the child script, e.g. writing to a pipe to the parent.
+/// fn report(err: &io::Error, parent: &mut impl Write) -> io::Result<()> {
+///     WireError::from(err).write_to(parent)
+/// }
+///
+/// // In the parent script.
+/// fn receive(child: &mut impl Read) -> io::Result<io::Error> {
+///     Ok(WireError::read_from(child)?.to_error(&[BAD_SIGNATURE]))
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct WireError {
+    kind: ErrorKind,
+    os_code: Option<i64>,
+    message_id: Option<u32>,
+}
+
+impl WireError {
+    /// The most bytes [`write_to`](WireError::write_to) writes.
+    pub const MAX_ENCODED_LEN: usize = 14;
+
+    /// Returns the kind of the error.
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    /// Returns the OS error code, if the error had one.
+    pub fn os_code(&self) -> Option<i64> {
+        self.os_code
+    }
+
+    /// Returns the id of the error's static message, if it had one. See
+    /// [`Error::message_id`].
+    pub fn message_id(&self) -> Option<u32> {
+        self.message_id
+    }
+
+    /// Writes the encoding of the error to `writer`.
+    pub fn write_to<W: Write + ?Sized>(&self, writer: &mut W) -> io::Result<()> {
+        let mut buf = [0; Self::MAX_ENCODED_LEN];
+        buf[0] = self.kind.to_u8();
+        let mut len = 2;
+        if let Some(code) = self.os_code {
+            buf[1] |= HAS_OS_CODE;
+            buf[len..len + 8].copy_from_slice(&code.to_le_bytes());
+            len += 8;
+        }
+        if let Some(id) = self.message_id {
+            buf[1] |= HAS_MESSAGE_ID;
+            buf[len..len + 4].copy_from_slice(&id.to_le_bytes());
+            len += 4;
+        }
+        writer.write_all(&buf[..len])
+    }
+
+    /// Reads an encoded error from `reader`.
+    ///
+    /// Fails with [`ErrorKind::InvalidData`] if the kind or the flags are
+    /// unknown, e.g. because the error was sent by a newer release.
+    pub fn read_from<R: Read + ?Sized>(reader: &mut R) -> io::Result<WireError> {
+        let mut header = [0; 2];
+        reader.read_exact(&mut header)?;
+        let kind = ErrorKind::try_from(header[0])?;
+        let flags = header[1];
+        if flags & !(HAS_OS_CODE | HAS_MESSAGE_ID) != 0 {
+            return Err(UNKNOWN_FLAGS);
+        }
+        let mut os_code = None;
+        if flags & HAS_OS_CODE != 0 {
+            let mut buf = [0; 8];
+            reader.read_exact(&mut buf)?;
+            os_code = Some(i64::from_le_bytes(buf));
+        }
+        let mut message_id = None;
+        if flags & HAS_MESSAGE_ID != 0 {
+            let mut buf = [0; 4];
+            reader.read_exact(&mut buf)?;
+            message_id = Some(u32::from_le_bytes(buf));
+        }
+        Ok(WireError {
+            kind,
+            os_code,
+            message_id,
+        })
+    }
+
+    /// Turns the received error back into an [`Error`].
+    ///
+    /// An OS code gives [`Error::from_raw_os_error`]. A message id gives the
+    /// error from `known` with the same kind and id, so errors defined with
+    /// `const_io_error!` on both sides round-trip. Otherwise, the result is
+    /// `Error::from(self.kind())`.
+    pub fn to_error(&self, known: &[Error]) -> Error {
+        if let Some(code) = self.os_code {
+            return Error::from_raw_os_error(code);
+        }
+        if let Some(id) = self.message_id {
+            for error in known {
+                if let Repr::SimpleMessage(msg) = *error.repr() {
+                    if msg.kind == self.kind && msg.id() == id {
+                        return Error::from_static_message(msg);
+                    }
+                }
+            }
+        }
+        Error::from(self.kind)
+    }
+}
+
+impl From<&Error> for WireError {
+    /// Encodes the error. Its context messages are not sent.
+    fn from(error: &Error) -> WireError {
+        WireError {
+            kind: error.kind(),
+            os_code: error.raw_os_error(),
+            message_id: error.message_id(),
+        }
+    }
+}
+
+impl fmt::Display for WireError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.os_code, self.message_id) {
+            (Some(code), _) => write!(f, "{} (os error {code})", self.kind),
+            (None, Some(id)) => write!(f, "{} (message {id:#010x})", self.kind),
+            (None, None) => self.kind.fmt(f),
+        }
+    }
+}
+
+impl Error {
+    /// Returns the id of the static message of an error created with
+    /// `const_io_error!`, or [`None`] for other errors.
+    ///
+    /// The id is a hash of the message text, so it stays the same as long
+    /// as the text does, no matter which script or release the error comes
+    /// from. It is what [`WireError`] sends in place of the message.
+    pub fn message_id(&self) -> Option<u32> {
+        match *self.repr() {
+            Repr::SimpleMessage(msg) => Some(msg.id()),
+            _ => None,
+        }
+    }
+}
+
+impl SimpleMessage {
+    /// The 32-bit FNV-1a hash of the message.
+    const fn id(&self) -> u32 {
+        let bytes = self.message.as_bytes();
+        let mut hash: u32 = 0x811c_9dc5;
+        let mut i = 0;
+        while i < bytes.len() {
+            hash ^= bytes[i] as u32;
+            hash = hash.wrapping_mul(0x0100_0193);
+            i += 1;
+        }
+        hash
+    }
+}
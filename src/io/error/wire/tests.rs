@@ -0,0 +1,118 @@
+use super::*;
+#[cfg(feature = "alloc")]
+use crate::io::Cursor;
+#[cfg(feature = "error_context")]
+use crate::io::ErrorExt;
+#[cfg(feature = "alloc")]
+use alloc::string::ToString;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+const BAD_SIGNATURE: Error = const_io_error!(ErrorKind::InvalidData, "bad signature");
+#[cfg(feature = "alloc")]
+const BAD_LENGTH: Error = const_io_error!(ErrorKind::InvalidData, "bad length");
+
+#[cfg(feature = "alloc")]
+fn round_trip(error: &Error) -> (Vec<u8>, WireError) {
+    let mut buf = Vec::new();
+    WireError::from(error).write_to(&mut buf).unwrap();
+    let wire = WireError::read_from(&mut &buf[..]).unwrap();
+    (buf, wire)
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn encode_kind() {
+    let (buf, wire) = round_trip(&Error::from(ErrorKind::UnexpectedEof));
+    assert_eq!(buf, [38, 0]);
+    assert_eq!(wire.kind(), ErrorKind::UnexpectedEof);
+    assert_eq!(wire.os_code(), None);
+    assert_eq!(wire.message_id(), None);
+    assert_eq!(wire.to_error(&[]).kind(), ErrorKind::UnexpectedEof);
+    assert_eq!(wire.to_string(), "unexpected end of file");
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn encode_os_code() {
+    let (buf, wire) = round_trip(&Error::from_raw_os_error(-2));
+    assert_eq!(buf, [40, 1, 0xfe, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]);
+    assert_eq!(wire.os_code(), Some(-2));
+
+    let (_, wire) = round_trip(&Error::from_raw_os_error(4));
+    let error = wire.to_error(&[]);
+    assert_eq!(error.raw_os_error(), Some(4));
+    assert_eq!(error.kind(), ErrorKind::InvalidData);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn encode_message() {
+    let (buf, wire) = round_trip(&BAD_SIGNATURE);
+    assert_eq!(buf.len(), 6);
+    assert_eq!(buf[..2], [22, 2]);
+    assert_eq!(wire.message_id(), BAD_SIGNATURE.message_id());
+    assert_ne!(wire.message_id(), BAD_LENGTH.message_id());
+
+    // The message comes back if the receiver knows it.
+    let error = wire.to_error(&[BAD_LENGTH, BAD_SIGNATURE]);
+    assert_eq!(error.message_id(), BAD_SIGNATURE.message_id());
+    assert!(error.get_ref().is_none());
+    assert_eq!(error.kind(), ErrorKind::InvalidData);
+    let error = wire.to_error(&[BAD_LENGTH]);
+    assert_eq!(error.message_id(), None);
+    assert_eq!(error.kind(), ErrorKind::InvalidData);
+}
+
+#[test]
+fn message_id_is_stable() {
+    // FNV-1a of "bad signature". This must never change.
+    assert_eq!(BAD_SIGNATURE.message_id(), Some(0x6acf_e1e2));
+    assert_eq!(Error::from(ErrorKind::Other).message_id(), None);
+    #[cfg(feature = "alloc")]
+    assert_eq!(
+        Error::new(ErrorKind::Other, "bad signature").message_id(),
+        None
+    );
+}
+
+#[cfg(feature = "error_context")]
+#[test]
+fn encode_context() {
+    let error = BAD_SIGNATURE.context("reading witness");
+    assert_eq!(error.message_id(), BAD_SIGNATURE.message_id());
+    let wire = WireError::from(&error);
+    assert_eq!(wire, WireError::from(&BAD_SIGNATURE));
+}
+
+#[test]
+fn decode_invalid() {
+    let err = WireError::read_from(&mut &[0, 0][..]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    let err = WireError::read_from(&mut &[200, 0][..]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    let err = WireError::read_from(&mut &[1, 4][..]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    let err = WireError::read_from(&mut &[1, 1, 0][..]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn encode_stream() {
+    let errors = [
+        Error::from(ErrorKind::BrokenPipe),
+        Error::from_raw_os_error(7),
+        BAD_SIGNATURE,
+    ];
+    let mut stream = Cursor::new(Vec::new());
+    for error in &errors {
+        WireError::from(error).write_to(&mut stream).unwrap();
+    }
+    assert_eq!(stream.get_ref().len(), 2 + 10 + 6,);
+    stream.set_position(0);
+    for error in &errors {
+        let wire = WireError::read_from(&mut stream).unwrap();
+        assert_eq!(wire, WireError::from(error));
+    }
+}
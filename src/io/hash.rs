@@ -0,0 +1,238 @@
+//! Hashing data while it streams through [`Read`] and [`Write`].
+//!
+//! [`HashingWriter`] feeds everything written through it into a [`Digest`],
+//! and [`VerifyingReader`] checks everything read through it against an
+//! expected digest once the inner reader reaches EOF. Any hash function can
+//! be plugged in by implementing [`Digest`]; with the `blake2b` feature,
+//! [`Blake2b`] provides CKB's default hash (blake2b-256 personalized with
+//! `"ckb-default-hash"`).
+#[cfg(feature = "blake2b")]
+mod blake2b;
+// The tests need `alloc`.
+#[cfg(all(test, feature = "alloc"))]
+mod tests;
+
+#[cfg(feature = "blake2b")]
+pub use self::blake2b::{blake2b_256, Blake2b, CKB_HASH_PERSONALIZATION};
+
+use crate::io::{self, const_io_error, ErrorKind, Read, Write};
+use core::fmt;
+
+/// An incremental hash function.
+pub trait Digest {
+    /// The finished hash, usually a byte array.
+    type Output: AsRef<[u8]> + PartialEq;
+
+    /// Feeds `data` into the hash state.
+    fn update(&mut self, data: &[u8]);
+
+    /// Consumes the hash state and returns the digest of all data fed to it.
+    fn finalize(self) -> Self::Output;
+}
+
+/// A writer which hashes every byte accepted by the inner writer.
+///
+/// Only the bytes the inner writer reports as written are hashed, so the
+/// digest always matches what actually reached it, even across short writes.
+///
+/// # Examples
+///
+/// ```no_run
+/// use ckb_rust_std::io::{self, hash::{Blake2b, HashingWriter}};
+///
+/// fn main() -> io::Result<()> {
+///     let mut reader: &[u8] = b"some cell data";
+///     let mut writer = HashingWriter::<_, Blake2b>::new(io::sink());
+///     io::copy(&mut reader, &mut writer)?;
+///     let (_sink, hash) = writer.finalize();
+///     Ok(())
+/// }
+/// ```
+pub struct HashingWriter<W, D> {
+    inner: W,
+    digest: D,
+}
+
+impl<W: Write, D: Digest + Default> HashingWriter<W, D> {
+    /// Creates a new `HashingWriter` with a default-constructed digest.
+    pub fn new(inner: W) -> HashingWriter<W, D> {
+        HashingWriter::with_digest(inner, D::default())
+    }
+}
+
+impl<W: Write, D: Digest> HashingWriter<W, D> {
+    /// Creates a new `HashingWriter` which feeds `digest`.
+    pub fn with_digest(inner: W, digest: D) -> HashingWriter<W, D> {
+        HashingWriter { inner, digest }
+    }
+
+    /// Gets a reference to the underlying writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Gets a mutable reference to the underlying writer.
+    ///
+    /// Bytes written directly to the underlying writer are not hashed.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Consumes this `HashingWriter`, returning the underlying writer and the
+    /// digest of everything written so far.
+    ///
+    /// The inner writer is not flushed.
+    pub fn finalize(self) -> (W, D::Output) {
+        (self.inner, self.digest.finalize())
+    }
+}
+
+impl<W: Write, D: Digest> Write for HashingWriter<W, D> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.digest.update(&buf[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<W: fmt::Debug, D> fmt::Debug for HashingWriter<W, D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HashingWriter")
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
+}
+
+enum State<D: Digest> {
+    Hashing(D),
+    Verified,
+    Mismatch,
+}
+
+/// A reader which checks the data read through it against an expected digest.
+///
+/// Data is passed through unchanged. When the inner reader reports EOF, the
+/// digest of everything read is compared with the expected one: if they
+/// differ, that read and every read after it fail with
+/// [`ErrorKind::InvalidData`] instead of returning `Ok(0)`. A successful EOF
+/// therefore means the whole stream was authentic, but the bytes returned
+/// before it must not be trusted until then.
+///
+/// # Examples
+///
+/// ```no_run
+/// use ckb_rust_std::io::{self, hash::{Blake2b, VerifyingReader}, Read};
+///
+/// fn load(data: &[u8], data_hash: [u8; 32]) -> io::Result<()> {
+///     let mut reader = VerifyingReader::<_, Blake2b>::new(data, data_hash);
+///     let mut header = [0u8; 4];
+///     reader.read_exact(&mut header)?;
+///     // Reaching EOF checks the hash.
+///     io::copy(&mut reader, &mut io::sink())?;
+///     Ok(())
+/// }
+/// ```
+pub struct VerifyingReader<R, D: Digest> {
+    inner: R,
+    state: State<D>,
+    expected: D::Output,
+}
+
+impl<R: Read, D: Digest + Default> VerifyingReader<R, D> {
+    /// Creates a new `VerifyingReader` with a default-constructed digest.
+    pub fn new(inner: R, expected: D::Output) -> VerifyingReader<R, D> {
+        VerifyingReader::with_digest(inner, D::default(), expected)
+    }
+}
+
+impl<R: Read, D: Digest> VerifyingReader<R, D> {
+    /// Creates a new `VerifyingReader` which feeds `digest`.
+    pub fn with_digest(inner: R, digest: D, expected: D::Output) -> VerifyingReader<R, D> {
+        VerifyingReader {
+            inner,
+            state: State::Hashing(digest),
+            expected,
+        }
+    }
+
+    /// Gets a reference to the underlying reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Gets a mutable reference to the underlying reader.
+    ///
+    /// Bytes read directly from the underlying reader are not hashed.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Returns `true` once EOF has been reached and the digest matched.
+    pub fn is_verified(&self) -> bool {
+        matches!(self.state, State::Verified)
+    }
+
+    /// Consumes this `VerifyingReader`, returning the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    fn verify(&mut self) -> io::Result<()> {
+        match core::mem::replace(&mut self.state, State::Mismatch) {
+            State::Hashing(digest) => {
+                if digest.finalize() == self.expected {
+                    self.state = State::Verified;
+                    Ok(())
+                } else {
+                    Err(HASH_MISMATCH)
+                }
+            }
+            State::Verified => {
+                self.state = State::Verified;
+                Ok(())
+            }
+            State::Mismatch => Err(HASH_MISMATCH),
+        }
+    }
+}
+
+const HASH_MISMATCH: io::Error = const_io_error!(
+    ErrorKind::InvalidData,
+    "data does not match the expected hash"
+);
+
+impl<R: Read, D: Digest> Read for VerifyingReader<R, D> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if let State::Mismatch = self.state {
+            return Err(HASH_MISMATCH);
+        }
+        let n = self.inner.read(buf)?;
+        if n > 0 {
+            match &mut self.state {
+                State::Hashing(digest) => digest.update(&buf[..n]),
+                // The inner reader produced more data after EOF; it can no
+                // longer be trusted.
+                _ => {
+                    self.state = State::Mismatch;
+                    return Err(HASH_MISMATCH);
+                }
+            }
+        } else if !buf.is_empty() {
+            self.verify()?;
+        }
+        Ok(n)
+    }
+}
+
+impl<R: fmt::Debug, D: Digest> fmt::Debug for VerifyingReader<R, D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("VerifyingReader")
+            .field("inner", &self.inner)
+            .field("verified", &matches!(self.state, State::Verified))
+            .finish_non_exhaustive()
+    }
+}
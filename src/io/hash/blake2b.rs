@@ -0,0 +1,201 @@
+//! A portable BLAKE2b (RFC 7693) producing 256-bit digests.
+//!
+//! Only unkeyed hashing with an optional 16-byte personalization is
+//! supported, which is all CKB needs.
+use super::Digest;
+use core::fmt;
+
+/// The personalization CKB uses for its default hash.
+pub const CKB_HASH_PERSONALIZATION: &[u8; 16] = b"ckb-default-hash";
+
+const BLOCK_LEN: usize = 128;
+const OUT_LEN: usize = 32;
+
+const IV: [u64; 8] = [
+    0x6a09e667f3bcc908,
+    0xbb67ae8584caa73b,
+    0x3c6ef372fe94f82b,
+    0xa54ff53a5f1d36f1,
+    0x510e527fade682d1,
+    0x9b05688c2b3e6c1f,
+    0x1f83d9abfb41bd6b,
+    0x5be0cd19137e2179,
+];
+
+const SIGMA: [[usize; 16]; 12] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+];
+
+/// BLAKE2b with a 32-byte output.
+///
+/// [`Blake2b::default`] uses [`CKB_HASH_PERSONALIZATION`], matching
+/// `ckb_hash::blake2b_256`.
+#[derive(Clone)]
+pub struct Blake2b {
+    h: [u64; 8],
+    // Total number of bytes compressed so far.
+    t: u128,
+    buf: [u8; BLOCK_LEN],
+    buf_len: usize,
+}
+
+impl Blake2b {
+    /// Creates a hasher with the given personalization.
+    pub const fn with_personalization(personal: &[u8; 16]) -> Blake2b {
+        let mut h = IV;
+        // Parameter block: digest length, key length 0, fanout 1, depth 1.
+        h[0] ^= 0x01010000 ^ OUT_LEN as u64;
+        h[6] ^= u64::from_le_bytes([
+            personal[0],
+            personal[1],
+            personal[2],
+            personal[3],
+            personal[4],
+            personal[5],
+            personal[6],
+            personal[7],
+        ]);
+        h[7] ^= u64::from_le_bytes([
+            personal[8],
+            personal[9],
+            personal[10],
+            personal[11],
+            personal[12],
+            personal[13],
+            personal[14],
+            personal[15],
+        ]);
+        Blake2b {
+            h,
+            t: 0,
+            buf: [0; BLOCK_LEN],
+            buf_len: 0,
+        }
+    }
+
+    /// Feeds `data` into the hash state.
+    pub fn update(&mut self, mut data: &[u8]) {
+        // The last block must be compressed with the final flag set, so a
+        // full buffer is only compressed once more data arrives.
+        if self.buf_len > 0 {
+            let take = core::cmp::min(BLOCK_LEN - self.buf_len, data.len());
+            self.buf[self.buf_len..self.buf_len + take].copy_from_slice(&data[..take]);
+            self.buf_len += take;
+            data = &data[take..];
+            if data.is_empty() {
+                return;
+            }
+            self.t += BLOCK_LEN as u128;
+            let block = self.buf;
+            self.compress(&block, false);
+            self.buf_len = 0;
+        }
+        while data.len() > BLOCK_LEN {
+            let (block, rest) = data.split_at(BLOCK_LEN);
+            self.t += BLOCK_LEN as u128;
+            self.compress(block.try_into().unwrap(), false);
+            data = rest;
+        }
+        self.buf[..data.len()].copy_from_slice(data);
+        self.buf_len = data.len();
+    }
+
+    /// Returns the digest of all data fed so far.
+    pub fn finalize(mut self) -> [u8; OUT_LEN] {
+        self.t += self.buf_len as u128;
+        self.buf[self.buf_len..].fill(0);
+        let block = self.buf;
+        self.compress(&block, true);
+        let mut out = [0u8; OUT_LEN];
+        for (chunk, word) in out.chunks_exact_mut(8).zip(self.h.iter()) {
+            chunk.copy_from_slice(&word.to_le_bytes());
+        }
+        out
+    }
+
+    fn compress(&mut self, block: &[u8; BLOCK_LEN], last: bool) {
+        let mut m = [0u64; 16];
+        for (word, chunk) in m.iter_mut().zip(block.chunks_exact(8)) {
+            *word = u64::from_le_bytes(chunk.try_into().unwrap());
+        }
+        let mut v = [0u64; 16];
+        v[..8].copy_from_slice(&self.h);
+        v[8..].copy_from_slice(&IV);
+        v[12] ^= self.t as u64;
+        v[13] ^= (self.t >> 64) as u64;
+        if last {
+            v[14] = !v[14];
+        }
+
+        #[inline(always)]
+        fn g(v: &mut [u64; 16], a: usize, b: usize, c: usize, d: usize, x: u64, y: u64) {
+            v[a] = v[a].wrapping_add(v[b]).wrapping_add(x);
+            v[d] = (v[d] ^ v[a]).rotate_right(32);
+            v[c] = v[c].wrapping_add(v[d]);
+            v[b] = (v[b] ^ v[c]).rotate_right(24);
+            v[a] = v[a].wrapping_add(v[b]).wrapping_add(y);
+            v[d] = (v[d] ^ v[a]).rotate_right(16);
+            v[c] = v[c].wrapping_add(v[d]);
+            v[b] = (v[b] ^ v[c]).rotate_right(63);
+        }
+
+        for s in SIGMA.iter() {
+            g(&mut v, 0, 4, 8, 12, m[s[0]], m[s[1]]);
+            g(&mut v, 1, 5, 9, 13, m[s[2]], m[s[3]]);
+            g(&mut v, 2, 6, 10, 14, m[s[4]], m[s[5]]);
+            g(&mut v, 3, 7, 11, 15, m[s[6]], m[s[7]]);
+            g(&mut v, 0, 5, 10, 15, m[s[8]], m[s[9]]);
+            g(&mut v, 1, 6, 11, 12, m[s[10]], m[s[11]]);
+            g(&mut v, 2, 7, 8, 13, m[s[12]], m[s[13]]);
+            g(&mut v, 3, 4, 9, 14, m[s[14]], m[s[15]]);
+        }
+
+        for i in 0..8 {
+            self.h[i] ^= v[i] ^ v[i + 8];
+        }
+    }
+}
+
+impl Default for Blake2b {
+    fn default() -> Blake2b {
+        Blake2b::with_personalization(CKB_HASH_PERSONALIZATION)
+    }
+}
+
+impl fmt::Debug for Blake2b {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Blake2b").finish_non_exhaustive()
+    }
+}
+
+impl Digest for Blake2b {
+    type Output = [u8; OUT_LEN];
+
+    #[inline]
+    fn update(&mut self, data: &[u8]) {
+        Blake2b::update(self, data)
+    }
+
+    #[inline]
+    fn finalize(self) -> [u8; OUT_LEN] {
+        Blake2b::finalize(self)
+    }
+}
+
+/// Hashes `data` with CKB's default hash.
+pub fn blake2b_256(data: &[u8]) -> [u8; OUT_LEN] {
+    let mut hasher = Blake2b::default();
+    hasher.update(data);
+    hasher.finalize()
+}
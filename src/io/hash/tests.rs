@@ -0,0 +1,199 @@
+use super::*;
+use crate::io::{copy, BufRead, BufReader, Cursor};
+use alloc::vec::Vec;
+
+/// A toy digest: a running byte sum and count, enough to tell streams apart.
+#[derive(Default, Clone, Debug)]
+struct SumDigest {
+    sum: u64,
+    len: u64,
+}
+
+impl Digest for SumDigest {
+    type Output = [u8; 16];
+
+    fn update(&mut self, data: &[u8]) {
+        self.sum += data.iter().map(|&b| b as u64).sum::<u64>();
+        self.len += data.len() as u64;
+    }
+
+    fn finalize(self) -> [u8; 16] {
+        let mut out = [0; 16];
+        out[..8].copy_from_slice(&self.sum.to_le_bytes());
+        out[8..].copy_from_slice(&self.len.to_le_bytes());
+        out
+    }
+}
+
+fn sum_of(data: &[u8]) -> [u8; 16] {
+    let mut d = SumDigest::default();
+    d.update(data);
+    d.finalize()
+}
+
+/// Accepts at most `max` bytes per write.
+struct ShortWriter {
+    max: usize,
+    data: Vec<u8>,
+}
+
+impl Write for ShortWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = core::cmp::min(self.max, buf.len());
+        self.data.extend_from_slice(&buf[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn hashing_writer_hashes_accepted_bytes() {
+    let mut w = HashingWriter::<_, SumDigest>::new(ShortWriter {
+        max: 3,
+        data: Vec::new(),
+    });
+    assert_eq!(w.write(b"hello").unwrap(), 3);
+    w.write_all(b"lo, world").unwrap();
+    let (inner, digest) = w.finalize();
+    assert_eq!(inner.data, b"hello, world");
+    assert_eq!(digest, sum_of(b"hello, world"));
+}
+
+#[test]
+fn hashing_writer_with_copy() {
+    let data: Vec<u8> = (0..5000u32).map(|i| i as u8).collect();
+    let mut w = HashingWriter::with_digest(Vec::new(), SumDigest::default());
+    assert_eq!(copy(&mut &data[..], &mut w).unwrap(), 5000);
+    let (inner, digest) = w.finalize();
+    assert_eq!(inner, data);
+    assert_eq!(digest, sum_of(&data));
+}
+
+#[test]
+fn verifying_reader_accepts_matching_data() {
+    let data = b"some cell data";
+    let mut r = VerifyingReader::<_, SumDigest>::new(&data[..], sum_of(data));
+    let mut out = Vec::new();
+    assert!(!r.is_verified());
+    r.read_to_end(&mut out).unwrap();
+    assert_eq!(out, data);
+    assert!(r.is_verified());
+    // EOF stays EOF.
+    assert_eq!(r.read(&mut [0; 4]).unwrap(), 0);
+}
+
+#[test]
+fn verifying_reader_rejects_mismatch() {
+    let data = b"some cell data";
+    let mut r = VerifyingReader::<_, SumDigest>::new(&data[..], sum_of(b"other data"));
+    let mut buf = [0; 4];
+    r.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"some");
+
+    let mut out = Vec::new();
+    let err = r.read_to_end(&mut out).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    assert!(!r.is_verified());
+    // The failure is sticky.
+    let err = r.read(&mut buf).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+}
+
+#[test]
+fn verifying_reader_empty_buf_is_not_eof() {
+    let data = b"abc";
+    let mut r = VerifyingReader::<_, SumDigest>::new(&data[..], sum_of(b"xyz"));
+    assert_eq!(r.read(&mut []).unwrap(), 0);
+    assert!(!r.is_verified());
+}
+
+#[test]
+fn verifying_reader_buffered() {
+    let data: Vec<u8> = (0..300u32).map(|i| i as u8).collect();
+    let r = VerifyingReader::<_, SumDigest>::new(Cursor::new(&data), sum_of(&data));
+    let mut r = BufReader::with_capacity(7, r);
+    let mut out = Vec::new();
+    while !r.fill_buf().unwrap().is_empty() {
+        let n = r.buffer().len();
+        out.extend_from_slice(r.buffer());
+        r.consume(n);
+    }
+    assert_eq!(out, data);
+    assert!(r.get_ref().is_verified());
+}
+
+#[cfg(feature = "blake2b")]
+mod blake2b {
+    use super::*;
+
+    fn hex(bytes: &[u8]) -> alloc::string::String {
+        use core::fmt::Write;
+        let mut s = alloc::string::String::new();
+        for b in bytes {
+            write!(s, "{b:02x}").unwrap();
+        }
+        s
+    }
+
+    #[test]
+    fn known_vectors() {
+        assert_eq!(
+            hex(&blake2b_256(b"")),
+            "44f4c69744d5f8c55d642062949dcae49bc4e7ef43d388c5a12f42b5633d163e"
+        );
+        assert_eq!(
+            hex(&blake2b_256(b"abc")),
+            "521c604cc09b814b0a9106305395def35d0211b9996a3e0f326ae4d671bd8fc2"
+        );
+        assert_eq!(
+            hex(&blake2b_256(&[b'a'; 128])),
+            "b4a38cc5e9d1fa8e874e557e0f59eed3e0a3d9d2f2c2722982067419097a190c"
+        );
+        assert_eq!(
+            hex(&blake2b_256(&[b'a'; 129])),
+            "38ae68da9521ffebff94948935fc595b4d2fc54b1dfcb3151838bf09ead83d87"
+        );
+        let mut h = Blake2b::with_personalization(&[0; 16]);
+        h.update(b"abc");
+        assert_eq!(
+            hex(&h.finalize()),
+            "bddd813c634239723171ef3fee98579b94964e3bb1cb3e427262c8c068d52319"
+        );
+    }
+
+    #[test]
+    fn incremental_matches_one_shot() {
+        let data: Vec<u8> = (0..1024u32).map(|i| i as u8).collect();
+        let expected = "4e90e88b8dd1826b0c8cb354a39f799029d822080d18ad17772d35048a85102a";
+        assert_eq!(hex(&blake2b_256(&data)), expected);
+        for chunk in [1, 7, 64, 127, 128, 129, 300] {
+            let mut h = Blake2b::default();
+            for part in data.chunks(chunk) {
+                h.update(part);
+            }
+            assert_eq!(hex(&h.finalize()), expected, "chunk size {chunk}");
+        }
+    }
+
+    #[test]
+    fn writer_and_reader() {
+        let data: Vec<u8> = (0..1024u32).map(|i| i as u8).collect();
+        let mut w = HashingWriter::<_, Blake2b>::new(io::sink());
+        copy(&mut &data[..], &mut w).unwrap();
+        let (_, hash) = w.finalize();
+        assert_eq!(hash, blake2b_256(&data));
+
+        let mut r = VerifyingReader::<_, Blake2b>::new(&data[..], hash);
+        copy(&mut r, &mut io::sink()).unwrap();
+        assert!(r.is_verified());
+
+        let mut bad = hash;
+        bad[0] ^= 1;
+        let mut r = VerifyingReader::<_, Blake2b>::new(&data[..], bad);
+        let err = copy(&mut r, &mut io::sink()).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+    }
+}
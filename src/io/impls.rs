@@ -1,12 +1,9 @@
-use crate::io::{self, BorrowedCursor, BufRead, Read, Seek, SeekFrom, Write};
-use alloc::boxed::Box;
-use alloc::collections::VecDeque;
-use alloc::fmt;
-use alloc::str;
-use alloc::string::String;
-use alloc::vec::Vec;
-use core::cmp;
-use core::mem;
+use crate::io::{self, BorrowedCursor, BufRead, IoSlice, IoSliceMut, Read, Seek, SeekFrom, Write};
+#[cfg(feature = "alloc")]
+use alloc::{boxed::Box, collections::VecDeque, string::String, vec::Vec};
+#[cfg(feature = "alloc")]
+use core::str;
+use core::{cmp, fmt, mem};
 impl<R: Read + ?Sized> Read for &mut R {
     #[inline]
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
@@ -17,12 +14,24 @@ impl<R: Read + ?Sized> Read for &mut R {
     fn read_buf(&mut self, cursor: BorrowedCursor<'_>) -> io::Result<()> {
         (**self).read_buf(cursor)
     }
+
     #[inline]
+    fn read_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
+        (**self).read_vectored(bufs)
+    }
+
+    #[inline]
+    fn is_read_vectored(&self) -> bool {
+        (**self).is_read_vectored()
+    }
+    #[inline]
+    #[cfg(feature = "alloc")]
     fn read_to_end(&mut self, buf: &mut Vec<u8>) -> io::Result<usize> {
         (**self).read_to_end(buf)
     }
 
     #[inline]
+    #[cfg(feature = "alloc")]
     fn read_to_string(&mut self, buf: &mut String) -> io::Result<usize> {
         (**self).read_to_string(buf)
     }
@@ -41,6 +50,17 @@ impl<W: Write + ?Sized> Write for &mut W {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
         (**self).write(buf)
     }
+
+    #[inline]
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        (**self).write_vectored(bufs)
+    }
+
+    #[inline]
+    fn is_write_vectored(&self) -> bool {
+        (**self).is_write_vectored()
+    }
+
     #[inline]
     fn flush(&mut self) -> io::Result<()> {
         (**self).flush()
@@ -72,21 +92,29 @@ impl<B: BufRead + ?Sized> BufRead for &mut B {
         (**self).fill_buf()
     }
 
+    #[inline]
+    fn fill_at_least(&mut self, n: usize) -> io::Result<&[u8]> {
+        (**self).fill_at_least(n)
+    }
+
     #[inline]
     fn consume(&mut self, amt: usize) {
         (**self).consume(amt)
     }
 
     #[inline]
+    #[cfg(feature = "alloc")]
     fn read_until(&mut self, byte: u8, buf: &mut Vec<u8>) -> io::Result<usize> {
         (**self).read_until(byte, buf)
     }
 
     #[inline]
+    #[cfg(feature = "alloc")]
     fn read_line(&mut self, buf: &mut String) -> io::Result<usize> {
         (**self).read_line(buf)
     }
 }
+#[cfg(feature = "alloc")]
 impl<R: Read + ?Sized> Read for Box<R> {
     #[inline]
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
@@ -98,6 +126,16 @@ impl<R: Read + ?Sized> Read for Box<R> {
         (**self).read_buf(cursor)
     }
 
+    #[inline]
+    fn read_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
+        (**self).read_vectored(bufs)
+    }
+
+    #[inline]
+    fn is_read_vectored(&self) -> bool {
+        (**self).is_read_vectored()
+    }
+
     #[inline]
     fn read_to_end(&mut self, buf: &mut Vec<u8>) -> io::Result<usize> {
         (**self).read_to_end(buf)
@@ -117,11 +155,23 @@ impl<R: Read + ?Sized> Read for Box<R> {
         (**self).read_buf_exact(cursor)
     }
 }
+#[cfg(feature = "alloc")]
 impl<W: Write + ?Sized> Write for Box<W> {
     #[inline]
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
         (**self).write(buf)
     }
+
+    #[inline]
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        (**self).write_vectored(bufs)
+    }
+
+    #[inline]
+    fn is_write_vectored(&self) -> bool {
+        (**self).is_write_vectored()
+    }
+
     #[inline]
     fn flush(&mut self) -> io::Result<()> {
         (**self).flush()
@@ -136,6 +186,7 @@ impl<W: Write + ?Sized> Write for Box<W> {
         (**self).write_fmt(fmt)
     }
 }
+#[cfg(feature = "alloc")]
 impl<S: Seek + ?Sized> Seek for Box<S> {
     #[inline]
     fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
@@ -147,12 +198,18 @@ impl<S: Seek + ?Sized> Seek for Box<S> {
         (**self).stream_position()
     }
 }
+#[cfg(feature = "alloc")]
 impl<B: BufRead + ?Sized> BufRead for Box<B> {
     #[inline]
     fn fill_buf(&mut self) -> io::Result<&[u8]> {
         (**self).fill_buf()
     }
 
+    #[inline]
+    fn fill_at_least(&mut self, n: usize) -> io::Result<&[u8]> {
+        (**self).fill_at_least(n)
+    }
+
     #[inline]
     fn consume(&mut self, amt: usize) {
         (**self).consume(amt)
@@ -205,13 +262,31 @@ impl Read for &[u8] {
         *self = b;
         Ok(())
     }
+
+    #[inline]
+    fn read_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
+        let mut nread = 0;
+        for buf in bufs {
+            nread += self.read(buf)?;
+            if self.is_empty() {
+                break;
+            }
+        }
+
+        Ok(nread)
+    }
+
+    #[inline]
+    fn is_read_vectored(&self) -> bool {
+        true
+    }
     #[inline]
     fn read_exact(&mut self, buf: &mut [u8]) -> io::Result<()> {
         if buf.len() > self.len() {
             // `read_exact` makes no promise about the content of `buf` if it
             // fails so don't bother about that.
             *self = &self[self.len()..];
-            return Err(io::Error::READ_EXACT_EOF);
+            return Err(io::Error::READ_EXACT_EOF.at_caller());
         }
         let (a, b) = self.split_at(buf.len());
 
@@ -234,7 +309,7 @@ impl Read for &[u8] {
             // Append everything we can to the cursor.
             cursor.append(self);
             *self = &self[self.len()..];
-            return Err(io::Error::READ_EXACT_EOF);
+            return Err(io::Error::READ_EXACT_EOF.at_caller());
         }
         let (a, b) = self.split_at(cursor.capacity());
 
@@ -245,6 +320,7 @@ impl Read for &[u8] {
     }
 
     #[inline]
+    #[cfg(feature = "alloc")]
     fn read_to_end(&mut self, buf: &mut Vec<u8>) -> io::Result<usize> {
         let len = self.len();
         buf.try_reserve(len)?;
@@ -254,6 +330,7 @@ impl Read for &[u8] {
     }
 
     #[inline]
+    #[cfg(feature = "alloc")]
     fn read_to_string(&mut self, buf: &mut String) -> io::Result<usize> {
         let content = str::from_utf8(self).map_err(|_| io::Error::INVALID_UTF8)?;
         let len = self.len();
@@ -293,6 +370,20 @@ impl Write for &mut [u8] {
         *self = b;
         Ok(amt)
     }
+
+    #[inline]
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        let mut nwritten = 0;
+        for buf in bufs {
+            nwritten += self.write(buf)?;
+            if self.is_empty() {
+                break;
+            }
+        }
+
+        Ok(nwritten)
+    }
+
     #[inline]
     fn is_write_vectored(&self) -> bool {
         true
@@ -302,7 +393,7 @@ impl Write for &mut [u8] {
         if self.write(data)? == data.len() {
             Ok(())
         } else {
-            Err(crate::io::Error::WRITE_ALL_EOF)
+            Err(crate::io::Error::WRITE_ALL_EOF.at_caller())
         }
     }
     #[inline]
@@ -313,12 +404,24 @@ impl Write for &mut [u8] {
 
 /// Write is implemented for `Vec<u8>` by appending to the vector.
 /// The vector will grow as needed.
+#[cfg(feature = "alloc")]
 impl Write for Vec<u8> {
     #[inline]
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
         self.extend_from_slice(buf);
         Ok(buf.len())
     }
+
+    #[inline]
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        let len = bufs.iter().map(|b| b.len()).sum();
+        self.reserve(len);
+        for buf in bufs {
+            self.extend_from_slice(buf);
+        }
+        Ok(len)
+    }
+
     #[inline]
     fn is_write_vectored(&self) -> bool {
         true
@@ -336,6 +439,7 @@ impl Write for Vec<u8> {
     }
 }
 /// Read is implemented for `VecDeque<u8>` by consuming bytes from the front of the `VecDeque`.
+#[cfg(feature = "alloc")]
 impl Read for VecDeque<u8> {
     /// Fill `buf` with the contents of the "front" slice as returned by
     /// [`as_slices`][`VecDeque::as_slices`]. If the contained byte slices of the `VecDeque` are
@@ -357,6 +461,30 @@ impl Read for VecDeque<u8> {
         Ok(())
     }
 
+    /// Fill `bufs` with the "front" slice followed by the "back" slice as returned by
+    /// [`as_slices`][`VecDeque::as_slices`], so a discontiguous `VecDeque` can be drained
+    /// in a single call.
+    #[inline]
+    fn read_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
+        let (mut front, mut back) = self.as_slices();
+        let mut nread = 0;
+        for buf in bufs {
+            let n = Read::read(&mut front, buf)?;
+            let n = n + Read::read(&mut back, &mut buf[n..])?;
+            nread += n;
+            if front.is_empty() && back.is_empty() {
+                break;
+            }
+        }
+        self.drain(..nread);
+        Ok(nread)
+    }
+
+    #[inline]
+    fn is_read_vectored(&self) -> bool {
+        true
+    }
+
     #[inline]
     fn read_to_end(&mut self, buf: &mut Vec<u8>) -> io::Result<usize> {
         // The total len is known upfront so we can reserve it in a single call.
@@ -377,6 +505,7 @@ impl Read for VecDeque<u8> {
     }
 }
 /// BufRead is implemented for `VecDeque<u8>` by reading bytes from the front of the `VecDeque`.
+#[cfg(feature = "alloc")]
 impl BufRead for VecDeque<u8> {
     /// Returns the contents of the "front" slice as returned by
     /// [`as_slices`][`VecDeque::as_slices`]. If the contained byte slices of the `VecDeque` are
@@ -393,12 +522,24 @@ impl BufRead for VecDeque<u8> {
 }
 
 /// Write is implemented for `VecDeque<u8>` by appending to the `VecDeque`, growing it as needed.
+#[cfg(feature = "alloc")]
 impl Write for VecDeque<u8> {
     #[inline]
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
         self.extend(buf);
         Ok(buf.len())
     }
+
+    #[inline]
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        let len = bufs.iter().map(|b| b.len()).sum();
+        self.reserve(len);
+        for buf in bufs {
+            self.extend(&**buf);
+        }
+        Ok(len)
+    }
+
     #[inline]
     fn is_write_vectored(&self) -> bool {
         true
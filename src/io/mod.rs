@@ -296,37 +296,128 @@ mod cherry_picking;
 #[cfg(test)]
 mod tests;
 
-pub use self::buffered::WriterPanicked;
+#[cfg(feature = "alloc")]
+pub use self::buffered::{BufOptions, BufReader, BufWriter, LineWriter, WriterPanicked};
+#[cfg(all(feature = "std", not(feature = "rust_before_181")))]
+pub use self::compat::{FromStd, IntoStd};
+#[cfg(feature = "error_context")]
+pub use self::error::{ErrorExt, ResultExt};
+#[cfg(feature = "alloc")]
+pub use self::stdio::{stderr, stdout, Stderr, Stdout};
 pub use self::{
-    buffered::{BufReader, BufWriter, IntoInnerError, LineWriter},
-    copy::copy,
+    buffered::{
+        ArrayBufReader, ArrayBufWriter, GenericBufReader, GenericBufWriter, IntoInnerError,
+    },
+    copy::{copy, copy_buf, copy_with_buffer, WriteFrom},
     cursor::Cursor,
-    error::{Error, ErrorKind, Result},
+    error::{Error, ErrorKind, Result, WireError},
+    positional::{ReadAt, ReadAtCursor, WriteAt},
     util::{empty, repeat, sink, Empty, Repeat, Sink},
 };
 pub use crate::io::cherry_picking::borrowed_buf::{BorrowedBuf, BorrowedCursor};
+#[cfg(feature = "alloc")]
 use alloc::boxed::Box;
-use alloc::fmt;
-use alloc::str;
+#[cfg(feature = "alloc")]
 use alloc::string::String;
+#[cfg(feature = "alloc")]
 use alloc::vec::Vec;
 use cherry_picking::memchr;
-use core::{cmp, slice};
+use core::ops::{Deref, DerefMut};
+use core::{cmp, fmt, mem, slice, str};
 mod buffered;
+pub mod byteorder;
+pub mod ckb;
+// The `std` feature needs Rust 1.87. Leaving it out with `rust_before_181`
+// keeps the `compile_error!` in `lib.rs` the only error.
+#[cfg(all(feature = "std", not(feature = "rust_before_181")))]
+#[clippy::msrv = "1.87"]
+mod compat;
 pub(crate) mod copy;
 mod cursor;
 pub mod error;
+#[cfg(feature = "hash")]
+pub mod hash;
 mod impls;
+pub mod molecule;
+mod positional;
 pub mod prelude;
+pub mod ring;
+#[cfg(feature = "alloc")]
+pub mod stdio;
 mod util;
+pub mod varint;
 pub(crate) use crate::const_io_error;
 
-const DEFAULT_BUF_SIZE: usize = 1024;
+/// The capacity of [`BufReader::new`] and [`BufWriter::new`], 1 KiB unless
+/// overridden with the `CKB_RUST_STD_BUF_SIZE` environment variable at build
+/// time.
+const DEFAULT_BUF_SIZE: usize = buf_size_from_env(option_env!("CKB_RUST_STD_BUF_SIZE"), 1024);
+
+/// The size of the stack buffer used by [`copy`], [`DEFAULT_BUF_SIZE`] unless
+/// overridden with the `CKB_RUST_STD_COPY_BUF_SIZE` environment variable at
+/// build time.
+const COPY_BUF_SIZE: usize =
+    buf_size_from_env(option_env!("CKB_RUST_STD_COPY_BUF_SIZE"), DEFAULT_BUF_SIZE);
+
+/// Parses a buffer size override, failing the build if it is not a positive
+/// decimal integer.
+const fn buf_size_from_env(var: Option<&str>, default: usize) -> usize {
+    let bytes = match var {
+        Some(var) => var.as_bytes(),
+        None => return default,
+    };
+    let mut size: usize = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        let digit = bytes[i];
+        if !digit.is_ascii_digit() {
+            panic!("buffer size overrides must be decimal integers");
+        }
+        size = match size.checked_mul(10) {
+            Some(n) => match n.checked_add((digit - b'0') as usize) {
+                Some(n) => n,
+                None => panic!("buffer size override is too large"),
+            },
+            None => panic!("buffer size override is too large"),
+        };
+        i += 1;
+    }
+    if size == 0 {
+        panic!("buffer size overrides must be positive");
+    }
+    size
+}
+
+const INVALID_SEEK: Error = const_io_error!(
+    ErrorKind::InvalidInput,
+    "invalid seek to a negative or overflowing position",
+);
+
+/// Resolves `style` to an absolute position for a seekable type whose
+/// position is `pos`. The length of the stream is only queried for
+/// [`SeekFrom::End`].
+pub(crate) fn seek_position<F>(style: SeekFrom, pos: u64, len: F) -> Result<u64>
+where
+    F: FnOnce() -> Result<u64>,
+{
+    match style {
+        SeekFrom::Start(n) => Some(n),
+        SeekFrom::End(n) => len()?.checked_add_signed(n),
+        SeekFrom::Current(n) => pos.checked_add_signed(n),
+    }
+    .ok_or(INVALID_SEEK)
+}
 
+#[cfg(feature = "alloc")]
+const READ_LIMIT_EXCEEDED: Error =
+    const_io_error!(ErrorKind::FileTooLarge, "data exceeds the read limit");
+
+#[cfg(feature = "alloc")]
 struct Guard<'a> {
     buf: &'a mut Vec<u8>,
     len: usize,
 }
+#[cfg(feature = "alloc")]
 impl Drop for Guard<'_> {
     fn drop(&mut self) {
         unsafe {
@@ -354,6 +445,7 @@ impl Drop for Guard<'_> {
 // 2. We're passing a raw buffer to the function `f`, and it is expected that
 //    the function only *appends* bytes to the buffer. We'll get undefined
 //    behavior if existing bytes are overwritten to have non-UTF-8 data.
+#[cfg(feature = "alloc")]
 pub(crate) unsafe fn append_to_string<F>(buf: &mut String, f: F) -> Result<usize>
 where
     F: FnOnce(&mut Vec<u8>) -> Result<usize>,
@@ -384,6 +476,7 @@ where
 //   at the same time, i.e. small reads suffer from syscall overhead, all reads incur initialization cost
 //   proportional to buffer size (#110650)
 //
+#[cfg(feature = "alloc")]
 pub(crate) fn default_read_to_end<R: Read + ?Sized>(
     r: &mut R,
     buf: &mut Vec<u8>,
@@ -501,6 +594,7 @@ pub(crate) fn default_read_to_end<R: Read + ?Sized>(
     }
 }
 
+#[cfg(feature = "alloc")]
 pub(crate) fn default_read_to_string<R: Read + ?Sized>(
     r: &mut R,
     buf: &mut String,
@@ -518,6 +612,29 @@ pub(crate) fn default_read_to_string<R: Read + ?Sized>(
     unsafe { append_to_string(buf, |b| default_read_to_end(r, b, size_hint)) }
 }
 
+pub(crate) fn default_read_vectored<F>(read: F, bufs: &mut [IoSliceMut<'_>]) -> Result<usize>
+where
+    F: FnOnce(&mut [u8]) -> Result<usize>,
+{
+    let buf = bufs
+        .iter_mut()
+        .find(|b| !b.is_empty())
+        .map_or(&mut [][..], |b| &mut **b);
+    read(buf)
+}
+
+pub(crate) fn default_write_vectored<F>(write: F, bufs: &[IoSlice<'_>]) -> Result<usize>
+where
+    F: FnOnce(&[u8]) -> Result<usize>,
+{
+    let buf = bufs
+        .iter()
+        .find(|b| !b.is_empty())
+        .map_or(&[][..], |b| &**b);
+    write(buf)
+}
+
+#[cfg_attr(feature = "error_location", track_caller)]
 pub(crate) fn default_read_exact<R: Read + ?Sized>(this: &mut R, mut buf: &mut [u8]) -> Result<()> {
     while !buf.is_empty() {
         match this.read(buf) {
@@ -530,7 +647,7 @@ pub(crate) fn default_read_exact<R: Read + ?Sized>(this: &mut R, mut buf: &mut [
         }
     }
     if !buf.is_empty() {
-        Err(Error::READ_EXACT_EOF)
+        Err(Error::READ_EXACT_EOF.at_caller())
     } else {
         Ok(())
     }
@@ -545,6 +662,7 @@ where
     Ok(())
 }
 
+#[cfg_attr(feature = "error_location", track_caller)]
 pub(crate) fn default_read_buf_exact<R: Read + ?Sized>(
     this: &mut R,
     mut cursor: BorrowedCursor<'_>,
@@ -558,7 +676,7 @@ pub(crate) fn default_read_buf_exact<R: Read + ?Sized>(
         }
 
         if cursor.written() == prev_written {
-            return Err(Error::READ_EXACT_EOF);
+            return Err(Error::READ_EXACT_EOF.at_caller());
         }
     }
 
@@ -720,6 +838,31 @@ pub trait Read {
     /// ```
     fn read(&mut self, buf: &mut [u8]) -> Result<usize>;
 
+    /// Like `read`, except that it reads into a slice of buffers.
+    ///
+    /// Data is copied to fill each buffer in order, with the final buffer
+    /// written to possibly being only partially filled. This method must
+    /// behave equivalently to a single call to `read` with concatenated
+    /// buffers.
+    ///
+    /// The default implementation calls `read` with either the first nonempty
+    /// buffer provided, or an empty one if none exists.
+    fn read_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> Result<usize> {
+        default_read_vectored(|b| self.read(b), bufs)
+    }
+
+    /// Determines if this `Read`er has an efficient `read_vectored`
+    /// implementation.
+    ///
+    /// If a `Read`er does not override the default `read_vectored`
+    /// implementation, code using it may want to avoid the method all together
+    /// and coalesce writes into a single buffer for higher performance.
+    ///
+    /// The default implementation returns `false`.
+    fn is_read_vectored(&self) -> bool {
+        false
+    }
+
     /// Read all bytes until EOF in this source, placing them into `buf`.
     ///
     /// All bytes read from this source will be appended to the specified buffer
@@ -799,6 +942,7 @@ pub trait Read {
     /// ```
     ///
     /// [`Vec::try_reserve`]: crate::vec::Vec::try_reserve
+    #[cfg(feature = "alloc")]
     fn read_to_end(&mut self, buf: &mut Vec<u8>) -> Result<usize> {
         default_read_to_end(self, buf, None)
     }
@@ -841,10 +985,64 @@ pub trait Read {
     /// reading from a file.)
     ///
     /// [`std::fs::read_to_string`]: crate::fs::read_to_string
+    #[cfg(feature = "alloc")]
     fn read_to_string(&mut self, buf: &mut String) -> Result<usize> {
         default_read_to_string(self, buf, None)
     }
 
+    /// Reads all bytes until EOF, like [`read_to_end`], but fails instead of
+    /// appending more than `limit` bytes to `buf`.
+    ///
+    /// Use this instead of [`read_to_end`] on untrusted input, such as
+    /// witnesses, so that oversized data is rejected before it exhausts the
+    /// VM's memory.
+    ///
+    /// # Errors
+    ///
+    /// If the source holds more than `limit` bytes, an error of the kind
+    /// [`ErrorKind::FileTooLarge`] is returned. `buf` is then left as it
+    /// was before the call, and exactly `limit + 1` bytes have been consumed
+    /// from the source. Other errors leave the data read so far in `buf`,
+    /// as with [`read_to_end`].
+    ///
+    /// [`read_to_end`]: Read::read_to_end
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use ckb_rust_std::io::{self, ckb::{self, Source}, Read};
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let mut witness = ckb::witness(0, Source::GroupInput)?;
+    ///     let mut buf = Vec::new();
+    ///     witness.read_to_end_limited(&mut buf, 32 * 1024)?;
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg(feature = "alloc")]
+    fn read_to_end_limited(&mut self, buf: &mut Vec<u8>, limit: usize) -> Result<usize> {
+        read_to_end_limited(self, buf, limit)
+    }
+
+    /// Reads all bytes until EOF into `buf`, like [`read_to_string`], but
+    /// fails instead of appending more than `limit` bytes.
+    ///
+    /// # Errors
+    ///
+    /// As with [`read_to_end_limited`], exceeding `limit` fails with
+    /// [`ErrorKind::FileTooLarge`] and leaves `buf` unchanged. Invalid UTF-8
+    /// is reported as with [`read_to_string`], also leaving `buf`
+    /// unchanged.
+    ///
+    /// [`read_to_string`]: Read::read_to_string
+    /// [`read_to_end_limited`]: Read::read_to_end_limited
+    #[cfg(feature = "alloc")]
+    fn read_to_string_limited(&mut self, buf: &mut String, limit: usize) -> Result<usize> {
+        // Like `default_read_to_string`, use the hardcoded implementation,
+        // which is known to only append to `buf`.
+        unsafe { append_to_string(buf, |b| read_to_end_limited(self, b, limit)) }
+    }
+
     /// Read the exact number of bytes required to fill `buf`.
     ///
     /// This function reads as many bytes as necessary to completely fill the
@@ -893,6 +1091,7 @@ pub trait Read {
     ///     Ok(())
     /// }
     /// ```
+    #[cfg_attr(feature = "error_location", track_caller)]
     fn read_exact(&mut self, buf: &mut [u8]) -> Result<()> {
         default_read_exact(self, buf)
     }
@@ -925,6 +1124,7 @@ pub trait Read {
     /// returns.
     ///
     /// If this function returns an error, all bytes read will be appended to `cursor`.
+    #[cfg_attr(feature = "error_location", track_caller)]
     fn read_buf_exact(&mut self, cursor: BorrowedCursor<'_>) -> Result<()> {
         default_read_buf_exact(self, cursor)
     }
@@ -1088,7 +1288,11 @@ pub trait Read {
     where
         Self: Sized,
     {
-        Take { inner: self, limit }
+        Take {
+            inner: self,
+            len: limit,
+            limit,
+        }
     }
 }
 
@@ -1136,12 +1340,259 @@ pub trait Read {
 ///     Ok(())
 /// }
 /// ```
+#[cfg(feature = "alloc")]
 pub fn read_to_string<R: Read>(mut reader: R) -> Result<String> {
     let mut buf = String::new();
     reader.read_to_string(&mut buf)?;
     Ok(buf)
 }
 
+/// A buffer type used with `Read::read_vectored`.
+///
+/// It is semantically a wrapper around a `&mut [u8]`, but is guaranteed to be
+/// ABI compatible with the `iovec` type on Unix platforms and `WSABUF` on
+/// Windows. In this `no_std` port it is a plain wrapper around the slice.
+#[repr(transparent)]
+pub struct IoSliceMut<'a>(&'a mut [u8]);
+
+impl fmt::Debug for IoSliceMut<'_> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&*self.0, fmt)
+    }
+}
+
+impl<'a> IoSliceMut<'a> {
+    /// Creates a new `IoSliceMut` wrapping a byte slice.
+    #[inline]
+    pub fn new(buf: &'a mut [u8]) -> IoSliceMut<'a> {
+        IoSliceMut(buf)
+    }
+
+    /// Advance the internal cursor of the slice.
+    ///
+    /// Also see [`IoSliceMut::advance_slices`] to advance the cursors of
+    /// multiple buffers.
+    ///
+    /// # Panics
+    ///
+    /// Panics when trying to advance beyond the end of the slice.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::IoSliceMut;
+    /// use std::ops::Deref;
+    ///
+    /// let mut data = [1; 8];
+    /// let mut buf = IoSliceMut::new(&mut data);
+    ///
+    /// // Mark 3 bytes as read.
+    /// buf.advance(3);
+    /// assert_eq!(buf.deref(), [1; 5].as_ref());
+    /// ```
+    #[inline]
+    pub fn advance(&mut self, n: usize) {
+        if self.0.len() < n {
+            panic!("advancing IoSliceMut beyond its length");
+        }
+        self.0 = &mut mem::take(&mut self.0)[n..];
+    }
+
+    /// Advance a slice of slices.
+    ///
+    /// Shrinks the slice to remove any `IoSliceMut`s that are fully advanced over.
+    /// If the cursor ends up in the middle of an `IoSliceMut`, it is modified
+    /// to start at that cursor.
+    ///
+    /// For example, if we have a slice of two 8-byte `IoSliceMut`s, and we advance by 10 bytes,
+    /// the result will only include the second `IoSliceMut`, advanced by 2 bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics when trying to advance beyond the end of the slices.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::IoSliceMut;
+    /// use std::ops::Deref;
+    ///
+    /// let mut buf1 = [1; 8];
+    /// let mut buf2 = [2; 16];
+    /// let mut buf3 = [3; 8];
+    /// let mut bufs = &mut [
+    ///     IoSliceMut::new(&mut buf1),
+    ///     IoSliceMut::new(&mut buf2),
+    ///     IoSliceMut::new(&mut buf3),
+    /// ][..];
+    ///
+    /// // Mark 10 bytes as read.
+    /// IoSliceMut::advance_slices(&mut bufs, 10);
+    /// assert_eq!(bufs[0].deref(), [2; 14].as_ref());
+    /// assert_eq!(bufs[1].deref(), [3; 8].as_ref());
+    /// ```
+    #[inline]
+    pub fn advance_slices(bufs: &mut &mut [IoSliceMut<'a>], n: usize) {
+        // Number of buffers to remove.
+        let mut remove = 0;
+        // Remaining length before reaching n.
+        let mut left = n;
+        for buf in bufs.iter() {
+            if let Some(remainder) = left.checked_sub(buf.len()) {
+                left = remainder;
+                remove += 1;
+            } else {
+                break;
+            }
+        }
+
+        *bufs = &mut mem::take(bufs)[remove..];
+        if bufs.is_empty() {
+            assert!(left == 0, "advancing io slices beyond their length");
+        } else {
+            bufs[0].advance(left);
+        }
+    }
+}
+
+impl<'a> Deref for IoSliceMut<'a> {
+    type Target = [u8];
+
+    #[inline]
+    fn deref(&self) -> &[u8] {
+        self.0
+    }
+}
+
+impl<'a> DerefMut for IoSliceMut<'a> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut [u8] {
+        self.0
+    }
+}
+
+/// A buffer type used with `Write::write_vectored`.
+///
+/// It is semantically a wrapper around a `&[u8]`, but is guaranteed to be
+/// ABI compatible with the `iovec` type on Unix platforms and `WSABUF` on
+/// Windows. In this `no_std` port it is a plain wrapper around the slice.
+#[derive(Copy, Clone)]
+#[repr(transparent)]
+pub struct IoSlice<'a>(&'a [u8]);
+
+impl fmt::Debug for IoSlice<'_> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.0, fmt)
+    }
+}
+
+impl<'a> IoSlice<'a> {
+    /// Creates a new `IoSlice` wrapping a byte slice.
+    #[must_use]
+    #[inline]
+    pub fn new(buf: &'a [u8]) -> IoSlice<'a> {
+        IoSlice(buf)
+    }
+
+    /// Advance the internal cursor of the slice.
+    ///
+    /// Also see [`IoSlice::advance_slices`] to advance the cursors of multiple
+    /// buffers.
+    ///
+    /// # Panics
+    ///
+    /// Panics when trying to advance beyond the end of the slice.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::IoSlice;
+    /// use std::ops::Deref;
+    ///
+    /// let data = [1; 8];
+    /// let mut buf = IoSlice::new(&data);
+    ///
+    /// // Mark 3 bytes as read.
+    /// buf.advance(3);
+    /// assert_eq!(buf.deref(), [1; 5].as_ref());
+    /// ```
+    #[inline]
+    pub fn advance(&mut self, n: usize) {
+        if self.0.len() < n {
+            panic!("advancing IoSlice beyond its length");
+        }
+        self.0 = &self.0[n..];
+    }
+
+    /// Advance a slice of slices.
+    ///
+    /// Shrinks the slice to remove any `IoSlice`s that are fully advanced over.
+    /// If the cursor ends up in the middle of an `IoSlice`, it is modified
+    /// to start at that cursor.
+    ///
+    /// For example, if we have a slice of two 8-byte `IoSlice`s, and we advance by 10 bytes,
+    /// the result will only include the second `IoSlice`, advanced by 2 bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics when trying to advance beyond the end of the slices.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::IoSlice;
+    /// use std::ops::Deref;
+    ///
+    /// let buf1 = [1; 8];
+    /// let buf2 = [2; 16];
+    /// let buf3 = [3; 8];
+    /// let mut bufs = &mut [
+    ///     IoSlice::new(&buf1),
+    ///     IoSlice::new(&buf2),
+    ///     IoSlice::new(&buf3),
+    /// ][..];
+    ///
+    /// // Mark 10 bytes as written.
+    /// IoSlice::advance_slices(&mut bufs, 10);
+    /// assert_eq!(bufs[0].deref(), [2; 14].as_ref());
+    /// assert_eq!(bufs[1].deref(), [3; 8].as_ref());
+    /// ```
+    #[inline]
+    pub fn advance_slices(bufs: &mut &mut [IoSlice<'a>], n: usize) {
+        // Number of buffers to remove.
+        let mut remove = 0;
+        // Remaining length before reaching n. This prevents overflow
+        // that could happen if the length of slices in `bufs` were instead
+        // accumulated. Those slice may be aliased and, if they are large
+        // enough, their added length may overflow a `usize`.
+        let mut left = n;
+        for buf in bufs.iter() {
+            if let Some(remainder) = left.checked_sub(buf.len()) {
+                left = remainder;
+                remove += 1;
+            } else {
+                break;
+            }
+        }
+
+        *bufs = &mut mem::take(bufs)[remove..];
+        if bufs.is_empty() {
+            assert!(left == 0, "advancing io slices beyond their length");
+        } else {
+            bufs[0].advance(left);
+        }
+    }
+}
+
+impl<'a> Deref for IoSlice<'a> {
+    type Target = [u8];
+
+    #[inline]
+    fn deref(&self) -> &[u8] {
+        self.0
+    }
+}
+
 /// A trait for objects which are byte-oriented sinks.
 ///
 /// Implementors of the `Write` trait are sometimes called 'writers'.
@@ -1234,6 +1685,52 @@ pub trait Write {
     ///
     /// [`Ok(n)`]: Ok
     fn write(&mut self, buf: &[u8]) -> Result<usize>;
+
+    /// Like [`write`], except that it writes from a slice of buffers.
+    ///
+    /// Data is copied from each buffer in order, with the final buffer
+    /// read from possibly being only partially consumed. This method must
+    /// behave as a call to [`write`] with the buffers concatenated would.
+    ///
+    /// The default implementation calls [`write`] with either the first nonempty
+    /// buffer provided, or an empty one if none exists.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::io::IoSlice;
+    /// use std::io::prelude::*;
+    /// use std::fs::File;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     let data1 = [1; 8];
+    ///     let data2 = [15; 8];
+    ///     let io_slice1 = IoSlice::new(&data1);
+    ///     let io_slice2 = IoSlice::new(&data2);
+    ///
+    ///     let mut buffer = File::create("foo.txt")?;
+    ///
+    ///     // Writes some prefix of the byte string, not necessarily all of it.
+    ///     buffer.write_vectored(&[io_slice1, io_slice2])?;
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// [`write`]: Write::write
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> Result<usize> {
+        default_write_vectored(|b| self.write(b), bufs)
+    }
+
+    /// Determines if this `Write`r has an efficient [`write_vectored`]
+    /// implementation.
+    ///
+    /// If a `Write`r does not override the default [`write_vectored`]
+    /// implementation, code using it may want to avoid the method all together
+    /// and coalesce writes into a single buffer for higher performance.
+    ///
+    /// The default implementation returns `false`.
+    ///
+    /// [`write_vectored`]: Write::write_vectored
     fn is_write_vectored(&self) -> bool {
         false
     }
@@ -1294,11 +1791,12 @@ pub trait Write {
     ///     Ok(())
     /// }
     /// ```
+    #[cfg_attr(feature = "error_location", track_caller)]
     fn write_all(&mut self, mut buf: &[u8]) -> Result<()> {
         while !buf.is_empty() {
             match self.write(buf) {
                 Ok(0) => {
-                    return Err(Error::WRITE_ALL_EOF);
+                    return Err(Error::WRITE_ALL_EOF.at_caller());
                 }
                 Ok(n) => buf = &buf[n..],
                 Err(ref e) if e.is_interrupted() => {}
@@ -1308,6 +1806,68 @@ pub trait Write {
         Ok(())
     }
 
+    /// Attempts to write multiple buffers into this writer.
+    ///
+    /// This method will continuously call [`write_vectored`] until there is no
+    /// more data to be written or an error of non-[`ErrorKind::Interrupted`]
+    /// kind is returned. This method will not return until all buffers have
+    /// been successfully written or such an error occurs. The first error that
+    /// is not of [`ErrorKind::Interrupted`] kind generated from this method will
+    /// be returned.
+    ///
+    /// If the buffer contains no data, this will never call [`write_vectored`].
+    ///
+    /// # Notes
+    ///
+    /// Unlike [`write_vectored`], this takes a *mutable* reference to
+    /// a slice of [`IoSlice`]s, not an immutable one. That's because we need to
+    /// modify the slice to keep track of the bytes already written.
+    ///
+    /// Once this function returns, the contents of `bufs` are unspecified, as
+    /// this depends on how many calls to [`write_vectored`] were necessary. It is
+    /// best to understand this function as taking ownership of `bufs` and to
+    /// not use `bufs` afterwards. The underlying buffers, to which the
+    /// [`IoSlice`]s point (but not the [`IoSlice`]s themselves), are unchanged and
+    /// can be reused.
+    ///
+    /// [`write_vectored`]: Write::write_vectored
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::{Write, IoSlice};
+    ///
+    /// let mut writer = Vec::new();
+    /// let bufs = &mut [
+    ///     IoSlice::new(&[1]),
+    ///     IoSlice::new(&[2, 3]),
+    ///     IoSlice::new(&[4, 5, 6]),
+    /// ];
+    ///
+    /// writer.write_all_vectored(bufs)?;
+    /// // Note: the contents of `bufs` is now undefined, see the Notes section.
+    ///
+    /// assert_eq!(writer, &[1, 2, 3, 4, 5, 6]);
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[cfg_attr(feature = "error_location", track_caller)]
+    fn write_all_vectored(&mut self, mut bufs: &mut [IoSlice<'_>]) -> Result<()> {
+        // Guarantee that bufs is empty if it contains no data,
+        // to avoid calling write_vectored if there is no data to be written.
+        IoSlice::advance_slices(&mut bufs, 0);
+        while !bufs.is_empty() {
+            match self.write_vectored(bufs) {
+                Ok(0) => {
+                    return Err(Error::WRITE_ALL_EOF.at_caller());
+                }
+                Ok(n) => IoSlice::advance_slices(&mut bufs, n),
+                Err(ref e) if e.is_interrupted() => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
     /// Writes a formatted string into this writer, returning any error
     /// encountered.
     ///
@@ -1615,6 +2175,7 @@ pub enum SeekFrom {
     Current(i64),
 }
 
+#[cfg(feature = "alloc")]
 fn read_until<R: BufRead + ?Sized>(r: &mut R, delim: u8, buf: &mut Vec<u8>) -> Result<usize> {
     let mut read = 0;
     loop {
@@ -1643,6 +2204,70 @@ fn read_until<R: BufRead + ?Sized>(r: &mut R, delim: u8, buf: &mut Vec<u8>) -> R
     }
 }
 
+#[cfg(feature = "alloc")]
+fn read_to_end_limited<R: Read + ?Sized>(
+    r: &mut R,
+    buf: &mut Vec<u8>,
+    limit: usize,
+) -> Result<usize> {
+    let start_len = buf.len();
+    // Read one byte past the limit to tell a source of exactly `limit` bytes
+    // from a larger one.
+    let cap = (limit as u64).saturating_add(1);
+    let mut take = Take {
+        inner: r,
+        len: cap,
+        limit: cap,
+    };
+    let n = take.read_to_end(buf)?;
+    if n > limit {
+        buf.truncate(start_len);
+        return Err(READ_LIMIT_EXCEEDED);
+    }
+    Ok(n)
+}
+
+#[cfg(feature = "alloc")]
+fn read_until_limited<R: BufRead + ?Sized>(
+    r: &mut R,
+    delim: u8,
+    buf: &mut Vec<u8>,
+    limit: usize,
+) -> Result<usize> {
+    let start_len = buf.len();
+    let mut read = 0;
+    loop {
+        let (done, used, exceeded) = {
+            let available = match r.fill_buf() {
+                Ok(n) => n,
+                Err(ref e) if e.is_interrupted() => continue,
+                Err(e) => return Err(e),
+            };
+            let allowance = limit - read;
+            match memchr::memchr(delim, available) {
+                Some(i) if i < allowance => {
+                    buf.extend_from_slice(&available[..=i]);
+                    (true, i + 1, false)
+                }
+                _ if available.len() > allowance => (true, allowance, true),
+                _ => {
+                    buf.extend_from_slice(available);
+                    (false, available.len(), false)
+                }
+            }
+        };
+        r.consume(used);
+        read += used;
+        if exceeded {
+            buf.truncate(start_len);
+            return Err(READ_LIMIT_EXCEEDED);
+        }
+        if done || used == 0 {
+            return Ok(read);
+        }
+    }
+}
+
 fn skip_until<R: BufRead + ?Sized>(r: &mut R, delim: u8) -> Result<usize> {
     let mut read = 0;
     loop {
@@ -1754,6 +2379,49 @@ pub trait BufRead: Read {
     /// ```
     fn fill_buf(&mut self) -> Result<&[u8]>;
 
+    /// Returns the contents of the internal buffer like [`fill_buf`], asking
+    /// for at least `n` bytes.
+    ///
+    /// `n` is only a hint: the result may hold fewer than `n` bytes even
+    /// before EOF, so callers must check its length. Only [`BufReader`] and
+    /// [`ArrayBufReader`] guarantee `n` bytes before EOF, and only when `n`
+    /// fits in their capacity. The returned bytes are not consumed.
+    ///
+    /// * [`BufReader`] and [`ArrayBufReader`] read until `n` bytes, or their
+    ///   capacity, are buffered, moving buffered data to the front of the
+    ///   buffer as needed.
+    /// * In-memory readers such as `&[u8]` and [`Cursor`] always return
+    ///   all remaining data, which is all they have.
+    /// * [`Chain`] passes `n` on to the stream it is reading from, so its
+    ///   buffer never spans the boundary between the two.
+    /// * Any other `BufRead` uses the default implementation, which ignores
+    ///   `n` and returns the result of a single [`fill_buf`], as a generic
+    ///   `BufRead` has no way to ask for more without consuming what is
+    ///   buffered. Wrap such a reader in a [`BufReader`] when `n` bytes are
+    ///   needed.
+    ///
+    /// [`fill_buf`]: BufRead::fill_buf
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use ckb_rust_std::io::{self, BufRead, BufReader};
+    ///
+    /// fn header<R: BufRead>(mut reader: R) -> io::Result<u32> {
+    ///     let buf = reader.fill_at_least(4)?;
+    ///     let Some(header) = buf.get(..4) else {
+    ///         return Err(io::ErrorKind::UnexpectedEof.into());
+    ///     };
+    ///     let n = u32::from_le_bytes(header.try_into().unwrap());
+    ///     reader.consume(4);
+    ///     Ok(n)
+    /// }
+    /// ```
+    fn fill_at_least(&mut self, n: usize) -> Result<&[u8]> {
+        let _ = n;
+        self.fill_buf()
+    }
+
     /// Tells this buffer that `amt` bytes have been consumed from the buffer,
     /// so they should no longer be returned in calls to `read`.
     ///
@@ -1859,6 +2527,7 @@ pub trait BufRead: Read {
     /// assert_eq!(num_bytes, 0);
     /// assert_eq!(buf, b"");
     /// ```
+    #[cfg(feature = "alloc")]
     fn read_until(&mut self, byte: u8, buf: &mut Vec<u8>) -> Result<usize> {
         read_until(self, byte, buf)
     }
@@ -1987,6 +2656,7 @@ pub trait BufRead: Read {
     /// assert_eq!(num_bytes, 0);
     /// assert_eq!(buf, "");
     /// ```
+    #[cfg(feature = "alloc")]
     fn read_line(&mut self, buf: &mut String) -> Result<usize> {
         // Note that we are not calling the `.read_until` method here, but
         // rather our hardcoded implementation. For more details as to why, see
@@ -1994,6 +2664,42 @@ pub trait BufRead: Read {
         unsafe { append_to_string(buf, |b| read_until(self, b'\n', b)) }
     }
 
+    /// Reads bytes into `buf` until the delimiter `byte` or EOF is reached,
+    /// like [`read_until`], but fails instead of appending more than `limit`
+    /// bytes, delimiter included.
+    ///
+    /// # Errors
+    ///
+    /// If no delimiter is found within `limit` bytes, and the data does not
+    /// end there, an error of the kind [`ErrorKind::FileTooLarge`] is
+    /// returned. `buf` is then left as it was before the call, and exactly
+    /// `limit` bytes have been consumed, so [`skip_until`] can be used to
+    /// move on to the next delimiter. Other errors leave the data read so
+    /// far in `buf`, as with [`read_until`].
+    ///
+    /// [`read_until`]: BufRead::read_until
+    /// [`skip_until`]: BufRead::skip_until
+    #[cfg(feature = "alloc")]
+    fn read_until_limited(&mut self, byte: u8, buf: &mut Vec<u8>, limit: usize) -> Result<usize> {
+        read_until_limited(self, byte, buf, limit)
+    }
+
+    /// Reads a line into `buf`, like [`read_line`], but fails instead of
+    /// appending more than `limit` bytes, newline included.
+    ///
+    /// # Errors
+    ///
+    /// Exceeding `limit` is reported as with [`read_until_limited`], leaving
+    /// `buf` unchanged. Invalid UTF-8 is reported as with [`read_line`],
+    /// also leaving `buf` unchanged.
+    ///
+    /// [`read_line`]: BufRead::read_line
+    /// [`read_until_limited`]: BufRead::read_until_limited
+    #[cfg(feature = "alloc")]
+    fn read_line_limited(&mut self, buf: &mut String, limit: usize) -> Result<usize> {
+        unsafe { append_to_string(buf, |b| read_until_limited(self, b'\n', b, limit)) }
+    }
+
     /// Returns an iterator over the contents of this reader split on the byte
     /// `byte`.
     ///
@@ -2024,6 +2730,7 @@ pub trait BufRead: Read {
     /// assert_eq!(split_iter.next(), Some(b"dolor".to_vec()));
     /// assert_eq!(split_iter.next(), None);
     /// ```
+    #[cfg(feature = "alloc")]
     fn split(self, byte: u8) -> Split<Self>
     where
         Self: Sized,
@@ -2031,6 +2738,27 @@ pub trait BufRead: Read {
         Split {
             buf: self,
             delim: byte,
+            limit: None,
+        }
+    }
+
+    /// Returns an iterator like [`split`] which fails on segments longer
+    /// than `limit` bytes, delimiter included.
+    ///
+    /// An oversized segment is reported as an error of the kind
+    /// [`ErrorKind::FileTooLarge`]; the rest of it is then skipped without
+    /// being buffered, and iteration carries on with the next segment.
+    ///
+    /// [`split`]: BufRead::split
+    #[cfg(feature = "alloc")]
+    fn split_limited(self, byte: u8, limit: usize) -> Split<Self>
+    where
+        Self: Sized,
+    {
+        Split {
+            buf: self,
+            delim: byte,
+            limit: Some(limit),
         }
     }
 
@@ -2063,11 +2791,48 @@ pub trait BufRead: Read {
     /// # Errors
     ///
     /// Each line of the iterator has the same error semantics as [`BufRead::read_line`].
+    #[cfg(feature = "alloc")]
     fn lines(self) -> Lines<Self>
     where
         Self: Sized,
     {
-        Lines { buf: self }
+        Lines {
+            buf: self,
+            limit: None,
+        }
+    }
+
+    /// Returns an iterator like [`lines`] which fails on lines longer than
+    /// `limit` bytes, line ending included.
+    ///
+    /// An oversized line is reported as an error of the kind
+    /// [`ErrorKind::FileTooLarge`]; the rest of it is then skipped without
+    /// being buffered, and iteration carries on with the next line.
+    ///
+    /// [`lines`]: BufRead::lines
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use ckb_rust_std::io::{self, BufRead, ErrorKind};
+    ///
+    /// let cursor = io::Cursor::new(b"short\nfar too long\nok");
+    ///
+    /// let mut lines = cursor.lines_limited(8);
+    /// assert_eq!(lines.next().unwrap().unwrap(), "short");
+    /// assert_eq!(lines.next().unwrap().unwrap_err().kind(), ErrorKind::FileTooLarge);
+    /// assert_eq!(lines.next().unwrap().unwrap(), "ok");
+    /// assert!(lines.next().is_none());
+    /// ```
+    #[cfg(feature = "alloc")]
+    fn lines_limited(self, limit: usize) -> Lines<Self>
+    where
+        Self: Sized,
+    {
+        Lines {
+            buf: self,
+            limit: Some(limit),
+        }
     }
 }
 
@@ -2165,6 +2930,23 @@ impl<T: Read, U: Read> Read for Chain<T, U> {
         }
         self.second.read(buf)
     }
+
+    fn read_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> Result<usize> {
+        if !self.done_first {
+            match self.first.read_vectored(bufs)? {
+                0 if bufs.iter().any(|b| !b.is_empty()) => self.done_first = true,
+                n => return Ok(n),
+            }
+        }
+        self.second.read_vectored(bufs)
+    }
+
+    #[inline]
+    fn is_read_vectored(&self) -> bool {
+        self.first.is_read_vectored() || self.second.is_read_vectored()
+    }
+
+    #[cfg(feature = "alloc")]
     fn read_to_end(&mut self, buf: &mut Vec<u8>) -> Result<usize> {
         let mut read = 0;
         if !self.done_first {
@@ -2207,6 +2989,16 @@ impl<T: BufRead, U: BufRead> BufRead for Chain<T, U> {
         self.second.fill_buf()
     }
 
+    fn fill_at_least(&mut self, n: usize) -> Result<&[u8]> {
+        if !self.done_first {
+            match self.first.fill_at_least(n)? {
+                [] => self.done_first = true,
+                buf => return Ok(buf),
+            }
+        }
+        self.second.fill_at_least(n)
+    }
+
     fn consume(&mut self, amt: usize) {
         if !self.done_first {
             self.first.consume(amt)
@@ -2215,6 +3007,7 @@ impl<T: BufRead, U: BufRead> BufRead for Chain<T, U> {
         }
     }
 
+    #[cfg(feature = "alloc")]
     fn read_until(&mut self, byte: u8, buf: &mut Vec<u8>) -> Result<usize> {
         let mut read = 0;
         if !self.done_first {
@@ -2233,6 +3026,56 @@ impl<T: BufRead, U: BufRead> BufRead for Chain<T, U> {
     // We don't override `read_line` here because an UTF-8 sequence could be
     // split between the two parts of the chain
 }
+/// Seeks within the concatenation of the whole of both streams.
+///
+/// Position `n` is position `n` of the first stream if `n` is less than its
+/// length, and position `n - len` of the second stream otherwise. Seeking
+/// into the first stream rewinds the second one to its start, so reading
+/// carries on across the boundary.
+///
+/// Every seek queries the length of the first stream, and seeking from the
+/// end that of the second one, with [`Seek::stream_len`].
+impl<T: Seek, U: Seek> Seek for Chain<T, U> {
+    fn seek(&mut self, pos: SeekFrom) -> Result<u64> {
+        let first_len = self.first.stream_len()?;
+        let new_position = match pos {
+            SeekFrom::Start(n) => Some(n),
+            SeekFrom::End(n) => self.stream_len()?.checked_add_signed(n),
+            SeekFrom::Current(n) => self.stream_position()?.checked_add_signed(n),
+        }
+        .ok_or(INVALID_SEEK)?;
+        if new_position < first_len {
+            self.first.seek(SeekFrom::Start(new_position))?;
+            self.second.rewind()?;
+            self.done_first = false;
+        } else {
+            self.first.seek(SeekFrom::Start(first_len))?;
+            self.second
+                .seek(SeekFrom::Start(new_position - first_len))?;
+            self.done_first = true;
+        }
+        Ok(new_position)
+    }
+
+    fn stream_len(&mut self) -> Result<u64> {
+        self.first
+            .stream_len()?
+            .checked_add(self.second.stream_len()?)
+            .ok_or(INVALID_SEEK)
+    }
+
+    fn stream_position(&mut self) -> Result<u64> {
+        if !self.done_first {
+            self.first.stream_position()
+        } else {
+            self.first
+                .stream_len()?
+                .checked_add(self.second.stream_position()?)
+                .ok_or(INVALID_SEEK)
+        }
+    }
+}
+
 impl<T: SizeHint, U: SizeHint> SizeHint for Chain<T, U> {
     #[inline]
     fn lower_bound(&self) -> usize {
@@ -2260,6 +3103,9 @@ impl<T: SizeHint, U: SizeHint> SizeHint for Chain<T, U> {
 #[derive(Debug)]
 pub struct Take<T> {
     inner: T,
+    // The size of the window, i.e. the limit it was created or last reset
+    // with. The position within the window is `len - limit`.
+    len: u64,
     limit: u64,
 }
 
@@ -2317,9 +3163,16 @@ impl<T> Take<T> {
     /// }
     /// ```
     pub fn set_limit(&mut self, limit: u64) {
+        self.len = limit;
         self.limit = limit;
     }
 
+    /// Returns the number of bytes read since the `Take` was created or its
+    /// limit was last set, which is its position when seeking.
+    pub fn position(&self) -> u64 {
+        self.len - self.limit
+    }
+
     /// Consumes the `Take`, returning the wrapped reader.
     ///
     /// # Examples
@@ -2469,6 +3322,18 @@ impl<T: BufRead> BufRead for Take<T> {
         Ok(&buf[..cap])
     }
 
+    fn fill_at_least(&mut self, n: usize) -> Result<&[u8]> {
+        // Don't call into inner reader at all at EOF because it may still block
+        if self.limit == 0 {
+            return Ok(&[]);
+        }
+
+        let n = cmp::min(n as u64, self.limit) as usize;
+        let buf = self.inner.fill_at_least(n)?;
+        let cap = cmp::min(buf.len() as u64, self.limit) as usize;
+        Ok(&buf[..cap])
+    }
+
     fn consume(&mut self, amt: usize) {
         // Don't let callers reset the limit by passing an overlarge value
         let amt = cmp::min(amt as u64, self.limit) as usize;
@@ -2476,6 +3341,63 @@ impl<T: BufRead> BufRead for Take<T> {
         self.inner.consume(amt);
     }
 }
+/// Seeks within the window of the `Take`, relative to where the inner reader
+/// was when the `Take` was created or its limit last set.
+///
+/// The window spans `0..=len`, where `len` is that limit. Positions before
+/// its start are clamped to `0` and positions past its end to `len`, so
+/// seeking never leaves the window, just as reading never does. Seeking
+/// moves the inner reader by the same amount, so the window must not extend
+/// past the end of the inner stream for positions near its end to be
+/// reachable. If the inner reader fails to seek, the `Take` is unchanged.
+impl<T: Seek> Seek for Take<T> {
+    fn seek(&mut self, pos: SeekFrom) -> Result<u64> {
+        let new_position = match pos {
+            SeekFrom::Start(n) => n,
+            SeekFrom::End(n) => self.len.saturating_add_signed(n),
+            SeekFrom::Current(n) => self.position().saturating_add_signed(n),
+        };
+        let new_position = cmp::min(new_position, self.len);
+        self.seek_within(new_position)?;
+        Ok(new_position)
+    }
+
+    fn stream_len(&mut self) -> Result<u64> {
+        Ok(self.len)
+    }
+
+    fn stream_position(&mut self) -> Result<u64> {
+        Ok(self.position())
+    }
+
+    fn seek_relative(&mut self, offset: i64) -> Result<()> {
+        self.seek(SeekFrom::Current(offset)).map(drop)
+    }
+}
+
+impl<T: Seek> Take<T> {
+    // Moves to `pos`, which must lie within the window, with a single seek
+    // of the inner reader, so that an error leaves the `Take` unchanged.
+    fn seek_within(&mut self, pos: u64) -> Result<()> {
+        let position = self.position();
+        match i64::try_from(pos.abs_diff(position)) {
+            Ok(0) => {}
+            Ok(distance) if pos >= position => self.inner.seek_relative(distance)?,
+            Ok(distance) => self.inner.seek_relative(-distance)?,
+            // Too far for a relative seek, so go through the absolute
+            // position of the inner reader.
+            Err(_) => {
+                let start = self.inner.stream_position()?.checked_sub(position);
+                let target = start.and_then(|start| start.checked_add(pos));
+                self.inner
+                    .seek(SeekFrom::Start(target.ok_or(INVALID_SEEK)?))?;
+            }
+        }
+        self.limit = self.len - pos;
+        Ok(())
+    }
+}
+
 impl<T: SizeHint> SizeHint for Take<T> {
     #[inline]
     fn lower_bound(&self) -> usize {
@@ -2562,6 +3484,7 @@ impl<T: SizeHint> SizeHint for &mut T {
         SizeHint::upper_bound(*self)
     }
 }
+#[cfg(feature = "alloc")]
 impl<T: SizeHint> SizeHint for Box<T> {
     #[inline]
     fn lower_bound(&self) -> usize {
@@ -2586,6 +3509,16 @@ impl SizeHint for &[u8] {
     }
 }
 
+// Skips the rest of an item that exceeded the limit of a `Split` or `Lines`,
+// so that the next call to `next` starts at the following item.
+#[cfg(feature = "alloc")]
+fn skip_oversized<B: BufRead>(buf: &mut B, delim: u8, e: Error) -> Result<usize> {
+    if e.kind() == ErrorKind::FileTooLarge {
+        buf.skip_until(delim)?;
+    }
+    Err(e)
+}
+
 /// An iterator over the contents of an instance of `BufRead` split on a
 /// particular byte.
 ///
@@ -2594,16 +3527,26 @@ impl SizeHint for &[u8] {
 ///
 /// [`split`]: BufRead::split
 #[derive(Debug)]
+#[cfg(feature = "alloc")]
 pub struct Split<B> {
     buf: B,
     delim: u8,
+    limit: Option<usize>,
 }
+#[cfg(feature = "alloc")]
 impl<B: BufRead> Iterator for Split<B> {
     type Item = Result<Vec<u8>>;
 
     fn next(&mut self) -> Option<Result<Vec<u8>>> {
         let mut buf = Vec::new();
-        match self.buf.read_until(self.delim, &mut buf) {
+        let result = match self.limit {
+            None => self.buf.read_until(self.delim, &mut buf),
+            Some(limit) => self
+                .buf
+                .read_until_limited(self.delim, &mut buf, limit)
+                .or_else(|e| skip_oversized(&mut self.buf, self.delim, e)),
+        };
+        match result {
             Ok(0) => None,
             Ok(_n) => {
                 if buf[buf.len() - 1] == self.delim {
@@ -2623,15 +3566,25 @@ impl<B: BufRead> Iterator for Split<B> {
 ///
 /// [`lines`]: BufRead::lines
 #[derive(Debug)]
+#[cfg(feature = "alloc")]
 pub struct Lines<B> {
     buf: B,
+    limit: Option<usize>,
 }
+#[cfg(feature = "alloc")]
 impl<B: BufRead> Iterator for Lines<B> {
     type Item = Result<String>;
 
     fn next(&mut self) -> Option<Result<String>> {
         let mut buf = String::new();
-        match self.buf.read_line(&mut buf) {
+        let result = match self.limit {
+            None => self.buf.read_line(&mut buf),
+            Some(limit) => self
+                .buf
+                .read_line_limited(&mut buf, limit)
+                .or_else(|e| skip_oversized(&mut self.buf, b'\n', e)),
+        };
+        match result {
             Ok(0) => None,
             Ok(_n) => {
                 if buf.ends_with('\n') {
@@ -0,0 +1,427 @@
+//! Streaming access to [molecule]-encoded data.
+//!
+//! Molecule is the serialization format of CKB's on-chain data structures.
+//! Every composite type starts with little-endian `u32` headers:
+//!
+//! * a **fixvec** is an item count followed by fixed-size items;
+//! * a **dynvec** or **table** is a total size and one offset per item or
+//!   field, followed by the items or fields themselves;
+//! * a **union** is an item id followed by the item;
+//! * an **option** is empty for `None` and the inner value for `Some`;
+//! * arrays and structs are plain fixed-size bytes.
+//!
+//! [`MoleculeReader`] walks these headers over any [`Read`] + [`Seek`]
+//! source (a [`Cursor`], a [`SyscallReader`], ...) reading only the headers it
+//! needs, and [`MoleculeWriter`] emits them into any [`Write`].
+//!
+//! All malformed input is reported as [`ErrorKind::InvalidData`].
+//!
+//! [molecule]: https://github.com/nervosnetwork/molecule
+//! [`Cursor`]: crate::io::Cursor
+//! [`SyscallReader`]: crate::io::ckb::SyscallReader
+// The tests need `alloc`.
+#[cfg(all(test, feature = "alloc"))]
+mod tests;
+
+use crate::io::{self, const_io_error, ErrorKind, Read, Seek, SeekFrom, Write};
+use core::cmp;
+
+const HEADER_SIZE: u64 = 4;
+
+const TRUNCATED: io::Error = const_io_error!(
+    ErrorKind::InvalidData,
+    "molecule: data is too short for its header",
+);
+const TOTAL_SIZE_MISMATCH: io::Error = const_io_error!(
+    ErrorKind::InvalidData,
+    "molecule: header size does not match the data length",
+);
+const INVALID_OFFSET: io::Error = const_io_error!(
+    ErrorKind::InvalidData,
+    "molecule: item offsets are out of order or out of bounds",
+);
+const INDEX_OUT_OF_BOUNDS: io::Error = const_io_error!(
+    ErrorKind::InvalidData,
+    "molecule: item index is out of bounds",
+);
+const FIXED_SIZE_MISMATCH: io::Error = const_io_error!(
+    ErrorKind::InvalidData,
+    "molecule: fixed-size value has the wrong length",
+);
+const TOO_LARGE: io::Error = const_io_error!(
+    ErrorKind::InvalidInput,
+    "molecule: size does not fit in a u32 header",
+);
+
+/// A view of one molecule value inside a [`Read`] + [`Seek`] source.
+///
+/// The methods which navigate into a composite value return a new
+/// `MoleculeReader` borrowing the same source, windowed to the child value,
+/// so nested structures can be walked without copying anything. Each call
+/// only reads the `u32` headers it needs.
+///
+/// A `MoleculeReader` is itself [`Read`] + [`Seek`] over its window, which is
+/// how the bytes of leaf values are read.
+///
+/// # Examples
+///
+/// ```no_run
+/// use ckb_rust_std::io::{self, molecule::MoleculeReader, Cursor, Read};
+///
+/// // WitnessArgs { lock: BytesOpt, input_type: BytesOpt, output_type: BytesOpt }
+/// fn lock_len(witness: &[u8]) -> io::Result<Option<u32>> {
+///     let mut witness_args = MoleculeReader::new(Cursor::new(witness))?;
+///     let mut lock = witness_args.table_field(0)?;
+///     match lock.option() {
+///         Some(mut bytes) => Ok(Some(bytes.fixvec_len(1)?)),
+///         None => Ok(None),
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct MoleculeReader<R> {
+    inner: R,
+    // Absolute offset of the window in `inner`.
+    start: u64,
+    len: u64,
+    // Position relative to `start`. May exceed `len` after seeking.
+    pos: u64,
+}
+
+impl<R: Read + Seek> MoleculeReader<R> {
+    /// Creates a reader over everything from the current position of `inner`
+    /// to its end.
+    pub fn new(mut inner: R) -> io::Result<MoleculeReader<R>> {
+        let start = inner.stream_position()?;
+        let end = inner.seek(SeekFrom::End(0))?;
+        Ok(MoleculeReader::with_window(
+            inner,
+            start,
+            end.saturating_sub(start),
+        ))
+    }
+
+    /// Creates a reader over the `len` bytes of `inner` starting at `start`.
+    pub fn with_window(inner: R, start: u64, len: u64) -> MoleculeReader<R> {
+        MoleculeReader {
+            inner,
+            start,
+            len,
+            pos: 0,
+        }
+    }
+
+    /// Returns the length of this value in bytes.
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Returns `true` if this value is empty, e.g. a `None` option.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Gets a reference to the underlying reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Gets a mutable reference to the underlying reader.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Consumes this `MoleculeReader`, returning the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    fn read_u32_at(&mut self, offset: u64) -> io::Result<u32> {
+        if offset + HEADER_SIZE > self.len {
+            return Err(TRUNCATED);
+        }
+        let mut buf = [0u8; 4];
+        self.inner.seek(SeekFrom::Start(self.start + offset))?;
+        self.inner.read_exact(&mut buf)?;
+        Ok(u32::from_le_bytes(buf))
+    }
+
+    fn child(&mut self, offset: u64, len: u64) -> MoleculeReader<&mut R> {
+        debug_assert!(offset + len <= self.len);
+        MoleculeReader::with_window(&mut self.inner, self.start + offset, len)
+    }
+
+    /// Reads this value as an array or struct of exactly `N` bytes.
+    pub fn read_fixed<const N: usize>(&mut self) -> io::Result<[u8; N]> {
+        if self.len != N as u64 {
+            return Err(FIXED_SIZE_MISMATCH);
+        }
+        let mut buf = [0u8; N];
+        self.inner.seek(SeekFrom::Start(self.start))?;
+        self.inner.read_exact(&mut buf)?;
+        Ok(buf)
+    }
+
+    /// Returns the item count of this fixvec of `item_size`-byte items,
+    /// checking it against the length of the value.
+    pub fn fixvec_len(&mut self, item_size: u64) -> io::Result<u32> {
+        let count = self.read_u32_at(0)?;
+        match (count as u64).checked_mul(item_size) {
+            Some(size) if size + HEADER_SIZE == self.len => Ok(count),
+            _ => Err(TOTAL_SIZE_MISMATCH),
+        }
+    }
+
+    /// Returns the item at `index` of this fixvec of `item_size`-byte items.
+    pub fn fixvec_item(
+        &mut self,
+        item_size: u64,
+        index: u32,
+    ) -> io::Result<MoleculeReader<&mut R>> {
+        if index >= self.fixvec_len(item_size)? {
+            return Err(INDEX_OUT_OF_BOUNDS);
+        }
+        Ok(self.child(HEADER_SIZE + index as u64 * item_size, item_size))
+    }
+
+    /// Returns the items of this fixvec of `item_size`-byte items as one
+    /// value, e.g. the raw content of `Bytes`.
+    pub fn fixvec_data(&mut self, item_size: u64) -> io::Result<MoleculeReader<&mut R>> {
+        self.fixvec_len(item_size)?;
+        Ok(self.child(HEADER_SIZE, self.len - HEADER_SIZE))
+    }
+
+    /// Returns the item count of this dynvec.
+    pub fn dynvec_len(&mut self) -> io::Result<u32> {
+        let total = self.read_u32_at(0)?;
+        if total as u64 != self.len {
+            return Err(TOTAL_SIZE_MISMATCH);
+        }
+        if self.len == HEADER_SIZE {
+            return Ok(0);
+        }
+        let first = self.read_u32_at(HEADER_SIZE)?;
+        if first % 4 != 0 || (first as u64) < 2 * HEADER_SIZE || first > total {
+            return Err(INVALID_OFFSET);
+        }
+        Ok(first / 4 - 1)
+    }
+
+    /// Returns the item at `index` of this dynvec.
+    pub fn dynvec_item(&mut self, index: u32) -> io::Result<MoleculeReader<&mut R>> {
+        let count = self.dynvec_len()?;
+        if index >= count {
+            return Err(INDEX_OUT_OF_BOUNDS);
+        }
+        let header_len = HEADER_SIZE * (count as u64 + 1);
+        let offset_at = HEADER_SIZE * (index as u64 + 1);
+        let start = self.read_u32_at(offset_at)? as u64;
+        let end = if index + 1 < count {
+            self.read_u32_at(offset_at + HEADER_SIZE)? as u64
+        } else {
+            self.len
+        };
+        if start < header_len || start > end || end > self.len {
+            return Err(INVALID_OFFSET);
+        }
+        Ok(self.child(start, end - start))
+    }
+
+    /// Returns the number of fields in this table.
+    ///
+    /// A table written by a newer schema may have more fields than the
+    /// reader expects; those extra fields can simply be ignored.
+    pub fn table_field_count(&mut self) -> io::Result<u32> {
+        self.dynvec_len()
+    }
+
+    /// Returns the field at `index` of this table.
+    pub fn table_field(&mut self, index: u32) -> io::Result<MoleculeReader<&mut R>> {
+        self.dynvec_item(index)
+    }
+
+    /// Returns the item id and the item of this union.
+    pub fn union(&mut self) -> io::Result<(u32, MoleculeReader<&mut R>)> {
+        let id = self.read_u32_at(0)?;
+        Ok((id, self.child(HEADER_SIZE, self.len - HEADER_SIZE)))
+    }
+
+    /// Returns the inner value of this option, or `None` if it is empty.
+    pub fn option(&mut self) -> Option<MoleculeReader<&mut R>> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(self.child(0, self.len))
+        }
+    }
+}
+
+impl<R: Read + Seek> Read for MoleculeReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let remaining = self.len.saturating_sub(self.pos);
+        let amt = cmp::min(buf.len() as u64, remaining) as usize;
+        if amt == 0 {
+            return Ok(0);
+        }
+        // Other views may share `inner`, so always position it first.
+        self.inner.seek(SeekFrom::Start(self.start + self.pos))?;
+        let n = self.inner.read(&mut buf[..amt])?;
+        self.pos += n as u64;
+        Ok(n)
+    }
+}
+
+impl<R: Read + Seek> Seek for MoleculeReader<R> {
+    fn seek(&mut self, style: SeekFrom) -> io::Result<u64> {
+        self.pos = io::seek_position(style, self.pos, || Ok(self.len))?;
+        Ok(self.pos)
+    }
+
+    fn stream_len(&mut self) -> io::Result<u64> {
+        Ok(self.len)
+    }
+
+    fn stream_position(&mut self) -> io::Result<u64> {
+        Ok(self.pos)
+    }
+}
+
+/// Returns the encoded size of a fixvec of `count` items of `item_size`
+/// bytes.
+pub const fn fixvec_size(count: usize, item_size: usize) -> usize {
+    HEADER_SIZE as usize + count * item_size
+}
+
+/// Returns the encoded size of a dynvec or table whose items or fields have
+/// the given encoded sizes.
+pub fn dynvec_size(item_sizes: &[usize]) -> usize {
+    HEADER_SIZE as usize * (item_sizes.len() + 1) + item_sizes.iter().sum::<usize>()
+}
+
+fn to_u32(n: usize) -> io::Result<u32> {
+    u32::try_from(n).map_err(|_| TOO_LARGE)
+}
+
+/// A writer which emits molecule headers.
+///
+/// Composite values are written as a header followed by their items. Since
+/// the header of a dynvec or table records the size of every item, those
+/// sizes are passed up front (see [`dynvec_size`] and [`fixvec_size`] for
+/// nested values); the items themselves are then written through the
+/// [`Write`] impl, so nothing has to be buffered.
+///
+/// # Examples
+///
+/// ```no_run
+/// use ckb_rust_std::io::{self, molecule::{fixvec_size, MoleculeWriter}};
+///
+/// // WitnessArgs { lock: Some(signature), input_type: None, output_type: None }
+/// fn witness_args<W: io::Write>(out: W, signature: &[u8; 65]) -> io::Result<()> {
+///     let mut writer = MoleculeWriter::new(out);
+///     writer.write_table_header(&[fixvec_size(65, 1), 0, 0])?;
+///     writer.write_bytes(signature)
+/// }
+/// ```
+#[derive(Debug)]
+pub struct MoleculeWriter<W> {
+    inner: W,
+}
+
+impl<W: Write> MoleculeWriter<W> {
+    /// Creates a new `MoleculeWriter`.
+    pub fn new(inner: W) -> MoleculeWriter<W> {
+        MoleculeWriter { inner }
+    }
+
+    /// Gets a reference to the underlying writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Gets a mutable reference to the underlying writer.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Consumes this `MoleculeWriter`, returning the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+
+    fn write_u32(&mut self, n: u32) -> io::Result<()> {
+        self.inner.write_all(&n.to_le_bytes())
+    }
+
+    /// Writes the header of a fixvec of `count` items. The items follow.
+    pub fn write_fixvec_header(&mut self, count: usize) -> io::Result<()> {
+        let count = to_u32(count)?;
+        self.write_u32(count)
+    }
+
+    /// Writes `data` as `Bytes`, i.e. a fixvec of bytes.
+    pub fn write_bytes(&mut self, data: &[u8]) -> io::Result<()> {
+        self.write_fixvec_header(data.len())?;
+        self.inner.write_all(data)
+    }
+
+    /// Writes the header of a dynvec whose items have the given encoded
+    /// sizes. The items follow.
+    pub fn write_dynvec_header(&mut self, item_sizes: &[usize]) -> io::Result<()> {
+        let total = to_u32(dynvec_size(item_sizes))?;
+        self.write_u32(total)?;
+        let mut offset = HEADER_SIZE as usize * (item_sizes.len() + 1);
+        for size in item_sizes {
+            self.write_u32(offset as u32)?;
+            offset += size;
+        }
+        Ok(())
+    }
+
+    /// Writes the header of a table whose fields have the given encoded
+    /// sizes. The fields follow.
+    pub fn write_table_header(&mut self, field_sizes: &[usize]) -> io::Result<()> {
+        self.write_dynvec_header(field_sizes)
+    }
+
+    /// Writes a dynvec of already encoded items.
+    pub fn write_dynvec(&mut self, items: &[&[u8]]) -> io::Result<()> {
+        // Avoid an allocation: compute the header from the items directly.
+        let total: usize = items.iter().map(|item| item.len()).sum();
+        let header_len = HEADER_SIZE as usize * (items.len() + 1);
+        self.write_u32(to_u32(header_len + total)?)?;
+        let mut offset = header_len;
+        for item in items {
+            self.write_u32(offset as u32)?;
+            offset += item.len();
+        }
+        for item in items {
+            self.inner.write_all(item)?;
+        }
+        Ok(())
+    }
+
+    /// Writes a table of already encoded fields.
+    pub fn write_table(&mut self, fields: &[&[u8]]) -> io::Result<()> {
+        self.write_dynvec(fields)
+    }
+
+    /// Writes the header of a union holding the item with id `item_id`. The
+    /// item follows.
+    pub fn write_union_header(&mut self, item_id: u32) -> io::Result<()> {
+        self.write_u32(item_id)
+    }
+}
+
+impl<W: Write> Write for MoleculeWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.inner.write(buf)
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.inner.write_all(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
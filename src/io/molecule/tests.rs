@@ -0,0 +1,188 @@
+use super::*;
+use crate::io::{Cursor, ErrorKind};
+use alloc::vec;
+use alloc::vec::Vec;
+
+fn u32s(values: &[u32]) -> Vec<u8> {
+    values.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+/// `WitnessArgs { lock: Some(lock), input_type: None, output_type: None }`
+/// encoded by hand.
+fn witness_args(lock: &[u8]) -> Vec<u8> {
+    let lock_size = 4 + lock.len() as u32;
+    let total = 16 + lock_size;
+    let mut data = u32s(&[total, 16, total, total, lock.len() as u32]);
+    data.extend_from_slice(lock);
+    data
+}
+
+fn reader(data: &[u8]) -> MoleculeReader<Cursor<&[u8]>> {
+    MoleculeReader::new(Cursor::new(data)).unwrap()
+}
+
+#[test]
+fn read_table_fields() {
+    let data = witness_args(&[7; 65]);
+    let mut witness = reader(&data);
+    assert_eq!(witness.len(), data.len() as u64);
+    assert_eq!(witness.table_field_count().unwrap(), 3);
+
+    let mut lock = witness.table_field(0).unwrap();
+    let mut bytes = lock.option().unwrap();
+    assert_eq!(bytes.fixvec_len(1).unwrap(), 65);
+    let mut content = Vec::new();
+    bytes
+        .fixvec_data(1)
+        .unwrap()
+        .read_to_end(&mut content)
+        .unwrap();
+    assert_eq!(content, [7; 65]);
+
+    assert!(witness.table_field(1).unwrap().option().is_none());
+    assert!(witness.table_field(2).unwrap().is_empty());
+    let err = witness.table_field(3).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+}
+
+#[test]
+fn read_empty_dynvec() {
+    let data = u32s(&[4]);
+    assert_eq!(reader(&data).dynvec_len().unwrap(), 0);
+    let err = reader(&data).dynvec_item(0).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+}
+
+#[test]
+fn read_fixvec_items() {
+    let data = u32s(&[3, 10, 20, 30]);
+    let mut vec = reader(&data);
+    assert_eq!(vec.fixvec_len(4).unwrap(), 3);
+    let item = vec.fixvec_item(4, 2).unwrap().read_fixed::<4>().unwrap();
+    assert_eq!(u32::from_le_bytes(item), 30);
+    assert!(vec.fixvec_item(4, 3).is_err());
+
+    // The item size must agree with the length.
+    let err = vec.fixvec_len(8).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+}
+
+#[test]
+fn read_union() {
+    let mut data = u32s(&[2]);
+    data.extend_from_slice(&[1, 2, 3]);
+    let mut union = reader(&data);
+    let (id, mut item) = union.union().unwrap();
+    assert_eq!(id, 2);
+    assert_eq!(item.read_fixed::<3>().unwrap(), [1, 2, 3]);
+    let err = item.read_fixed::<4>().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+}
+
+#[test]
+fn read_at_offset_in_source() {
+    // The value starts wherever the source is positioned.
+    let mut data = vec![0xff; 5];
+    data.extend(u32s(&[1, 42]));
+    let mut cursor = Cursor::new(&data[..]);
+    cursor.set_position(5);
+    let mut vec = MoleculeReader::new(cursor).unwrap();
+    let item = vec.fixvec_item(4, 0).unwrap().read_fixed::<4>().unwrap();
+    assert_eq!(u32::from_le_bytes(item), 42);
+}
+
+#[test]
+fn views_share_the_source() {
+    let mut writer = MoleculeWriter::new(Vec::new());
+    writer.write_dynvec(&[b"abc", b"defg"]).unwrap();
+    let data = writer.into_inner();
+
+    let mut vec = reader(&data);
+    let mut first = [0u8; 2];
+    let mut rest = [0u8; 1];
+    vec.dynvec_item(0).unwrap().read_exact(&mut first).unwrap();
+    // Reading another item moves the shared source, which must not affect
+    // the next view.
+    assert_eq!(
+        vec.dynvec_item(1).unwrap().read_fixed::<4>().unwrap(),
+        *b"defg"
+    );
+    let mut item = vec.dynvec_item(0).unwrap();
+    item.seek(SeekFrom::Start(2)).unwrap();
+    item.read_exact(&mut rest).unwrap();
+    assert_eq!((first, rest), (*b"ab", *b"c"));
+    assert_eq!(item.read(&mut rest).unwrap(), 0);
+}
+
+#[test]
+fn reject_malformed_headers() {
+    let cases: &[Vec<u8>] = &[
+        // Too short for the total size.
+        vec![1, 0],
+        // Total size disagrees with the data length.
+        u32s(&[16, 8, 0]),
+        // First offset is not a multiple of 4.
+        u32s(&[12, 9, 0]),
+        // First offset points past the end.
+        u32s(&[12, 16, 0]),
+        // Offsets go backwards.
+        u32s(&[16, 12, 8, 0]),
+    ];
+    for data in cases {
+        let err = reader(data).dynvec_item(0).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData, "{data:?}");
+    }
+}
+
+#[test]
+fn write_table_header() {
+    let lock = [7u8; 65];
+    let mut writer = MoleculeWriter::new(Vec::new());
+    writer
+        .write_table_header(&[fixvec_size(lock.len(), 1), 0, 0])
+        .unwrap();
+    writer.write_bytes(&lock).unwrap();
+    assert_eq!(writer.into_inner(), witness_args(&lock));
+}
+
+#[test]
+fn write_table_of_fields() {
+    let mut bytes = MoleculeWriter::new(Vec::new());
+    bytes.write_bytes(&[7; 65]).unwrap();
+    let bytes = bytes.into_inner();
+
+    let mut writer = MoleculeWriter::new(Vec::new());
+    writer.write_table(&[&bytes, &[], &[]]).unwrap();
+    assert_eq!(writer.into_inner(), witness_args(&[7; 65]));
+}
+
+#[test]
+fn write_nested_round_trip() {
+    // Union { id: 1, item: dynvec [Bytes("x"), Bytes("yz")] }
+    let items = [fixvec_size(1, 1), fixvec_size(2, 1)];
+    let mut writer = MoleculeWriter::new(Vec::new());
+    writer.write_union_header(1).unwrap();
+    writer.write_dynvec_header(&items).unwrap();
+    writer.write_bytes(b"x").unwrap();
+    writer.write_bytes(b"yz").unwrap();
+    let data = writer.into_inner();
+    assert_eq!(data.len(), 4 + dynvec_size(&items));
+
+    let mut union = reader(&data);
+    let (id, mut vec) = union.union().unwrap();
+    assert_eq!(id, 1);
+    assert_eq!(vec.dynvec_len().unwrap(), 2);
+    let mut item = vec.dynvec_item(1).unwrap();
+    assert_eq!(
+        item.fixvec_data(1).unwrap().read_fixed::<2>().unwrap(),
+        *b"yz"
+    );
+}
+
+#[test]
+fn write_fixvec_header() {
+    let mut writer = MoleculeWriter::new(Vec::new());
+    writer.write_fixvec_header(2).unwrap();
+    writer.write_all(&[1, 2]).unwrap();
+    assert_eq!(writer.into_inner(), [2, 0, 0, 0, 1, 2]);
+}
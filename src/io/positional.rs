@@ -0,0 +1,384 @@
+//! Positional I/O: reading and writing at an explicit offset.
+//!
+//! [`Read`] and [`Seek`] share one cursor, so two parsers walking the same
+//! source have to take turns or each own a copy. [`ReadAt`] takes the offset
+//! as an argument instead and reads through `&self`, which makes a single
+//! source shareable: wrap a reference to it in as many [`ReadAtCursor`]s as
+//! needed, each with its own position. [`WriteAt`] is the writing
+//! counterpart.
+#[cfg(test)]
+mod tests;
+
+use super::cursor::slice_write;
+#[cfg(feature = "alloc")]
+use super::cursor::vec_write;
+use crate::io::{self, const_io_error, Cursor, ErrorKind, Read, Result, Seek, SeekFrom};
+#[cfg(feature = "alloc")]
+use alloc::{boxed::Box, vec::Vec};
+use core::cmp;
+
+const UNKNOWN_SIZE: io::Error = const_io_error!(
+    ErrorKind::Unsupported,
+    "cannot seek from the end of a source of unknown size",
+);
+
+/// Reads from a source at a given offset.
+///
+/// Unlike [`Read`], reading does not move any cursor, so a `ReadAt` can be
+/// read through a shared reference by any number of users at once.
+pub trait ReadAt {
+    /// Reads some bytes starting at `offset` into `buf`, returning how many
+    /// bytes were read.
+    ///
+    /// As with [`Read::read`], a short read is not an error, and `Ok(0)`
+    /// means `offset` is at or past the end of the source (or `buf` is
+    /// empty).
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> Result<usize>;
+
+    /// Reads exactly `buf.len()` bytes starting at `offset`.
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`ErrorKind::UnexpectedEof`] if the source ends before
+    /// `buf` is filled, in which case the contents of `buf` are unspecified.
+    #[cfg_attr(feature = "error_location", track_caller)]
+    fn read_exact_at(&self, mut buf: &mut [u8], mut offset: u64) -> Result<()> {
+        while !buf.is_empty() {
+            match self.read_at(buf, offset) {
+                Ok(0) => break,
+                Ok(n) => {
+                    buf = &mut buf[n..];
+                    offset += n as u64;
+                }
+                Err(e) if e.is_interrupted() => {}
+                Err(e) => return Err(e),
+            }
+        }
+        if !buf.is_empty() {
+            Err(io::Error::READ_EXACT_EOF.at_caller())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Returns the size of the source in bytes, if it is known.
+    ///
+    /// [`ReadAtCursor`] needs this to seek from the end. The default
+    /// implementation returns `None`.
+    fn size(&self) -> Option<u64> {
+        None
+    }
+}
+
+/// Writes to a destination at a given offset.
+pub trait WriteAt {
+    /// Writes some bytes of `buf` starting at `offset`, returning how many
+    /// bytes were written.
+    ///
+    /// Fixed-size destinations return a short count, or `Ok(0)`, when
+    /// `buf` does not fit; growable ones such as `Vec<u8>` are extended,
+    /// padding any gap before `offset` with zeros.
+    fn write_at(&mut self, buf: &[u8], offset: u64) -> Result<usize>;
+
+    /// Writes all of `buf` starting at `offset`.
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`ErrorKind::WriteZero`] if the destination has no room
+    /// for the whole of `buf`.
+    #[cfg_attr(feature = "error_location", track_caller)]
+    fn write_all_at(&mut self, mut buf: &[u8], mut offset: u64) -> Result<()> {
+        while !buf.is_empty() {
+            match self.write_at(buf, offset) {
+                Ok(0) => return Err(io::Error::WRITE_ALL_EOF.at_caller()),
+                Ok(n) => {
+                    buf = &buf[n..];
+                    offset += n as u64;
+                }
+                Err(e) if e.is_interrupted() => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<R: ReadAt + ?Sized> ReadAt for &R {
+    #[inline]
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> Result<usize> {
+        (**self).read_at(buf, offset)
+    }
+
+    #[inline]
+    fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> Result<()> {
+        (**self).read_exact_at(buf, offset)
+    }
+
+    #[inline]
+    fn size(&self) -> Option<u64> {
+        (**self).size()
+    }
+}
+
+impl<R: ReadAt + ?Sized> ReadAt for &mut R {
+    #[inline]
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> Result<usize> {
+        (**self).read_at(buf, offset)
+    }
+
+    #[inline]
+    fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> Result<()> {
+        (**self).read_exact_at(buf, offset)
+    }
+
+    #[inline]
+    fn size(&self) -> Option<u64> {
+        (**self).size()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<R: ReadAt + ?Sized> ReadAt for Box<R> {
+    #[inline]
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> Result<usize> {
+        (**self).read_at(buf, offset)
+    }
+
+    #[inline]
+    fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> Result<()> {
+        (**self).read_exact_at(buf, offset)
+    }
+
+    #[inline]
+    fn size(&self) -> Option<u64> {
+        (**self).size()
+    }
+}
+
+impl<W: WriteAt + ?Sized> WriteAt for &mut W {
+    #[inline]
+    fn write_at(&mut self, buf: &[u8], offset: u64) -> Result<usize> {
+        (**self).write_at(buf, offset)
+    }
+
+    #[inline]
+    fn write_all_at(&mut self, buf: &[u8], offset: u64) -> Result<()> {
+        (**self).write_all_at(buf, offset)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<W: WriteAt + ?Sized> WriteAt for Box<W> {
+    #[inline]
+    fn write_at(&mut self, buf: &[u8], offset: u64) -> Result<usize> {
+        (**self).write_at(buf, offset)
+    }
+
+    #[inline]
+    fn write_all_at(&mut self, buf: &[u8], offset: u64) -> Result<()> {
+        (**self).write_all_at(buf, offset)
+    }
+}
+
+impl ReadAt for [u8] {
+    #[inline]
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> Result<usize> {
+        let start = cmp::min(offset, self.len() as u64) as usize;
+        let mut remaining = &self[start..];
+        remaining.read(buf)
+    }
+
+    #[inline]
+    fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> Result<()> {
+        let start = cmp::min(offset, self.len() as u64) as usize;
+        let mut remaining = &self[start..];
+        remaining.read_exact(buf)
+    }
+
+    #[inline]
+    fn size(&self) -> Option<u64> {
+        Some(self.len() as u64)
+    }
+}
+
+impl WriteAt for [u8] {
+    #[inline]
+    fn write_at(&mut self, buf: &[u8], offset: u64) -> Result<usize> {
+        let mut pos = offset;
+        slice_write(&mut pos, self, buf)
+    }
+}
+
+impl<const N: usize> ReadAt for [u8; N] {
+    #[inline]
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> Result<usize> {
+        self.as_slice().read_at(buf, offset)
+    }
+
+    #[inline]
+    fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> Result<()> {
+        self.as_slice().read_exact_at(buf, offset)
+    }
+
+    #[inline]
+    fn size(&self) -> Option<u64> {
+        Some(N as u64)
+    }
+}
+
+impl<const N: usize> WriteAt for [u8; N] {
+    #[inline]
+    fn write_at(&mut self, buf: &[u8], offset: u64) -> Result<usize> {
+        self.as_mut_slice().write_at(buf, offset)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl ReadAt for Vec<u8> {
+    #[inline]
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> Result<usize> {
+        self.as_slice().read_at(buf, offset)
+    }
+
+    #[inline]
+    fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> Result<()> {
+        self.as_slice().read_exact_at(buf, offset)
+    }
+
+    #[inline]
+    fn size(&self) -> Option<u64> {
+        Some(self.len() as u64)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl WriteAt for Vec<u8> {
+    #[inline]
+    fn write_at(&mut self, buf: &[u8], offset: u64) -> Result<usize> {
+        let mut pos = offset;
+        vec_write(&mut pos, self, buf)
+    }
+}
+
+/// Reads from the cursor's underlying buffer, ignoring and keeping its
+/// position.
+impl<T: ReadAt> ReadAt for Cursor<T> {
+    #[inline]
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> Result<usize> {
+        self.get_ref().read_at(buf, offset)
+    }
+
+    #[inline]
+    fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> Result<()> {
+        self.get_ref().read_exact_at(buf, offset)
+    }
+
+    #[inline]
+    fn size(&self) -> Option<u64> {
+        self.get_ref().size()
+    }
+}
+
+/// Writes to the cursor's underlying buffer, ignoring and keeping its
+/// position.
+impl<T: WriteAt> WriteAt for Cursor<T> {
+    #[inline]
+    fn write_at(&mut self, buf: &[u8], offset: u64) -> Result<usize> {
+        self.get_mut().write_at(buf, offset)
+    }
+
+    #[inline]
+    fn write_all_at(&mut self, buf: &[u8], offset: u64) -> Result<()> {
+        self.get_mut().write_all_at(buf, offset)
+    }
+}
+
+/// A [`Read`] + [`Seek`] cursor over a [`ReadAt`] source.
+///
+/// The cursor only keeps a position; all data stays in the source. Since
+/// `&R` is a `ReadAt` whenever `R` is, any number of cursors can read one
+/// source independently. Seeking past the end is allowed, after which reads
+/// return `Ok(0)`; seeking from the end requires [`ReadAt::size`].
+///
+/// # Examples
+///
+/// ```no_run
+/// use ckb_rust_std::io::{self, ckb::{self, Source}, Read, ReadAtCursor, Seek, SeekFrom};
+///
+/// fn main() -> io::Result<()> {
+///     let witness = ckb::witness(0, Source::GroupInput)?;
+///     let mut header = ReadAtCursor::new(&witness);
+///     let mut body = ReadAtCursor::new(&witness);
+///     body.seek(SeekFrom::Start(16))?;
+///     let mut buf = [0; 4];
+///     header.read_exact(&mut buf)?;
+///     body.read_exact(&mut buf)?;
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ReadAtCursor<R> {
+    inner: R,
+    pos: u64,
+}
+
+impl<R> ReadAtCursor<R> {
+    /// Creates a cursor at the start of `inner`.
+    pub const fn new(inner: R) -> ReadAtCursor<R> {
+        ReadAtCursor { inner, pos: 0 }
+    }
+
+    /// Returns the current position of this cursor.
+    pub const fn position(&self) -> u64 {
+        self.pos
+    }
+
+    /// Sets the position of this cursor.
+    pub fn set_position(&mut self, pos: u64) {
+        self.pos = pos;
+    }
+
+    /// Gets a reference to the underlying source.
+    pub const fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Gets a mutable reference to the underlying source.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Consumes this cursor, returning the underlying source.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: ReadAt> Read for ReadAtCursor<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let n = self.inner.read_at(buf, self.pos)?;
+        self.pos += n as u64;
+        Ok(n)
+    }
+
+    fn read_exact(&mut self, buf: &mut [u8]) -> Result<()> {
+        self.inner.read_exact_at(buf, self.pos)?;
+        self.pos += buf.len() as u64;
+        Ok(())
+    }
+}
+
+impl<R: ReadAt> Seek for ReadAtCursor<R> {
+    fn seek(&mut self, style: SeekFrom) -> Result<u64> {
+        self.pos = io::seek_position(style, self.pos, || self.inner.size().ok_or(UNKNOWN_SIZE))?;
+        Ok(self.pos)
+    }
+
+    fn stream_len(&mut self) -> Result<u64> {
+        self.inner.size().ok_or(UNKNOWN_SIZE)
+    }
+
+    fn stream_position(&mut self) -> Result<u64> {
+        Ok(self.pos)
+    }
+}
@@ -0,0 +1,137 @@
+use super::*;
+#[cfg(feature = "alloc")]
+use crate::io::Write;
+#[cfg(feature = "alloc")]
+use alloc::vec;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+#[test]
+fn slice_read_at() {
+    let data: &[u8] = &[1, 2, 3, 4, 5];
+    let mut buf = [0; 3];
+    assert_eq!(data.read_at(&mut buf, 1).unwrap(), 3);
+    assert_eq!(buf, [2, 3, 4]);
+    assert_eq!(data.read_at(&mut buf, 3).unwrap(), 2);
+    assert_eq!(&buf[..2], [4, 5]);
+    assert_eq!(data.read_at(&mut buf, 5).unwrap(), 0);
+    assert_eq!(data.read_at(&mut buf, u64::MAX).unwrap(), 0);
+    assert_eq!(data.size(), Some(5));
+
+    data.read_exact_at(&mut buf, 2).unwrap();
+    assert_eq!(buf, [3, 4, 5]);
+    let err = data.read_exact_at(&mut buf, 3).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+    // Reading does not consume anything.
+    assert_eq!(data, [1, 2, 3, 4, 5]);
+}
+
+#[test]
+fn slice_write_at() {
+    let mut data = [0u8; 5];
+    assert_eq!(data.write_at(&[1, 2], 1).unwrap(), 2);
+    assert_eq!(data.write_at(&[3, 4, 5], 3).unwrap(), 2);
+    assert_eq!(data.write_at(&[6], 5).unwrap(), 0);
+    assert_eq!(data, [0, 1, 2, 3, 4]);
+
+    data.as_mut_slice().write_all_at(&[9, 9], 0).unwrap();
+    assert_eq!(data, [9, 9, 2, 3, 4]);
+    let err = data.write_all_at(&[7, 7], 4).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::WriteZero);
+    assert_eq!(data, [9, 9, 2, 3, 7]);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn vec_read_and_write_at() {
+    let mut v = vec![1, 2, 3];
+    assert_eq!(v.write_at(&[4, 5], 2).unwrap(), 2);
+    assert_eq!(v, [1, 2, 4, 5]);
+    // Writing past the end pads with zeros.
+    v.write_all_at(&[6], 6).unwrap();
+    assert_eq!(v, [1, 2, 4, 5, 0, 0, 6]);
+    assert_eq!(v.size(), Some(7));
+
+    let mut buf = [0; 2];
+    v.read_exact_at(&mut buf, 5).unwrap();
+    assert_eq!(buf, [0, 6]);
+    assert_eq!(v.read_at(&mut buf, 7).unwrap(), 0);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn cursor_position_is_untouched() {
+    let mut c = Cursor::new(vec![0u8; 4]);
+    c.set_position(1);
+    c.write_all_at(&[1, 2], 2).unwrap();
+    assert_eq!(c.position(), 1);
+    c.write_all(&[7]).unwrap();
+    assert_eq!(c.get_ref(), &[0, 7, 1, 2]);
+
+    let mut buf = [0; 3];
+    c.read_exact_at(&mut buf, 1).unwrap();
+    assert_eq!(buf, [7, 1, 2]);
+    assert_eq!(c.position(), 2);
+
+    let mut fixed = [0u8; 3];
+    let mut c = Cursor::new(&mut fixed[..]);
+    assert_eq!(c.write_at(&[5, 5, 5], 1).unwrap(), 2);
+    assert_eq!(fixed, [0, 5, 5]);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn read_at_cursor() {
+    let data = [10u8, 11, 12, 13, 14, 15];
+    let mut a = ReadAtCursor::new(&data);
+    let mut b = ReadAtCursor::new(&data);
+
+    let mut buf = [0; 4];
+    assert_eq!(a.read(&mut buf).unwrap(), 4);
+    assert_eq!(buf, [10, 11, 12, 13]);
+    assert_eq!(b.seek(SeekFrom::End(-2)).unwrap(), 4);
+    b.read_exact(&mut buf[..2]).unwrap();
+    assert_eq!(&buf[..2], [14, 15]);
+    assert_eq!(a.read(&mut buf).unwrap(), 2);
+    assert_eq!(&buf[..2], [14, 15]);
+    assert_eq!(a.read(&mut buf).unwrap(), 0);
+
+    assert_eq!(a.seek(SeekFrom::Current(-5)).unwrap(), 1);
+    let mut out = Vec::new();
+    a.read_to_end(&mut out).unwrap();
+    assert_eq!(out, [11, 12, 13, 14, 15]);
+    assert_eq!(a.stream_len().unwrap(), 6);
+
+    let err = a.seek(SeekFrom::Current(-7)).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+
+    // A failed read_exact leaves the position alone.
+    b.set_position(3);
+    let err = b.read_exact(&mut [0; 4]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+    assert_eq!(b.position(), 3);
+}
+
+/// A source without a known size.
+struct Counting;
+
+impl ReadAt for Counting {
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> Result<usize> {
+        for (i, b) in buf.iter_mut().enumerate() {
+            *b = (offset as usize + i) as u8;
+        }
+        Ok(buf.len())
+    }
+}
+
+#[test]
+fn read_at_cursor_unknown_size() {
+    let mut c = ReadAtCursor::new(Counting);
+    c.seek(SeekFrom::Start(250)).unwrap();
+    let mut buf = [0; 8];
+    c.read_exact(&mut buf).unwrap();
+    assert_eq!(buf, [250, 251, 252, 253, 254, 255, 0, 1]);
+    assert_eq!(c.position(), 258);
+    let err = c.seek(SeekFrom::End(0)).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Unsupported);
+}
@@ -0,0 +1,312 @@
+//! A fixed-capacity in-memory pipe.
+//!
+//! [`RingBuffer`] is a byte queue over a fixed block of memory, either a
+//! `[u8; N]` or a heap allocation, so a producer can never make it grow. It
+//! implements [`Write`] at the back and [`Read`] and [`BufRead`] at the
+//! front. Unlike `VecDeque<u8>`, [`fill_buf`](BufRead::fill_buf) returns all
+//! buffered data as one slice, compacting the buffer when the data wraps
+//! around its end.
+//!
+//! For producer/consumer stages, [`split`] turns a `RefCell<RingBuffer>` into
+//! a [`RingReader`] and a [`RingWriter`] that can be handed to different
+//! parts of a script.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use ckb_rust_std::io::{self, ring::{self, RingBuffer}, Read, Write};
+//! use core::cell::RefCell;
+//!
+//! fn main() -> io::Result<()> {
+//!     let ring = RefCell::new(RingBuffer::new([0u8; 256]));
+//!     let (mut reader, mut writer) = ring::split(&ring);
+//!     writer.write_all(b"decompressed bytes")?;
+//!     let mut buf = [0; 12];
+//!     reader.read_exact(&mut buf)?;
+//!     Ok(())
+//! }
+//! ```
+#[cfg(test)]
+mod tests;
+
+use crate::io::{self, const_io_error, BufRead, ErrorKind, Read, Write};
+#[cfg(feature = "alloc")]
+use alloc::{boxed::Box, vec};
+use core::cell::{Cell, RefCell};
+use core::{cmp, fmt};
+
+const FULL: io::Error = const_io_error!(ErrorKind::WouldBlock, "the ring buffer is full");
+
+const EMPTY: io::Error = const_io_error!(ErrorKind::WouldBlock, "the ring buffer is empty");
+
+const READER_DROPPED: io::Error = const_io_error!(
+    ErrorKind::BrokenPipe,
+    "the reading half of the ring buffer was dropped",
+);
+
+/// A fixed-capacity byte queue.
+///
+/// Writes append to the back of the queue and reads take from the front.
+/// Once the buffer is full, writes return `Ok(0)`, which makes
+/// [`write_all`](Write::write_all) fail with [`ErrorKind::WriteZero`], or,
+/// after [`set_would_block`](RingBuffer::set_would_block), an error of the
+/// kind [`ErrorKind::WouldBlock`]. Reading an empty buffer returns `Ok(0)`.
+pub struct RingBuffer<S> {
+    buf: S,
+    // Index of the first byte of data.
+    head: usize,
+    // Number of bytes of data, which may wrap around the end of `buf`.
+    len: usize,
+    would_block: bool,
+    // Set by the halves returned by `split` when they are dropped. They are
+    // `Cell`s so that dropping a half only needs a shared borrow.
+    reader_dropped: Cell<bool>,
+    writer_dropped: Cell<bool>,
+}
+
+impl<S: AsRef<[u8]> + AsMut<[u8]>> RingBuffer<S> {
+    /// Creates an empty ring buffer using `storage` as its memory, e.g. a
+    /// `[u8; N]`. The capacity is the length of `storage`.
+    pub const fn new(storage: S) -> RingBuffer<S> {
+        RingBuffer {
+            buf: storage,
+            head: 0,
+            len: 0,
+            would_block: false,
+            reader_dropped: Cell::new(false),
+            writer_dropped: Cell::new(false),
+        }
+    }
+
+    /// Returns the number of bytes the ring buffer can hold.
+    pub fn capacity(&self) -> usize {
+        self.buf.as_ref().len()
+    }
+
+    /// Returns the number of buffered bytes.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if no bytes are buffered.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns `true` if no more bytes can be written.
+    pub fn is_full(&self) -> bool {
+        self.len == self.capacity()
+    }
+
+    /// Returns the number of bytes that can be written before the buffer is
+    /// full.
+    pub fn remaining_capacity(&self) -> usize {
+        self.capacity() - self.len
+    }
+
+    /// Sets whether writing to a full buffer fails with
+    /// [`ErrorKind::WouldBlock`] instead of returning `Ok(0)`.
+    pub fn set_would_block(&mut self, would_block: bool) {
+        self.would_block = would_block;
+    }
+
+    /// Discards all buffered bytes.
+    pub fn clear(&mut self) {
+        self.head = 0;
+        self.len = 0;
+    }
+
+    /// Returns the buffered bytes as two slices, in order. The second slice
+    /// is only non-empty if the data wraps around the end of the buffer.
+    pub fn as_slices(&self) -> (&[u8], &[u8]) {
+        let buf = self.buf.as_ref();
+        let first = cmp::min(self.len, buf.len() - self.head);
+        (&buf[self.head..self.head + first], &buf[..self.len - first])
+    }
+
+    /// Moves the buffered bytes to the start of the buffer if they wrap
+    /// around its end, and returns them as one slice.
+    pub fn make_contiguous(&mut self) -> &[u8] {
+        let cap = self.capacity();
+        if self.head + self.len > cap {
+            self.buf.as_mut().rotate_left(self.head);
+            self.head = 0;
+        }
+        &self.buf.as_ref()[self.head..self.head + self.len]
+    }
+
+    /// Consumes the ring buffer, returning its storage.
+    pub fn into_inner(self) -> S {
+        self.buf
+    }
+
+    // Copies as much of `data` as fits to the back of the queue.
+    fn push(&mut self, data: &[u8]) -> usize {
+        let cap = self.capacity();
+        let n = cmp::min(data.len(), cap - self.len);
+        if n == 0 {
+            return 0;
+        }
+        if self.len == 0 {
+            self.head = 0;
+        }
+        let tail = (self.head + self.len) % cap;
+        let first = cmp::min(n, cap - tail);
+        let buf = self.buf.as_mut();
+        buf[tail..tail + first].copy_from_slice(&data[..first]);
+        buf[..n - first].copy_from_slice(&data[first..n]);
+        self.len += n;
+        n
+    }
+
+    // Copies as many bytes as fit in `out` from the front of the queue.
+    fn pop(&mut self, out: &mut [u8]) -> usize {
+        let (front, back) = self.as_slices();
+        let n = cmp::min(out.len(), self.len);
+        let first = cmp::min(n, front.len());
+        out[..first].copy_from_slice(&front[..first]);
+        out[first..n].copy_from_slice(&back[..n - first]);
+        self.consume(n);
+        n
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl RingBuffer<Box<[u8]>> {
+    /// Creates an empty ring buffer holding `capacity` bytes on the heap.
+    pub fn with_capacity(capacity: usize) -> RingBuffer<Box<[u8]>> {
+        RingBuffer::new(vec![0; capacity].into_boxed_slice())
+    }
+}
+
+impl<S: AsRef<[u8]> + AsMut<[u8]>> Read for RingBuffer<S> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        Ok(self.pop(buf))
+    }
+}
+
+impl<S: AsRef<[u8]> + AsMut<[u8]>> BufRead for RingBuffer<S> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        Ok(self.make_contiguous())
+    }
+
+    fn consume(&mut self, amt: usize) {
+        let amt = cmp::min(amt, self.len);
+        self.len -= amt;
+        self.head = if self.len == 0 {
+            0
+        } else {
+            (self.head + amt) % self.capacity()
+        };
+    }
+}
+
+impl<S: AsRef<[u8]> + AsMut<[u8]>> Write for RingBuffer<S> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self.push(buf) {
+            0 if !buf.is_empty() && self.would_block => Err(FULL),
+            n => Ok(n),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<S: AsRef<[u8]> + AsMut<[u8]>> fmt::Debug for RingBuffer<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RingBuffer")
+            .field("len", &self.len)
+            .field("capacity", &self.capacity())
+            .finish_non_exhaustive()
+    }
+}
+
+/// The reading half of a ring buffer, created by [`split`].
+///
+/// Reading an empty buffer fails with [`ErrorKind::WouldBlock`] while the
+/// [`RingWriter`] is alive, and returns `Ok(0)` once it has been dropped.
+pub struct RingReader<'a, S: AsRef<[u8]> + AsMut<[u8]>> {
+    ring: &'a RefCell<RingBuffer<S>>,
+}
+
+/// The writing half of a ring buffer, created by [`split`].
+///
+/// Writing to a full buffer behaves as for [`RingBuffer`]. Once the
+/// [`RingReader`] has been dropped, writes fail with
+/// [`ErrorKind::BrokenPipe`].
+pub struct RingWriter<'a, S: AsRef<[u8]> + AsMut<[u8]>> {
+    ring: &'a RefCell<RingBuffer<S>>,
+}
+
+/// Splits a shared ring buffer into a reading and a writing half.
+///
+/// Both halves borrow the `RefCell` only for the duration of each call, so
+/// the ring buffer can still be inspected in between. Dropping a half only
+/// borrows it immutably, and if it is mutably borrowed at that point, the
+/// other half is not told about the drop.
+///
+/// # Panics
+///
+/// Panics if the `RefCell` is mutably borrowed.
+pub fn split<S: AsRef<[u8]> + AsMut<[u8]>>(
+    ring: &RefCell<RingBuffer<S>>,
+) -> (RingReader<'_, S>, RingWriter<'_, S>) {
+    let inner = ring.borrow();
+    inner.reader_dropped.set(false);
+    inner.writer_dropped.set(false);
+    (RingReader { ring }, RingWriter { ring })
+}
+
+impl<S: AsRef<[u8]> + AsMut<[u8]>> Read for RingReader<'_, S> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut ring = self.ring.borrow_mut();
+        match ring.pop(buf) {
+            0 if !buf.is_empty() && !ring.writer_dropped.get() => Err(EMPTY),
+            n => Ok(n),
+        }
+    }
+}
+
+impl<S: AsRef<[u8]> + AsMut<[u8]>> Drop for RingReader<'_, S> {
+    fn drop(&mut self) {
+        if let Ok(ring) = self.ring.try_borrow() {
+            ring.reader_dropped.set(true);
+        }
+    }
+}
+
+impl<S: AsRef<[u8]> + AsMut<[u8]>> fmt::Debug for RingReader<'_, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RingReader").finish_non_exhaustive()
+    }
+}
+
+impl<S: AsRef<[u8]> + AsMut<[u8]>> Write for RingWriter<'_, S> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut ring = self.ring.borrow_mut();
+        if ring.reader_dropped.get() {
+            return Err(READER_DROPPED);
+        }
+        ring.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<S: AsRef<[u8]> + AsMut<[u8]>> Drop for RingWriter<'_, S> {
+    fn drop(&mut self) {
+        if let Ok(ring) = self.ring.try_borrow() {
+            ring.writer_dropped.set(true);
+        }
+    }
+}
+
+impl<S: AsRef<[u8]> + AsMut<[u8]>> fmt::Debug for RingWriter<'_, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RingWriter").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,178 @@
+use super::*;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+#[test]
+fn read_write() {
+    let mut ring = RingBuffer::new([0u8; 8]);
+    assert_eq!(ring.capacity(), 8);
+    assert!(ring.is_empty());
+    assert_eq!(ring.write(b"hello").unwrap(), 5);
+    assert_eq!(ring.len(), 5);
+    assert_eq!(ring.remaining_capacity(), 3);
+
+    let mut buf = [0; 3];
+    assert_eq!(ring.read(&mut buf).unwrap(), 3);
+    assert_eq!(&buf, b"hel");
+    assert_eq!(ring.read(&mut buf).unwrap(), 2);
+    assert_eq!(&buf[..2], b"lo");
+    assert_eq!(ring.read(&mut buf).unwrap(), 0);
+}
+
+#[test]
+fn full_buffer() {
+    let mut ring = RingBuffer::new([0u8; 4]);
+    assert_eq!(ring.write(b"abcdef").unwrap(), 4);
+    assert!(ring.is_full());
+    assert_eq!(ring.write(b"g").unwrap(), 0);
+    assert_eq!(ring.write(b"").unwrap(), 0);
+    let err = ring.write_all(b"g").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::WriteZero);
+
+    ring.set_would_block(true);
+    let err = ring.write(b"g").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::WouldBlock);
+    assert_eq!(ring.write(b"").unwrap(), 0);
+    assert_eq!(ring.as_slices(), (&b"abcd"[..], &b""[..]));
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn wrap_around() {
+    let mut ring = RingBuffer::new([0u8; 6]);
+    ring.write_all(b"abcd").unwrap();
+    ring.consume(3);
+    // "d" sits at index 3, so "efghi" wraps around the end.
+    ring.write_all(b"efghi").unwrap();
+    assert!(ring.is_full());
+    assert_eq!(ring.as_slices(), (&b"def"[..], &b"ghi"[..]));
+
+    let mut out = Vec::new();
+    ring.read_to_end(&mut out).unwrap();
+    assert_eq!(out, b"defghi");
+    assert!(ring.is_empty());
+}
+
+#[test]
+fn fill_buf_is_contiguous() {
+    let mut ring = RingBuffer::new([0u8; 5]);
+    ring.write_all(b"abcd").unwrap();
+    ring.consume(2);
+    ring.write_all(b"efg").unwrap();
+    assert_eq!(ring.as_slices(), (&b"cde"[..], &b"fg"[..]));
+    assert_eq!(ring.fill_buf().unwrap(), b"cdefg");
+    assert_eq!(ring.as_slices(), (&b"cdefg"[..], &b""[..]));
+    ring.consume(4);
+    assert_eq!(ring.fill_buf().unwrap(), b"g");
+    ring.consume(10);
+    assert_eq!(ring.fill_buf().unwrap(), b"");
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn buf_read_lines() {
+    let mut ring = RingBuffer::with_capacity(16);
+    let mut lines = Vec::new();
+    for chunk in [&b"one\ntw"[..], b"o\nthree\n", b"four\n"] {
+        ring.write_all(chunk).unwrap();
+        let mut line = Vec::new();
+        while ring.fill_buf().unwrap().contains(&b'\n') {
+            line.clear();
+            ring.read_until(b'\n', &mut line).unwrap();
+            lines.push(line.clone());
+        }
+    }
+    assert_eq!(lines, [&b"one\n"[..], b"two\n", b"three\n", b"four\n"]);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn zero_capacity() {
+    let mut ring = RingBuffer::with_capacity(0);
+    assert_eq!(ring.write(b"a").unwrap(), 0);
+    assert_eq!(ring.read(&mut [0; 1]).unwrap(), 0);
+    assert_eq!(ring.fill_buf().unwrap(), b"");
+}
+
+#[test]
+fn split_halves() {
+    let ring = RefCell::new(RingBuffer::new([0u8; 4]));
+    let (mut reader, mut writer) = split(&ring);
+
+    let mut buf = [0; 8];
+    let err = reader.read(&mut buf).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::WouldBlock);
+
+    assert_eq!(writer.write(b"abcdef").unwrap(), 4);
+    assert_eq!(ring.borrow().len(), 4);
+    assert_eq!(reader.read(&mut buf[..2]).unwrap(), 2);
+    assert_eq!(writer.write(b"ef").unwrap(), 2);
+    assert_eq!(reader.read(&mut buf).unwrap(), 4);
+    assert_eq!(&buf[..4], b"cdef");
+
+    drop(writer);
+    assert_eq!(reader.read(&mut buf).unwrap(), 0);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn split_reader_dropped() {
+    let ring = RefCell::new(RingBuffer::with_capacity(4));
+    ring.borrow_mut().set_would_block(true);
+    let (reader, mut writer) = split(&ring);
+    writer.write_all(b"abcd").unwrap();
+    let err = writer.write(b"e").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::WouldBlock);
+
+    drop(reader);
+    let err = writer.write(b"e").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::BrokenPipe);
+    drop(writer);
+
+    // Splitting again starts with both halves alive.
+    let (_reader, mut writer) = split(&ring);
+    ring.borrow_mut().clear();
+    assert_eq!(writer.write(b"e").unwrap(), 1);
+}
+
+#[test]
+fn split_drop_while_borrowed() {
+    let ring = RefCell::new(RingBuffer::new([0u8; 4]));
+    let (reader, mut writer) = split(&ring);
+    let inspect = ring.borrow();
+    drop(reader);
+    assert_eq!(inspect.len(), 0);
+    drop(inspect);
+    let err = writer.write(b"a").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::BrokenPipe);
+
+    // A mutable borrow hides the drop instead of panicking.
+    let (mut reader, writer) = split(&ring);
+    let inspect = ring.borrow_mut();
+    drop(writer);
+    drop(inspect);
+    let err = reader.read(&mut [0; 1]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::WouldBlock);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn producer_consumer() {
+    let input: Vec<u8> = (0..1000u32).map(|i| (i % 251) as u8).collect();
+    let ring = RefCell::new(RingBuffer::new([0u8; 7]));
+    let (mut reader, mut writer) = split(&ring);
+    let mut output = Vec::new();
+    let mut buf = [0; 3];
+    let mut rest = &input[..];
+    while !rest.is_empty() {
+        let n = writer.write(&rest[..rest.len().min(5)]).unwrap();
+        rest = &rest[n..];
+        match reader.read(&mut buf) {
+            Ok(n) => output.extend_from_slice(&buf[..n]),
+            Err(e) => assert_eq!(e.kind(), ErrorKind::WouldBlock),
+        }
+    }
+    drop(writer);
+    reader.read_to_end(&mut output).unwrap();
+    assert_eq!(output, input);
+}
@@ -0,0 +1,383 @@
+//! Standard output and error for CKB scripts.
+//!
+//! CKB-VM has no file descriptors for stdout or stderr; the only output
+//! channel is the `ckb_debug` syscall, which prints one NUL-terminated string
+//! per call. [`Stdout`] and [`Stderr`] assemble output into lines of at most
+//! [`MAX_LINE_LEN`] bytes and hand every line, without its trailing newline,
+//! to a [`DebugSink`].
+//! Interior NUL bytes, which would otherwise truncate the message, are
+//! escaped as `\0`.
+//!
+//! The [`print!`], [`println!`], [`eprint!`] and [`eprintln!`] macros write to
+//! [`stdout()`] and [`stderr()`], so code ported from `std` keeps working.
+//! As with `std`, all handles returned by [`stdout()`] share one line buffer,
+//! so `print!("ab"); println!("123")` emits the single line `ab123`. The
+//! buffer is a static, so printing does not allocate. A partial
+//! line is only emitted once it is completed or [flushed](Write::flush), so a
+//! script whose output may end without a newline should call
+//! `stdout().flush()` before it returns.
+//!
+//! By default, output goes to the `ckb_debug` syscall (and is discarded
+//! when not running on CKB-VM). [`set_sink`] redirects the output of
+//! [`stdout()`] and [`stderr()`], and [`Capture`] collects the lines written
+//! through a handle created with [`Stdout::with_sink`], which is convenient
+//! in tests.
+//!
+//! [`print!`]: crate::print
+//! [`println!`]: crate::println
+//! [`eprint!`]: crate::eprint
+//! [`eprintln!`]: crate::eprintln
+#[cfg(test)]
+mod tests;
+
+use crate::io::{self, ckb::syscalls, Write};
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cell::UnsafeCell;
+use core::ffi::CStr;
+use core::fmt;
+use core::sync::atomic::{AtomicBool, AtomicPtr, Ordering};
+
+/// A destination for lines of debug output.
+pub trait DebugSink {
+    /// Emits one line, without its trailing newline.
+    fn debug(&mut self, line: &CStr);
+}
+
+impl<S: DebugSink + ?Sized> DebugSink for &mut S {
+    #[inline]
+    fn debug(&mut self, line: &CStr) {
+        (**self).debug(line)
+    }
+}
+
+/// The `ckb_debug` syscall.
+///
+/// Off CKB-VM, lines are silently discarded.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DebugSyscall;
+
+impl DebugSink for DebugSyscall {
+    fn debug(&mut self, line: &CStr) {
+        // There is nowhere to report a failure to print.
+        let _ = syscalls::debug(line);
+    }
+}
+
+// A `fn(&CStr)` set by `set_sink`, or null for `DebugSyscall`.
+static SINK: AtomicPtr<()> = AtomicPtr::new(core::ptr::null_mut());
+
+/// Redirects the output of [`stdout()`] and [`stderr()`] to `sink`,
+/// returning the previous sink, if any.
+///
+/// Passing `None` restores the `ckb_debug` syscall.
+pub fn set_sink(sink: Option<fn(&CStr)>) -> Option<fn(&CStr)> {
+    let new = sink.map_or(core::ptr::null_mut(), |f| f as *mut ());
+    let old = SINK.swap(new, Ordering::AcqRel);
+    // SAFETY: `SINK` only ever holds null or a `fn(&CStr)`.
+    (!old.is_null()).then(|| unsafe { core::mem::transmute::<*mut (), fn(&CStr)>(old) })
+}
+
+/// The sink selected by [`set_sink`], used by [`stdout()`] and [`stderr()`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GlobalSink;
+
+impl DebugSink for GlobalSink {
+    fn debug(&mut self, line: &CStr) {
+        let sink = SINK.load(Ordering::Acquire);
+        if sink.is_null() {
+            DebugSyscall.debug(line)
+        } else {
+            // SAFETY: `SINK` only ever holds null or a `fn(&CStr)`.
+            let sink = unsafe { core::mem::transmute::<*mut (), fn(&CStr)>(sink) };
+            sink(line)
+        }
+    }
+}
+
+/// A [`DebugSink`] which records every line, for asserting on output in
+/// tests.
+///
+/// # Examples
+///
+/// ```no_run
+/// use ckb_rust_std::io::{stdio::{Capture, Stdout}, Write};
+///
+/// let mut capture = Capture::new();
+/// let mut out = Stdout::with_sink(&mut capture);
+/// writeln!(out, "hello {}", 42).unwrap();
+/// drop(out);
+/// assert_eq!(capture.lines(), ["hello 42"]);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Capture {
+    lines: Vec<String>,
+}
+
+impl Capture {
+    /// Creates an empty `Capture`.
+    pub fn new() -> Capture {
+        Capture::default()
+    }
+
+    /// Returns the lines captured so far, with NULs still escaped.
+    pub fn lines(&self) -> &[String] {
+        &self.lines
+    }
+
+    /// Consumes this `Capture`, returning the captured lines.
+    pub fn into_lines(self) -> Vec<String> {
+        self.lines
+    }
+}
+
+impl DebugSink for Capture {
+    fn debug(&mut self, line: &CStr) {
+        self.lines
+            .push(String::from_utf8_lossy(line.to_bytes()).into_owned());
+    }
+}
+
+/// The longest line, in bytes after escaping NULs, that is emitted in one
+/// piece. A longer line is emitted in pieces of this size.
+pub const MAX_LINE_LEN: usize = 1024;
+
+/// A line being assembled for a `DebugSink`, kept NUL-escaped and with room
+/// for its terminator.
+struct Line {
+    buf: [u8; MAX_LINE_LEN + 1],
+    len: usize,
+}
+
+impl Line {
+    const fn new() -> Line {
+        Line {
+            buf: [0; MAX_LINE_LEN + 1],
+            len: 0,
+        }
+    }
+
+    fn write(&mut self, sink: &mut impl DebugSink, buf: &[u8]) {
+        for &byte in buf {
+            match byte {
+                b'\n' => self.emit(sink),
+                0 => self.push(sink, b"\\0"),
+                byte => self.push(sink, &[byte]),
+            }
+        }
+    }
+
+    // Emits the line first if `bytes` do not fit, so an escape is never split.
+    fn push(&mut self, sink: &mut impl DebugSink, bytes: &[u8]) {
+        if self.len + bytes.len() > MAX_LINE_LEN {
+            self.emit(sink);
+        }
+        self.buf[self.len..self.len + bytes.len()].copy_from_slice(bytes);
+        self.len += bytes.len();
+    }
+
+    fn emit(&mut self, sink: &mut impl DebugSink) {
+        self.buf[self.len] = 0;
+        let line =
+            CStr::from_bytes_with_nul(&self.buf[..=self.len]).expect("interior NULs are escaped");
+        sink.debug(line);
+        self.len = 0;
+    }
+
+    fn flush(&mut self, sink: &mut impl DebugSink) {
+        if self.len > 0 {
+            self.emit(sink);
+        }
+    }
+}
+
+/// A value shared by all handles, such as the partial line of [`stdout()`].
+struct Locked<T> {
+    locked: AtomicBool,
+    value: UnsafeCell<T>,
+}
+
+// SAFETY: `value` is only accessed while holding `locked`.
+unsafe impl<T: Send> Sync for Locked<T> {}
+
+impl<T> Locked<T> {
+    const fn new(value: T) -> Locked<T> {
+        Locked {
+            locked: AtomicBool::new(false),
+            value: UnsafeCell::new(value),
+        }
+    }
+
+    fn with<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        struct Unlock<'a>(&'a AtomicBool);
+
+        impl Drop for Unlock<'_> {
+            fn drop(&mut self) {
+                self.0.store(false, Ordering::Release);
+            }
+        }
+
+        while self
+            .locked
+            .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            core::hint::spin_loop();
+        }
+        let _unlock = Unlock(&self.locked);
+        // SAFETY: holding `locked` gives exclusive access to `value`.
+        f(unsafe { &mut *self.value.get() })
+    }
+}
+
+static STDOUT_LINE: Locked<Line> = Locked::new(Line::new());
+static STDERR_LINE: Locked<Line> = Locked::new(Line::new());
+
+/// Where a handle keeps its partial line.
+enum LineSlot {
+    // A handle from `with_sink`, which emits its partial line when dropped.
+    Owned(Box<Line>),
+    // A handle from `stdout()` or `stderr()`. The lock is only held while
+    // one write is copied, so formatting arguments may print too.
+    Shared(&'static Locked<Line>),
+}
+
+macro_rules! std_stream {
+    ($name:ident, $ctor:ident, $shared:ident, $what:literal) => {
+        #[doc = concat!("A line-buffered handle to the ", $what, " stream.")]
+        ///
+        #[doc = concat!("Created by [`", stringify!($ctor), "()`], or by [`",
+                        stringify!($name), "::with_sink`] to write into another [`DebugSink`].")]
+        /// Every completed line is emitted immediately, and a line reaching
+        /// [`MAX_LINE_LEN`] bytes is emitted in pieces. A partial line is
+        /// emitted on [`flush`](Write::flush). When a handle is dropped, its
+        #[doc = concat!("partial line is kept for the next [`", stringify!($ctor),
+                        "()`] handle if it")]
+        /// came from there, and emitted otherwise.
+        pub struct $name<S: DebugSink = GlobalSink> {
+            sink: S,
+            line: LineSlot,
+        }
+
+        impl<S: DebugSink> $name<S> {
+            #[doc = concat!("Creates a `", stringify!($name), "` which emits lines to `sink`.")]
+            pub fn with_sink(sink: S) -> $name<S> {
+                $name {
+                    sink,
+                    line: LineSlot::Owned(Box::new(Line::new())),
+                }
+            }
+        }
+
+        impl<S: DebugSink> Write for $name<S> {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                match &mut self.line {
+                    LineSlot::Owned(line) => line.write(&mut self.sink, buf),
+                    LineSlot::Shared(shared) => shared.with(|line| line.write(&mut self.sink, buf)),
+                }
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                match &mut self.line {
+                    LineSlot::Owned(line) => line.flush(&mut self.sink),
+                    LineSlot::Shared(shared) => shared.with(|line| line.flush(&mut self.sink)),
+                }
+                Ok(())
+            }
+        }
+
+        impl<S: DebugSink> Drop for $name<S> {
+            fn drop(&mut self) {
+                if let LineSlot::Owned(line) = &mut self.line {
+                    line.flush(&mut self.sink);
+                }
+            }
+        }
+
+        impl<S: DebugSink> fmt::Debug for $name<S> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.debug_struct(stringify!($name)).finish_non_exhaustive()
+            }
+        }
+
+        #[doc = concat!("Returns a new handle to the ", $what, " stream.")]
+        ///
+        /// Output goes to the sink selected with [`set_sink`], the
+        /// `ckb_debug` syscall by default. This does not allocate.
+        pub fn $ctor() -> $name {
+            $name {
+                sink: GlobalSink,
+                line: LineSlot::Shared(&$shared),
+            }
+        }
+    };
+}
+
+std_stream!(Stdout, stdout, STDOUT_LINE, "standard output");
+std_stream!(Stderr, stderr, STDERR_LINE, "standard error");
+
+#[doc(hidden)]
+pub fn _print(args: fmt::Arguments<'_>) {
+    if let Err(e) = stdout().write_fmt(args) {
+        panic!("failed printing to stdout: {e}");
+    }
+}
+
+#[doc(hidden)]
+pub fn _eprint(args: fmt::Arguments<'_>) {
+    if let Err(e) = stderr().write_fmt(args) {
+        panic!("failed printing to stderr: {e}");
+    }
+}
+
+/// Prints to the standard output.
+///
+/// Equivalent to `std::print!`, writing to [`stdout()`](crate::io::stdout).
+/// Since CKB's debug output is line-oriented, text without a trailing newline
+/// is held back until a later call completes the line or stdout is flushed.
+#[macro_export]
+macro_rules! print {
+    ($($arg:tt)*) => {
+        $crate::io::stdio::_print(::core::format_args!($($arg)*))
+    };
+}
+
+/// Prints to the standard output, with a newline.
+///
+/// Equivalent to `std::println!`, writing to
+/// [`stdout()`](crate::io::stdout).
+#[macro_export]
+macro_rules! println {
+    () => {
+        $crate::print!("\n")
+    };
+    ($($arg:tt)*) => {
+        $crate::io::stdio::_print(::core::format_args!("{}\n", ::core::format_args!($($arg)*)))
+    };
+}
+
+/// Prints to the standard error.
+///
+/// Equivalent to `std::eprint!`, writing to [`stderr()`](crate::io::stderr).
+#[macro_export]
+macro_rules! eprint {
+    ($($arg:tt)*) => {
+        $crate::io::stdio::_eprint(::core::format_args!($($arg)*))
+    };
+}
+
+/// Prints to the standard error, with a newline.
+///
+/// Equivalent to `std::eprintln!`, writing to
+/// [`stderr()`](crate::io::stderr).
+#[macro_export]
+macro_rules! eprintln {
+    () => {
+        $crate::eprint!("\n")
+    };
+    ($($arg:tt)*) => {
+        $crate::io::stdio::_eprint(::core::format_args!("{}\n", ::core::format_args!($($arg)*)))
+    };
+}
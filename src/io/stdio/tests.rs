@@ -0,0 +1,93 @@
+use super::*;
+
+#[test]
+fn splits_lines() {
+    let mut capture = Capture::new();
+    let mut out = Stdout::with_sink(&mut capture);
+    out.write_all(b"one\ntwo\n\nthr").unwrap();
+    out.write_all(b"ee\nfour").unwrap();
+    drop(out);
+    assert_eq!(capture.lines(), ["one", "two", "", "three", "four"]);
+}
+
+#[test]
+fn buffers_partial_lines() {
+    let mut capture = Capture::new();
+    let mut err = Stderr::with_sink(&mut capture);
+    write!(err, "a").unwrap();
+    write!(err, "b").unwrap();
+    err.flush().unwrap();
+    write!(err, "c").unwrap();
+    writeln!(err, "d").unwrap();
+    drop(err);
+    assert_eq!(capture.lines(), ["ab", "cd"]);
+}
+
+#[test]
+fn escapes_nul() {
+    let mut capture = Capture::new();
+    let mut out = Stdout::with_sink(&mut capture);
+    out.write_all(b"a\0b\n\0\n").unwrap();
+    drop(out);
+    assert_eq!(capture.lines(), ["a\\0b", "\\0"]);
+}
+
+#[test]
+fn long_lines_are_split() {
+    let mut capture = Capture::new();
+    let mut out = Stdout::with_sink(&mut capture);
+    let long = [b'x'; MAX_LINE_LEN * 2 + 5];
+    out.write_all(&long[..1000]).unwrap();
+    out.write_all(&long[1000..]).unwrap();
+    out.write_all(b"\n").unwrap();
+    drop(out);
+    let lines = capture.into_lines();
+    let lens: Vec<usize> = lines.iter().map(String::len).collect();
+    assert_eq!(lens, [MAX_LINE_LEN, MAX_LINE_LEN, 5]);
+}
+
+#[test]
+fn escapes_are_not_split() {
+    let mut capture = Capture::new();
+    let mut out = Stdout::with_sink(&mut capture);
+    out.write_all(&[b'x'; MAX_LINE_LEN - 1]).unwrap();
+    out.write_all(b"\0\n").unwrap();
+    drop(out);
+    let lines = capture.into_lines();
+    assert_eq!(lines[0].len(), MAX_LINE_LEN - 1);
+    assert_eq!(lines[1], "\\0");
+}
+
+static CAPTURED: Locked<Vec<u8>> = Locked::new(Vec::new());
+
+fn capturing_sink(line: &CStr) {
+    CAPTURED.with(|captured| {
+        captured.extend_from_slice(line.to_bytes());
+        captured.push(b'\n');
+    });
+}
+
+// This is the only test using the global sink, as tests run concurrently.
+#[test]
+fn macros_use_global_sink() {
+    assert!(set_sink(Some(capturing_sink)).is_none());
+    crate::print!("ab");
+    crate::println!("{}", 123);
+    crate::println!();
+    crate::eprint!("x{}", 'y');
+    crate::eprintln!("z");
+    write!(stdout(), "fo").unwrap();
+    writeln!(stdout(), "ur").unwrap();
+    crate::print!("tail");
+    assert_eq!(
+        CAPTURED.with(|captured| captured.len()),
+        "ab123\n\nxyz\nfour\n".len()
+    );
+    stdout().flush().unwrap();
+    assert!(set_sink(None).is_some());
+    let captured = CAPTURED.with(core::mem::take);
+    assert_eq!(
+        String::from_utf8(captured).unwrap(),
+        "ab123\n\nxyz\nfour\ntail\n"
+    );
+}
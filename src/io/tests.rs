@@ -1,12 +1,23 @@
+#[cfg(feature = "alloc")]
+use alloc::collections::VecDeque;
+#[cfg(feature = "alloc")]
 use alloc::string::{String, ToString};
+#[cfg(feature = "alloc")]
 use alloc::vec;
+#[cfg(feature = "alloc")]
 use alloc::vec::Vec;
+use core::ops::Deref;
 
 use super::{BorrowedBuf, Cursor, SeekFrom};
 use crate::io;
-use crate::io::{cmp, error::ErrorKind, BufRead, BufReader, Read, Seek};
+use crate::io::{buf_size_from_env, IoSlice, IoSliceMut};
+#[cfg(feature = "alloc")]
+use crate::io::{cmp, BufReader, Write};
+use crate::io::{error::ErrorKind, BufRead, Read, Seek};
+#[cfg(feature = "alloc")]
 use crate::io::{Error, DEFAULT_BUF_SIZE};
 
+#[cfg(feature = "alloc")]
 #[test]
 #[cfg_attr(target_os = "emscripten", ignore)]
 fn read_until() {
@@ -27,6 +38,7 @@ fn read_until() {
     assert_eq!(v, []);
 }
 
+#[cfg(feature = "alloc")]
 #[test]
 fn skip_until() {
     let bytes: &[u8] = b"read\0ignore\0read\0ignore\0read\0ignore\0";
@@ -57,6 +69,7 @@ fn skip_until() {
     assert_eq!(skipped, 0);
 }
 
+#[cfg(feature = "alloc")]
 #[test]
 fn split() {
     let buf = Cursor::new(&b"12"[..]);
@@ -71,6 +84,7 @@ fn split() {
     assert!(s.next().is_none());
 }
 
+#[cfg(feature = "alloc")]
 #[test]
 fn read_line() {
     let mut buf = Cursor::new(&b"12"[..]);
@@ -90,6 +104,7 @@ fn read_line() {
     assert_eq!(v, "");
 }
 
+#[cfg(feature = "alloc")]
 #[test]
 fn lines() {
     let buf = Cursor::new(&b"12\r"[..]);
@@ -114,6 +129,7 @@ fn buf_read_has_data_left() {
     assert!(!buf.has_data_left().unwrap());
 }
 
+#[cfg(feature = "alloc")]
 #[test]
 fn read_to_end() {
     let mut c = Cursor::new(&b""[..]);
@@ -135,6 +151,7 @@ fn read_to_end() {
     assert_eq!(v, data);
 }
 
+#[cfg(feature = "alloc")]
 #[test]
 fn read_to_string() {
     let mut c = Cursor::new(&b""[..]);
@@ -238,6 +255,7 @@ fn borrowed_cursor_advance_overflow() {
     buf.unfilled().advance(usize::MAX);
 }
 
+#[cfg(feature = "alloc")]
 #[test]
 fn take_eof() {
     struct R;
@@ -259,6 +277,7 @@ fn take_eof() {
     assert_eq!(b"", R.take(0).fill_buf().unwrap());
 }
 
+#[cfg(feature = "alloc")]
 fn cmp_bufread<Br1: BufRead, Br2: BufRead>(mut br1: Br1, mut br2: Br2, exp: &[u8]) {
     let mut cat = Vec::new();
     loop {
@@ -282,9 +301,10 @@ fn cmp_bufread<Br1: BufRead, Br2: BufRead>(mut br1: Br1, mut br2: Br2, exp: &[u8
     }
     assert_eq!(br1.fill_buf().unwrap().len(), 0);
     assert_eq!(br2.fill_buf().unwrap().len(), 0);
-    assert_eq!(&cat[..], &exp[..])
+    assert_eq!(&cat[..], exp)
 }
 
+#[cfg(feature = "alloc")]
 #[test]
 fn chain_bufread() {
     let testdata = b"ABCDEFGHIJKL";
@@ -298,6 +318,7 @@ fn chain_bufread() {
     cmp_bufread(chain1, chain2, &testdata[..]);
 }
 
+#[cfg(feature = "alloc")]
 #[test]
 fn chain_splitted_char() {
     let chain = b"\xc3".chain(b"\xa9".as_slice());
@@ -309,6 +330,7 @@ fn chain_splitted_char() {
     assert_eq!(buf, "é\n");
 }
 
+#[cfg(feature = "alloc")]
 #[test]
 fn chain_zero_length_read_is_not_eof() {
     let a = b"A";
@@ -320,6 +342,7 @@ fn chain_zero_length_read_is_not_eof() {
     assert_eq!("AB", s);
 }
 
+#[cfg(feature = "alloc")]
 #[test]
 fn seek_len() -> io::Result<()> {
     let mut c = Cursor::new(vec![0; 15]);
@@ -339,6 +362,7 @@ fn seek_len() -> io::Result<()> {
     Ok(())
 }
 
+#[cfg(feature = "alloc")]
 #[test]
 fn seek_position() -> io::Result<()> {
     // All `asserts` are duplicated here to make sure the method does not
@@ -369,12 +393,190 @@ fn seek_position() -> io::Result<()> {
     Ok(())
 }
 
+#[test]
+fn take_seek() -> io::Result<()> {
+    let mut buf = Cursor::new(b"0123456789");
+    buf.set_position(2);
+    let mut take = buf.by_ref().take(4);
+    let mut buf1 = [0u8; 1];
+    let mut buf2 = [0u8; 2];
+    assert_eq!(take.stream_position()?, 0);
+    assert_eq!(take.stream_len()?, 4);
+
+    assert_eq!(take.seek(SeekFrom::Start(0))?, 0);
+    take.read_exact(&mut buf2)?;
+    assert_eq!(buf2, [b'2', b'3']);
+    assert_eq!(take.seek(SeekFrom::Start(1))?, 1);
+    take.read_exact(&mut buf2)?;
+    assert_eq!(buf2, [b'3', b'4']);
+    assert_eq!(take.seek(SeekFrom::Start(2))?, 2);
+    take.read_exact(&mut buf2)?;
+    assert_eq!(buf2, [b'4', b'5']);
+    assert_eq!(take.seek(SeekFrom::Start(3))?, 3);
+    take.read_exact(&mut buf1)?;
+    assert_eq!(buf1, [b'5']);
+    assert_eq!(take.seek(SeekFrom::Start(4))?, 4);
+    assert_eq!(take.read(&mut buf1)?, 0);
+
+    assert_eq!(take.seek(SeekFrom::End(0))?, 4);
+    assert_eq!(take.seek(SeekFrom::End(-1))?, 3);
+    take.read_exact(&mut buf1)?;
+    assert_eq!(buf1, [b'5']);
+    assert_eq!(take.seek(SeekFrom::End(-4))?, 0);
+    take.read_exact(&mut buf2)?;
+    assert_eq!(buf2, [b'2', b'3']);
+
+    assert_eq!(take.seek(SeekFrom::Current(0))?, 2);
+    assert_eq!(take.seek(SeekFrom::Current(-1))?, 1);
+    take.seek_relative(2)?;
+    assert_eq!(take.stream_position()?, 3);
+    take.read_exact(&mut buf1)?;
+    assert_eq!(buf1, [b'5']);
+
+    // Seeking outside of the window stops at its edges.
+    for (pos, clamped) in [
+        (SeekFrom::Start(5), 4),
+        (SeekFrom::End(1), 4),
+        (SeekFrom::End(-5), 0),
+        (SeekFrom::End(i64::MIN), 0),
+        (SeekFrom::Current(i64::MAX), 4),
+        (SeekFrom::Current(-5), 0),
+    ] {
+        assert_eq!(take.seek(pos)?, clamped);
+        assert_eq!(take.stream_position()?, clamped);
+        assert_eq!(take.limit(), 4 - clamped);
+    }
+    take.seek_relative(5)?;
+    assert_eq!(take.stream_position()?, 4);
+    assert_eq!(take.read(&mut buf1)?, 0);
+
+    // The inner reader moved along with the window.
+    take.seek(SeekFrom::Start(1))?;
+    assert_eq!(take.get_ref().position(), 3);
+
+    // A new limit starts a new window at the current position.
+    take.set_limit(2);
+    assert_eq!(take.stream_position()?, 0);
+    take.read_exact(&mut buf2)?;
+    assert_eq!(buf2, [b'3', b'4']);
+    take.rewind()?;
+    assert_eq!(take.get_ref().position(), 3);
+    Ok(())
+}
+
+#[test]
+fn take_seek_far() -> io::Result<()> {
+    // The window is larger than a single relative seek can cross.
+    let mut take = Cursor::new(b"0123").take(u64::MAX);
+    assert_eq!(take.seek(SeekFrom::End(0))?, u64::MAX);
+    assert_eq!(take.get_ref().position(), u64::MAX);
+    assert_eq!(take.limit(), 0);
+    assert_eq!(take.seek(SeekFrom::Start(1))?, 1);
+    assert_eq!(take.get_ref().position(), 1);
+    let mut buf = [0u8; 3];
+    take.read_exact(&mut buf)?;
+    assert_eq!(&buf, b"123");
+    Ok(())
+}
+
+#[test]
+fn take_seek_error_changes_nothing() {
+    struct Unseekable;
+
+    impl Read for Unseekable {
+        fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+            Ok(0)
+        }
+    }
+
+    impl Seek for Unseekable {
+        fn seek(&mut self, _: SeekFrom) -> io::Result<u64> {
+            Err(ErrorKind::Unsupported.into())
+        }
+    }
+
+    let mut take = Unseekable.take(u64::MAX);
+    for pos in [SeekFrom::Start(1), SeekFrom::End(0), SeekFrom::End(-1)] {
+        let err = take.seek(pos).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+        assert_eq!(take.limit(), u64::MAX);
+    }
+    // Staying in place needs no seek of the inner reader.
+    assert_eq!(take.seek(SeekFrom::Current(0)).unwrap(), 0);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn chain_seek() -> io::Result<()> {
+    let mut chain = Cursor::new(b"0123").chain(Cursor::new(b"456789"));
+    let mut buf = [0u8; 3];
+    assert_eq!(chain.stream_len()?, 10);
+    assert_eq!(chain.stream_position()?, 0);
+
+    // Reading across the boundary after seeking into the first half.
+    assert_eq!(chain.seek(SeekFrom::Start(2))?, 2);
+    chain.read_exact(&mut buf)?;
+    assert_eq!(&buf, b"234");
+    assert_eq!(chain.stream_position()?, 5);
+
+    // Back into the first half rewinds the second one.
+    assert_eq!(chain.seek(SeekFrom::Current(-4))?, 1);
+    let mut all = Vec::new();
+    chain.read_to_end(&mut all)?;
+    assert_eq!(all, b"123456789");
+    assert_eq!(chain.stream_position()?, 10);
+
+    assert_eq!(chain.seek(SeekFrom::End(-3))?, 7);
+    chain.read_exact(&mut buf)?;
+    assert_eq!(&buf, b"789");
+    assert_eq!(chain.seek(SeekFrom::Start(4))?, 4);
+    chain.read_exact(&mut buf)?;
+    assert_eq!(&buf, b"456");
+
+    // Past the end, reads return EOF as with a `Cursor`.
+    assert_eq!(chain.seek(SeekFrom::End(5))?, 15);
+    assert_eq!(chain.read(&mut buf)?, 0);
+
+    let err = chain.seek(SeekFrom::Current(-16)).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+
+    chain.rewind()?;
+    let mut lines = String::new();
+    chain.read_to_string(&mut lines)?;
+    assert_eq!(lines, "0123456789");
+    Ok(())
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn chain_of_takes_seek() -> io::Result<()> {
+    // Two fields of one source, joined and rewound as a unit.
+    let data = b"..abc..defg";
+    let mut first = Cursor::new(&data[..]);
+    first.set_position(2);
+    let mut second = Cursor::new(&data[..]);
+    second.set_position(7);
+    let mut chain = first.take(3).chain(second.take(4));
+    assert_eq!(chain.stream_len()?, 7);
+    chain.seek(SeekFrom::Start(1))?;
+    let mut s = String::new();
+    chain.read_to_string(&mut s)?;
+    assert_eq!(s, "bcdefg");
+    chain.rewind()?;
+    s.clear();
+    chain.read_to_string(&mut s)?;
+    assert_eq!(s, "abcdefg");
+    Ok(())
+}
+
 // A simple example reader which uses the default implementation of
 // read_to_end.
+#[cfg(feature = "alloc")]
 struct ExampleSliceReader<'a> {
     slice: &'a [u8],
 }
 
+#[cfg(feature = "alloc")]
 impl<'a> Read for ExampleSliceReader<'a> {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
         let len = cmp::min(self.slice.len(), buf.len());
@@ -384,6 +586,7 @@ impl<'a> Read for ExampleSliceReader<'a> {
     }
 }
 
+#[cfg(feature = "alloc")]
 #[test]
 fn test_read_to_end_capacity() -> io::Result<()> {
     let input = &b"foo"[..];
@@ -415,7 +618,7 @@ fn test_take_wrong_length() {
         }
     }
 
-    let mut buffer = vec![0; 4];
+    let mut buffer = [0; 4];
     let mut reader = LieAboutSize(true).take(4);
     // Primed the `Limit` by lying about the read size.
     let _ = reader.read(&mut buffer[..]);
@@ -453,6 +656,7 @@ fn cursor_read_exact_eof() {
     assert_eq!(buf.filled(), b"123456");
 }
 // Issue #120603
+#[cfg(feature = "alloc")]
 #[test]
 #[should_panic]
 fn read_buf_broken_read() {
@@ -468,6 +672,7 @@ fn read_buf_broken_read() {
     let _ = BufReader::new(MalformedRead).fill_buf();
 }
 
+#[cfg(feature = "alloc")]
 #[test]
 fn read_buf_full_read() {
     struct FullRead;
@@ -488,6 +693,7 @@ fn read_buf_full_read() {
 // Miri does not support signalling OOM
 #[cfg_attr(miri, ignore)]
 // 64-bit only to be sure the allocator will fail fast on an impossible to satsify size
+#[cfg(feature = "alloc")]
 #[cfg(target_pointer_width = "64")]
 fn try_oom_error() {
     let mut v = Vec::<u8>::new();
@@ -495,3 +701,377 @@ fn try_oom_error() {
     let io_err = io::Error::from(reserve_err);
     assert_eq!(io::ErrorKind::OutOfMemory, io_err.kind());
 }
+
+#[test]
+fn io_slice_mut_advance_slices() {
+    let mut buf1 = [1; 8];
+    let mut buf2 = [2; 16];
+    let mut buf3 = [3; 8];
+    let mut bufs = &mut [
+        IoSliceMut::new(&mut buf1),
+        IoSliceMut::new(&mut buf2),
+        IoSliceMut::new(&mut buf3),
+    ][..];
+
+    // Only in a single buffer..
+    IoSliceMut::advance_slices(&mut bufs, 1);
+    assert_eq!(bufs[0].deref(), [1; 7].as_ref());
+    assert_eq!(bufs[1].deref(), [2; 16].as_ref());
+    assert_eq!(bufs[2].deref(), [3; 8].as_ref());
+
+    // Removing a buffer, leaving others as is.
+    IoSliceMut::advance_slices(&mut bufs, 7);
+    assert_eq!(bufs[0].deref(), [2; 16].as_ref());
+    assert_eq!(bufs[1].deref(), [3; 8].as_ref());
+
+    // Removing a buffer and removing from the next buffer.
+    IoSliceMut::advance_slices(&mut bufs, 18);
+    assert_eq!(bufs[0].deref(), [3; 6].as_ref());
+}
+
+#[test]
+#[should_panic]
+fn io_slice_mut_advance_slices_empty_slice() {
+    let mut empty_bufs = &mut [][..];
+    IoSliceMut::advance_slices(&mut empty_bufs, 1);
+}
+
+#[test]
+#[should_panic]
+fn io_slice_mut_advance_slices_beyond_total_length() {
+    let mut buf1 = [1; 8];
+    let mut bufs = &mut [IoSliceMut::new(&mut buf1)][..];
+
+    IoSliceMut::advance_slices(&mut bufs, 9);
+    assert!(bufs.is_empty());
+}
+
+#[test]
+fn io_slice_advance_slices() {
+    let buf1 = [1; 8];
+    let buf2 = [2; 16];
+    let buf3 = [3; 8];
+    let mut bufs = &mut [
+        IoSlice::new(&buf1),
+        IoSlice::new(&buf2),
+        IoSlice::new(&buf3),
+    ][..];
+
+    // Only in a single buffer..
+    IoSlice::advance_slices(&mut bufs, 1);
+    assert_eq!(bufs[0].deref(), [1; 7].as_ref());
+    assert_eq!(bufs[1].deref(), [2; 16].as_ref());
+    assert_eq!(bufs[2].deref(), [3; 8].as_ref());
+
+    // Removing a buffer, leaving others as is.
+    IoSlice::advance_slices(&mut bufs, 7);
+    assert_eq!(bufs[0].deref(), [2; 16].as_ref());
+    assert_eq!(bufs[1].deref(), [3; 8].as_ref());
+
+    // Removing a buffer and removing from the next buffer.
+    IoSlice::advance_slices(&mut bufs, 18);
+    assert_eq!(bufs[0].deref(), [3; 6].as_ref());
+}
+
+#[test]
+#[should_panic]
+fn io_slice_advance_slices_beyond_total_length() {
+    let buf1 = [1; 8];
+    let mut bufs = &mut [IoSlice::new(&buf1)][..];
+
+    IoSlice::advance_slices(&mut bufs, 9);
+    assert!(bufs.is_empty());
+}
+
+/// Create a new writer that reads from at most `n_bufs` and reads
+/// `per_call` bytes (in total) per call to write.
+#[cfg(feature = "alloc")]
+fn test_writer(n_bufs: usize, per_call: usize) -> TestWriter {
+    TestWriter {
+        n_bufs,
+        per_call,
+        written: Vec::new(),
+    }
+}
+
+#[cfg(feature = "alloc")]
+struct TestWriter {
+    n_bufs: usize,
+    per_call: usize,
+    written: Vec<u8>,
+}
+
+#[cfg(feature = "alloc")]
+impl Write for TestWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.write_vectored(&[IoSlice::new(buf)])
+    }
+
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        let mut left = self.per_call;
+        let mut written = 0;
+        for buf in bufs.iter().take(self.n_bufs) {
+            let n = cmp::min(left, buf.len());
+            self.written.extend_from_slice(&buf[0..n]);
+            left -= n;
+            written += n;
+        }
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_writer_read_from_one_buf() {
+    let mut writer = test_writer(1, 2);
+
+    assert_eq!(writer.write(&[]).unwrap(), 0);
+    assert_eq!(writer.write_vectored(&[]).unwrap(), 0);
+
+    // Read at most 2 bytes.
+    assert_eq!(writer.write(&[1, 1, 1]).unwrap(), 2);
+    let bufs = &[IoSlice::new(&[2, 2, 2])];
+    assert_eq!(writer.write_vectored(bufs).unwrap(), 2);
+
+    // Only read from first buf.
+    let bufs = &[IoSlice::new(&[3]), IoSlice::new(&[4, 4])];
+    assert_eq!(writer.write_vectored(bufs).unwrap(), 1);
+
+    assert_eq!(writer.written, &[1, 1, 2, 2, 3]);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_write_all_vectored() {
+    #[rustfmt::skip] // Becomes unreadable otherwise.
+    let tests: Vec<(_, &'static [u8])> = vec![
+        (vec![], &[]),
+        (vec![IoSlice::new(&[]), IoSlice::new(&[])], &[]),
+        (vec![IoSlice::new(&[1])], &[1]),
+        (vec![IoSlice::new(&[1, 2])], &[1, 2]),
+        (vec![IoSlice::new(&[1, 2, 3])], &[1, 2, 3]),
+        (vec![IoSlice::new(&[1]), IoSlice::new(&[2])], &[1, 2]),
+        (vec![IoSlice::new(&[1]), IoSlice::new(&[2, 2])], &[1, 2, 2]),
+        (vec![IoSlice::new(&[1, 1]), IoSlice::new(&[2, 2, 2])], &[1, 1, 2, 2, 2]),
+        (vec![IoSlice::new(&[1, 1, 1]), IoSlice::new(&[2, 2, 2, 2])], &[1, 1, 1, 2, 2, 2, 2]),
+        (vec![IoSlice::new(&[1]), IoSlice::new(&[2]), IoSlice::new(&[3])], &[1, 2, 3]),
+        (vec![IoSlice::new(&[1]), IoSlice::new(&[2, 2]), IoSlice::new(&[3, 3, 3])], &[1, 2, 2, 3, 3, 3]),
+        (vec![IoSlice::new(&[1, 1, 1]), IoSlice::new(&[2, 2, 2]), IoSlice::new(&[3, 3, 3])], &[1, 1, 1, 2, 2, 2, 3, 3, 3]),
+    ];
+
+    let writer_configs = &[(1, 1), (1, 2), (1, 3), (2, 2), (2, 3), (3, 3)];
+
+    for (n_bufs, per_call) in writer_configs.iter().copied() {
+        for (mut input, wanted) in tests.clone().into_iter() {
+            let mut writer = test_writer(n_bufs, per_call);
+            assert!(writer.write_all_vectored(&mut input).is_ok());
+            assert_eq!(&*writer.written, wanted);
+        }
+    }
+}
+
+#[test]
+fn chain_read_vectored() {
+    let mut c = (&[1, 2][..]).chain(&[3, 4, 5][..]);
+    let mut buf1 = [0; 3];
+    let mut buf2 = [0; 3];
+    let mut bufs = [IoSliceMut::new(&mut buf1), IoSliceMut::new(&mut buf2)];
+    assert!(c.is_read_vectored());
+    assert_eq!(c.read_vectored(&mut bufs).unwrap(), 2);
+    assert_eq!(c.read_vectored(&mut bufs).unwrap(), 3);
+    assert_eq!(c.read_vectored(&mut bufs).unwrap(), 0);
+    assert_eq!(buf1, [3, 4, 5]);
+    assert_eq!(buf2, [0, 0, 0]);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn vec_deque_read_vectored() {
+    let mut deque = VecDeque::with_capacity(8);
+    deque.extend([0, 0, 0, 0, 0, 1]);
+    for _ in 0..5 {
+        deque.pop_front();
+    }
+    // Wrap the contents around the end of the ring buffer.
+    deque.extend([2, 3, 4, 5]);
+    assert!(!deque.as_slices().1.is_empty());
+
+    let mut buf1 = [0; 2];
+    let mut buf2 = [0; 4];
+    let mut bufs = [IoSliceMut::new(&mut buf1), IoSliceMut::new(&mut buf2)];
+    assert_eq!(deque.read_vectored(&mut bufs).unwrap(), 5);
+    assert!(deque.is_empty());
+    assert_eq!(buf1, [1, 2]);
+    assert_eq!(buf2, [3, 4, 5, 0]);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn vec_deque_read_vectored_contiguous() {
+    let mut deque = VecDeque::from([1, 2, 3, 4, 5]);
+    assert!(deque.as_slices().1.is_empty());
+
+    let mut buf1 = [0; 2];
+    let mut buf2 = [0; 2];
+    let mut bufs = [IoSliceMut::new(&mut buf1), IoSliceMut::new(&mut buf2)];
+    assert_eq!(deque.read_vectored(&mut bufs).unwrap(), 4);
+    assert_eq!(deque, [5]);
+    assert_eq!(buf1, [1, 2]);
+    assert_eq!(buf2, [3, 4]);
+}
+
+#[test]
+fn buf_size_overrides() {
+    assert_eq!(buf_size_from_env(None, 1024), 1024);
+    assert_eq!(buf_size_from_env(Some("65536"), 1024), 65536);
+    assert_eq!(buf_size_from_env(Some("007"), 1024), 7);
+}
+
+#[test]
+#[should_panic = "must be decimal integers"]
+fn buf_size_override_not_a_number() {
+    buf_size_from_env(Some("64K"), 1024);
+}
+
+#[test]
+#[should_panic = "must be positive"]
+fn buf_size_override_zero() {
+    buf_size_from_env(Some("0"), 1024);
+}
+
+#[test]
+#[should_panic = "too large"]
+fn buf_size_override_overflow() {
+    buf_size_from_env(Some("99999999999999999999999"), 1024);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn read_to_end_limited() {
+    let mut v = b"prefix".to_vec();
+    let mut r: &[u8] = b"12345";
+    assert_eq!(r.read_to_end_limited(&mut v, 5).unwrap(), 5);
+    assert_eq!(v, b"prefix12345");
+
+    let mut r: &[u8] = b"123456789";
+    let err = r.read_to_end_limited(&mut v, 5).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::FileTooLarge);
+    assert_eq!(v, b"prefix12345");
+    // Exactly `limit + 1` bytes were consumed.
+    assert_eq!(r, b"789");
+
+    let mut r = Cursor::new(Vec::new());
+    assert_eq!(r.read_to_end_limited(&mut v, 0).unwrap(), 0);
+    let mut r = Cursor::new(b"x");
+    assert!(r.read_to_end_limited(&mut v, 0).is_err());
+    let mut r = io::repeat(b'a');
+    assert!(r.read_to_end_limited(&mut v, 3000).is_err());
+    assert_eq!(v, b"prefix12345");
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn read_to_string_limited() {
+    let mut s = String::from("> ");
+    let mut r: &[u8] = b"hello";
+    assert_eq!(r.read_to_string_limited(&mut s, 8).unwrap(), 5);
+    assert_eq!(s, "> hello");
+
+    let mut r: &[u8] = b"hello world";
+    let err = r.read_to_string_limited(&mut s, 8).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::FileTooLarge);
+    assert_eq!(s, "> hello");
+
+    let mut r: &[u8] = b"\xff";
+    let err = r.read_to_string_limited(&mut s, 8).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    assert_eq!(s, "> hello");
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn read_until_limited() {
+    // Small buffer capacity, so items span several `fill_buf` calls.
+    let mut r = BufReader::with_capacity(2, &b"abc,defgh,ij"[..]);
+    let mut v = Vec::new();
+    assert_eq!(r.read_until_limited(b',', &mut v, 4).unwrap(), 4);
+    assert_eq!(v, b"abc,");
+
+    // The delimiter counts towards the limit.
+    v.clear();
+    let err = r.read_until_limited(b',', &mut v, 5).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::FileTooLarge);
+    assert!(v.is_empty());
+    // Exactly `limit` bytes were consumed; the rest of the item can be
+    // skipped.
+    assert_eq!(r.skip_until(b',').unwrap(), 1);
+
+    // An item ending at EOF may use the whole limit.
+    assert_eq!(r.read_until_limited(b',', &mut v, 2).unwrap(), 2);
+    assert_eq!(v, b"ij");
+    assert_eq!(r.read_until_limited(b',', &mut v, 0).unwrap(), 0);
+
+    let mut r = Cursor::new(&b"12345\n"[..]);
+    assert!(r.read_until_limited(b'\n', &mut v, 5).is_err());
+    assert_eq!(r.position(), 5);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn read_line_limited() {
+    let mut r = Cursor::new(&b"line one\nline two is long\n"[..]);
+    let mut s = String::new();
+    assert_eq!(r.read_line_limited(&mut s, 9).unwrap(), 9);
+    assert_eq!(s, "line one\n");
+    let err = r.read_line_limited(&mut s, 9).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::FileTooLarge);
+    assert_eq!(s, "line one\n");
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn lines_limited() {
+    let data = b"short\r\nthis line is too long\nok\n\nlast";
+    let lines: Vec<_> = BufReader::with_capacity(4, &data[..])
+        .lines_limited(8)
+        .map(|l| l.map_err(|e| e.kind()))
+        .collect();
+    assert_eq!(
+        lines,
+        [
+            Ok("short".to_string()),
+            Err(ErrorKind::FileTooLarge),
+            Ok("ok".to_string()),
+            Ok("".to_string()),
+            Ok("last".to_string()),
+        ]
+    );
+
+    // A limit of `usize::MAX` behaves like `lines`.
+    let lines: Vec<_> = Cursor::new(&data[..])
+        .lines_limited(usize::MAX)
+        .collect::<io::Result<_>>()
+        .unwrap();
+    assert_eq!(lines.len(), 5);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn split_limited() {
+    let segments: Vec<_> = Cursor::new(&b"a-bcdef-gh-"[..])
+        .split_limited(b'-', 3)
+        .map(|s| s.map_err(|e| e.kind()))
+        .collect();
+    assert_eq!(
+        segments,
+        [
+            Ok(b"a".to_vec()),
+            Err(ErrorKind::FileTooLarge),
+            Ok(b"gh".to_vec()),
+        ]
+    );
+}
@@ -0,0 +1,242 @@
+//! Variable-length integers in the LEB128 encoding.
+//!
+//! Unsigned integers are written seven bits at a time, least significant
+//! group first, with the high bit of each byte set while more bytes follow.
+//! Signed integers are first zigzag-mapped (`0, -1, 1, -2, ...` to
+//! `0, 1, 2, 3, ...`) so that small negative numbers stay short.
+//!
+//! Decoding is strict: an encoding longer than necessary, or one whose value
+//! does not fit in the requested type, fails with [`ErrorKind::InvalidData`].
+//!
+//! ```no_run
+//! use ckb_rust_std::io::{self, varint::{BufReadVarint, WriteVarint}};
+//!
+//! fn main() -> io::Result<()> {
+//!     let mut buf = [0u8; 8];
+//!     let mut writer = &mut buf[..];
+//!     writer.write_varint(624485u32)?;
+//!     writer.write_varint(-2i64)?;
+//!
+//!     let mut reader = &buf[..];
+//!     assert_eq!(reader.read_varint_buffered::<u32>()?, 624485);
+//!     assert_eq!(reader.read_varint_buffered::<i64>()?, -2);
+//!     Ok(())
+//! }
+//! ```
+//!
+//! [`ErrorKind::InvalidData`]: crate::io::ErrorKind::InvalidData
+#[cfg(test)]
+mod tests;
+
+use crate::io::{self, const_io_error, BufRead, ErrorKind, Read, Write};
+
+/// The length of the longest encoding of any [`Varint`], a `u128` or `i128`.
+pub const MAX_VARINT_LEN: usize = 19;
+
+const OVERLONG: io::Error = const_io_error!(
+    ErrorKind::InvalidData,
+    "varint is longer than its minimal encoding",
+);
+const OVERFLOW: io::Error = const_io_error!(
+    ErrorKind::InvalidData,
+    "varint does not fit in the target integer type",
+);
+
+mod private {
+    pub trait Sealed: Copy {
+        const BITS: u32;
+
+        // The unsigned (for signed types, zigzag-mapped) value.
+        fn to_raw(self) -> u128;
+
+        // The inverse of `to_raw`; `raw` always fits in `BITS` bits.
+        fn from_raw(raw: u128) -> Self;
+    }
+}
+
+/// An integer type which can be read and written as a varint.
+///
+/// This trait is sealed: it is implemented for `u16` through `u128` and
+/// `i16` through `i128`.
+pub trait Varint: private::Sealed {
+    /// The length of the longest encoding of this type.
+    const MAX_LEN: usize = (Self::BITS as usize).div_ceil(7);
+}
+
+macro_rules! impl_varint {
+    ($($unsigned:ident, $signed:ident;)*) => {$(
+        impl private::Sealed for $unsigned {
+            const BITS: u32 = $unsigned::BITS;
+
+            #[inline]
+            fn to_raw(self) -> u128 {
+                self as u128
+            }
+
+            #[inline]
+            fn from_raw(raw: u128) -> $unsigned {
+                raw as $unsigned
+            }
+        }
+
+        impl Varint for $unsigned {}
+
+        impl private::Sealed for $signed {
+            const BITS: u32 = $signed::BITS;
+
+            #[inline]
+            fn to_raw(self) -> u128 {
+                ((self << 1) ^ (self >> ($signed::BITS - 1))) as $unsigned as u128
+            }
+
+            #[inline]
+            fn from_raw(raw: u128) -> $signed {
+                let raw = raw as $unsigned;
+                (raw >> 1) as $signed ^ -((raw & 1) as $signed)
+            }
+        }
+
+        impl Varint for $signed {}
+    )*};
+}
+
+impl_varint! {
+    u16, i16;
+    u32, i32;
+    u64, i64;
+    u128, i128;
+}
+
+/// Accumulates the bytes of one varint.
+struct Decoder {
+    value: u128,
+    shift: u32,
+    bits: u32,
+}
+
+impl Decoder {
+    fn new(bits: u32) -> Decoder {
+        Decoder {
+            value: 0,
+            shift: 0,
+            bits,
+        }
+    }
+
+    /// Adds the next byte, returning `true` once the varint is complete.
+    #[inline]
+    fn push(&mut self, byte: u8) -> io::Result<bool> {
+        let payload = (byte & 0x7f) as u128;
+        let last = byte & 0x80 == 0;
+        if self.shift + 7 >= self.bits {
+            // The final byte the type allows: it must end the varint and
+            // carry no bits beyond the width of the type.
+            if !last || payload >> (self.bits - self.shift) != 0 {
+                return Err(OVERFLOW);
+            }
+        }
+        if last && byte == 0 && self.shift > 0 {
+            return Err(OVERLONG);
+        }
+        self.value |= payload << self.shift;
+        self.shift += 7;
+        Ok(last)
+    }
+}
+
+/// Extends [`Read`] with a method for reading varints.
+///
+/// The varint is read one byte at a time, so nothing past its end is
+/// consumed. Prefer [`BufReadVarint`] when the reader is buffered.
+pub trait ReadVarint: Read {
+    /// Reads a varint.
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`ErrorKind::InvalidData`] if the encoding is overlong or
+    /// the value does not fit in `T`, and with [`ErrorKind::UnexpectedEof`]
+    /// if the reader ends before the varint does.
+    #[cfg_attr(feature = "error_location", track_caller)]
+    fn read_varint<T: Varint>(&mut self) -> io::Result<T> {
+        let mut decoder = Decoder::new(T::BITS);
+        loop {
+            let mut byte = [0u8];
+            self.read_exact(&mut byte)?;
+            if decoder.push(byte[0])? {
+                return Ok(T::from_raw(decoder.value));
+            }
+        }
+    }
+}
+
+/// Extends [`BufRead`] with a method for reading varints directly out of
+/// the buffer.
+pub trait BufReadVarint: BufRead {
+    /// Reads a varint, decoding it straight from [`fill_buf`] and consuming
+    /// exactly its bytes.
+    ///
+    /// Errors are the same as for [`ReadVarint::read_varint`]. On an invalid
+    /// encoding, the bytes up to and including the offending one are
+    /// consumed.
+    ///
+    /// [`fill_buf`]: BufRead::fill_buf
+    #[cfg_attr(feature = "error_location", track_caller)]
+    fn read_varint_buffered<T: Varint>(&mut self) -> io::Result<T> {
+        let mut decoder = Decoder::new(T::BITS);
+        loop {
+            let available = match self.fill_buf() {
+                Ok(buf) => buf,
+                Err(e) if e.is_interrupted() => continue,
+                Err(e) => return Err(e),
+            };
+            if available.is_empty() {
+                return Err(io::Error::READ_EXACT_EOF.at_caller());
+            }
+            let mut used = 0;
+            let mut result = Ok(false);
+            for &byte in available {
+                used += 1;
+                result = decoder.push(byte);
+                if !matches!(result, Ok(false)) {
+                    break;
+                }
+            }
+            self.consume(used);
+            if result? {
+                return Ok(T::from_raw(decoder.value));
+            }
+        }
+    }
+}
+
+/// Extends [`Write`] with a method for writing varints.
+pub trait WriteVarint: Write {
+    /// Writes `n` as a varint in its minimal encoding.
+    fn write_varint<T: Varint>(&mut self, n: T) -> io::Result<()> {
+        let mut buf = [0u8; MAX_VARINT_LEN];
+        let len = encode(n, &mut buf);
+        self.write_all(&buf[..len])
+    }
+}
+
+impl<R: Read + ?Sized> ReadVarint for R {}
+
+impl<R: BufRead + ?Sized> BufReadVarint for R {}
+
+impl<W: Write + ?Sized> WriteVarint for W {}
+
+/// Encodes `n` into the front of `buf`, returning the encoded length.
+pub fn encode<T: Varint>(n: T, buf: &mut [u8; MAX_VARINT_LEN]) -> usize {
+    let mut raw = n.to_raw();
+    let mut len = 0;
+    loop {
+        let byte = (raw & 0x7f) as u8;
+        raw >>= 7;
+        if raw == 0 {
+            buf[len] = byte;
+            return len + 1;
+        }
+        buf[len] = byte | 0x80;
+        len += 1;
+    }
+}
@@ -0,0 +1,124 @@
+use super::*;
+#[cfg(feature = "alloc")]
+use crate::io::BufReader;
+use crate::io::ErrorKind;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+#[cfg(feature = "alloc")]
+fn encoded<T: Varint>(n: T) -> Vec<u8> {
+    let mut buf = Vec::new();
+    buf.write_varint(n).unwrap();
+    buf
+}
+
+/// Decodes `data` through both the byte-at-a-time and the buffered path,
+/// checking that they agree and that the whole input is consumed.
+fn decode<T: Varint + PartialEq + core::fmt::Debug>(data: &[u8]) -> io::Result<T> {
+    let mut reader = data;
+    let unbuffered = ReadVarint::read_varint::<T>(&mut reader);
+    let mut buffered_reader = data;
+    let buffered = buffered_reader.read_varint_buffered::<T>();
+    match (&unbuffered, &buffered) {
+        (Ok(a), Ok(b)) => {
+            assert_eq!(a, b);
+            assert!(reader.is_empty() && buffered_reader.is_empty());
+        }
+        (Err(a), Err(b)) => assert_eq!(a.kind(), b.kind()),
+        _ => panic!("{unbuffered:?} {buffered:?}"),
+    }
+    // A tiny buffer makes the varint straddle refills.
+    #[cfg(feature = "alloc")]
+    {
+        let mut tiny = BufReader::with_capacity(1, data);
+        let straddling = tiny.read_varint_buffered::<T>();
+        match (&unbuffered, &straddling) {
+            (Ok(a), Ok(c)) => assert_eq!(a, c),
+            (Err(a), Err(c)) => assert_eq!(a.kind(), c.kind()),
+            _ => panic!("{unbuffered:?} {straddling:?}"),
+        }
+    }
+    unbuffered
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn unsigned_vectors() {
+    assert_eq!(encoded(0u32), [0x00]);
+    assert_eq!(encoded(127u16), [0x7f]);
+    assert_eq!(encoded(128u16), [0x80, 0x01]);
+    assert_eq!(encoded(624485u32), [0xe5, 0x8e, 0x26]);
+    assert_eq!(encoded(u16::MAX), [0xff, 0xff, 0x03]);
+    assert_eq!(encoded(u64::MAX).len(), u64::MAX_LEN);
+    assert_eq!(encoded(u128::MAX).len(), MAX_VARINT_LEN);
+
+    assert_eq!(decode::<u32>(&[0xe5, 0x8e, 0x26]).unwrap(), 624485);
+    assert_eq!(decode::<u16>(&[0xff, 0xff, 0x03]).unwrap(), u16::MAX);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn signed_zigzag() {
+    assert_eq!(encoded(0i32), [0x00]);
+    assert_eq!(encoded(-1i32), [0x01]);
+    assert_eq!(encoded(1i32), [0x02]);
+    assert_eq!(encoded(-64i16), [0x7f]);
+    assert_eq!(encoded(64i16), [0x80, 0x01]);
+    assert_eq!(decode::<i16>(&[0x7f]).unwrap(), -64);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn round_trip_extremes() {
+    macro_rules! check {
+        ($($ty:ident),*) => {$(
+            for n in [$ty::MIN, $ty::MIN + 1, 0, 1, $ty::MAX - 1, $ty::MAX] {
+                assert_eq!(decode::<$ty>(&encoded(n)).unwrap(), n);
+            }
+        )*};
+    }
+    check!(u16, u32, u64, u128, i16, i32, i64, i128);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn stops_at_end_of_varint() {
+    let mut data = encoded(300u32);
+    data.extend_from_slice(&[0xaa]);
+    let mut reader = &data[..];
+    assert_eq!(reader.read_varint_buffered::<u32>().unwrap(), 300);
+    assert_eq!(reader, [0xaa]);
+}
+
+#[test]
+fn reject_overlong() {
+    for data in [
+        &[0x80, 0x00][..],
+        &[0x81, 0x80, 0x00],
+        &[0xff, 0x80, 0x80, 0x00],
+    ] {
+        let err = decode::<u64>(data).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData, "{data:?}");
+    }
+}
+
+#[test]
+fn reject_overflow() {
+    // One bit past u16::MAX, and too many bytes for a u16.
+    for data in [&[0xff, 0xff, 0x04][..], &[0xff, 0xff, 0x83, 0x00]] {
+        let err = decode::<u16>(data).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData, "{data:?}");
+    }
+    // u64::MAX + 1.
+    let data = [0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x02];
+    let err = decode::<u64>(&data).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+}
+
+#[test]
+fn truncated() {
+    for data in [&[][..], &[0x80], &[0xff, 0xff]] {
+        let err = decode::<u32>(data).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::UnexpectedEof, "{data:?}");
+    }
+}
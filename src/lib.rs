@@ -2,7 +2,13 @@
 #![no_std]
 #![allow(clippy::doc_lazy_continuation)]
 
+#[cfg(feature = "alloc")]
 extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+#[cfg(all(feature = "std", feature = "rust_before_181"))]
+compile_error!("the `std` feature needs Rust 1.87 and cannot be combined with `rust_before_181`");
 
 #[cfg(not(doctest))]
 pub mod io;